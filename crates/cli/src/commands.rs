@@ -1,5 +1,8 @@
+pub(crate) mod remote;
 pub(crate) mod server;
 
+use annapurna_data::dietary::{Diet, Tag};
+use remote::RemoteCommand;
 use server::ServerCommand;
 
 #[derive(clap::Parser, Debug)]
@@ -14,6 +17,8 @@ pub(crate) enum Commands {
     Command(Command),
     /// commands for running the server
     Server(ServerCommand),
+    /// commands for using a running server
+    Remote(RemoteCommand),
 }
 
 #[derive(clap::Args, Debug)]
@@ -24,5 +29,27 @@ pub(crate) struct Command {
 
 #[derive(clap::Subcommand, Debug)]
 pub(crate) enum BasicCommands {
-    Run,
+    Run {
+        /// allow ingredients to be replaced by a more general one, such as `onion` for `onion/green`
+        #[arg(long)]
+        approximate: bool,
+        /// the most ingredients to suggest buying
+        #[arg(long)]
+        max_items: Option<usize>,
+        /// leave out recipes that do not suit a diet, such as `vegetarian` or `vegan`
+        #[arg(long)]
+        diet: Vec<Diet>,
+        /// leave out recipes with ingredients that contain something, such as `nut` or `gluten`
+        #[arg(long)]
+        avoid: Vec<Tag>,
+    },
+    /// suggest recipes that use up the inventory that is about to expire
+    UseItUp {
+        /// how many days ahead to look for expiring ingredients
+        #[arg(long, default_value_t = 3)]
+        days: i64,
+        /// the most ingredients a suggested recipe may be missing
+        #[arg(long, default_value_t = 1)]
+        max_missing: usize,
+    },
 }
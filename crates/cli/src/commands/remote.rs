@@ -0,0 +1,311 @@
+use annapurna::config::ClientConfig;
+use annapurna_client::{
+    types::{
+        CreateInventory, Diet, DietaryProfile, InventoryQuery, PageQuery, RankingQuery,
+        SuggestionQuery, Tag, Ulid,
+    },
+    Client,
+};
+use chrono::{DateTime, Utc};
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct RemoteCommand {
+    #[clap(subcommand)]
+    pub command: RemoteCommands,
+}
+
+/// A command run against the API of a running server
+#[derive(clap::Subcommand, Debug)]
+pub(crate) enum RemoteCommands {
+    /// check that the server is up
+    Health,
+    /// manage the household's inventory
+    Inventory {
+        #[clap(subcommand)]
+        command: InventoryCommands,
+    },
+    /// browse the recipes
+    Recipes {
+        #[clap(subcommand)]
+        command: RecipeCommands,
+    },
+    /// show or change what you will not eat
+    Diet {
+        #[clap(subcommand)]
+        command: DietCommands,
+    },
+    /// list the household's shopping lists
+    ShoppingLists,
+    /// list the households you belong to
+    Households,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub(crate) enum InventoryCommands {
+    /// list the inventory
+    List {
+        /// only list this ingredient, or a whole category such as `cheese/*`
+        #[arg(long)]
+        ingredient_type: Option<String>,
+    },
+    /// add an item to the inventory
+    Add {
+        ingredient_type: String,
+        quantity: String,
+        /// when the item expires, such as `2024-04-01T00:00:00Z`
+        #[arg(long)]
+        expires: Option<DateTime<Utc>>,
+    },
+    /// remove an item from the inventory
+    Remove { inventory_id: Ulid },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub(crate) enum RecipeCommands {
+    /// list the recipes
+    List,
+    /// show the ingredients of a recipe
+    Show { name: String },
+    /// suggest recipes that use up the inventory that is about to expire
+    Suggest {
+        /// how many days ahead to look for expiring ingredients
+        #[arg(long, default_value_t = 3)]
+        days: i64,
+        /// the most ingredients a suggested recipe may be missing
+        #[arg(long, default_value_t = 1)]
+        max_missing: usize,
+    },
+    /// rank the recipes by what is missing to make them
+    Rank {
+        /// the most ingredients to suggest buying
+        #[arg(long)]
+        max_items: Option<usize>,
+    },
+    /// list the recipes your dietary profile rules out, and why
+    Dietary,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub(crate) enum DietCommands {
+    /// show your dietary profile
+    Show,
+    /// replace your dietary profile
+    Set {
+        /// a diet you follow, such as `vegetarian` or `vegan`
+        #[arg(long)]
+        diet: Vec<Diet>,
+        /// something you do not eat, such as `nut` or `gluten`
+        #[arg(long)]
+        avoid: Vec<Tag>,
+    },
+}
+
+impl RemoteCommand {
+    pub(crate) async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let config = ClientConfig::load()?;
+
+        let mut client = Client::new(&config.api_url)?;
+        client.set_token(config.token);
+        client.set_household(config.household_id);
+
+        match &self.command {
+            RemoteCommands::Health => println!("{}", client.health().await?),
+            RemoteCommands::Inventory { command } => command.run(&client).await?,
+            RemoteCommands::Recipes { command } => command.run(&client).await?,
+            RemoteCommands::Diet { command } => command.run(&client).await?,
+            RemoteCommands::ShoppingLists => {
+                let mut query = PageQuery::default();
+                loop {
+                    let page = client.list_shopping_lists(&query).await?;
+                    for list in page.items {
+                        let checked = list.items.iter().filter(|item| item.checked).count();
+                        println!(
+                            "{} {} ({checked}/{} bought)",
+                            list.shopping_list_id,
+                            list.name,
+                            list.items.len()
+                        );
+                    }
+
+                    match page.next {
+                        Some(next) => query.after = Some(next),
+                        None => break,
+                    }
+                }
+            }
+            RemoteCommands::Households => {
+                for membership in client.list_households().await? {
+                    let household = membership.household;
+                    println!(
+                        "{} {} ({:?})",
+                        household.household_id, household.name, membership.role
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl InventoryCommands {
+    async fn run(&self, client: &Client) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            InventoryCommands::List { ingredient_type } => {
+                let mut query = InventoryQuery {
+                    ingredient_type: ingredient_type.clone(),
+                    ..Default::default()
+                };
+                loop {
+                    let page = client.list_inventory(&query).await?;
+                    for item in page.items {
+                        print!(
+                            "{} {} {}",
+                            item.inventory_id, item.ingredient_type, item.quantity
+                        );
+                        if let Some(expiration_date) = item.expiration_date {
+                            print!(", expires {}", expiration_date.date_naive());
+                        }
+                        println!();
+                    }
+
+                    match page.next {
+                        Some(next) => query.after = Some(next),
+                        None => break,
+                    }
+                }
+            }
+            InventoryCommands::Add {
+                ingredient_type,
+                quantity,
+                expires,
+            } => {
+                let item = client
+                    .create_inventory(&CreateInventory {
+                        inventory_id: None,
+                        ingredient_type: ingredient_type.clone(),
+                        quantity: quantity.clone(),
+                        expiration_date: *expires,
+                    })
+                    .await?;
+                println!("added {}", item.inventory_id);
+            }
+            InventoryCommands::Remove { inventory_id } => {
+                client.delete_inventory(*inventory_id).await?;
+                println!("removed {inventory_id}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RecipeCommands {
+    async fn run(&self, client: &Client) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            RecipeCommands::List => {
+                let mut query = PageQuery::default();
+                loop {
+                    let page = client.list_recipes(&query).await?;
+                    for recipe in page.items {
+                        println!("{}", recipe.name);
+                    }
+
+                    match page.next {
+                        Some(next) => query.after = Some(next),
+                        None => break,
+                    }
+                }
+            }
+            RecipeCommands::Show { name } => {
+                let recipe = client.get_recipe(name).await?;
+                println!("{} ({} servings)", recipe.name, recipe.servings);
+                for ingredient in recipe.ingredients {
+                    match ingredient.quantity {
+                        Some(quantity) => println!("  {} {quantity}", ingredient.ingredient_type),
+                        None => println!("  {}", ingredient.ingredient_type),
+                    }
+                }
+            }
+            RecipeCommands::Suggest { days, max_missing } => {
+                let query = SuggestionQuery {
+                    days: Some(*days),
+                    max_missing: Some(*max_missing),
+                };
+
+                println!("Use it up:");
+                for suggestion in client.suggest_recipes(&query).await? {
+                    let uses = suggestion.uses.join(", ");
+                    print!(
+                        "  {} ({:.2}): uses {uses}",
+                        suggestion.recipe, suggestion.score
+                    );
+                    if !suggestion.missing.is_empty() {
+                        print!(", needs {}", suggestion.missing.join(", "));
+                    }
+                    println!();
+                }
+            }
+            RecipeCommands::Rank { max_items } => {
+                let query = RankingQuery {
+                    max_items: *max_items,
+                    ..Default::default()
+                };
+                let ranking = client.rank_recipes(&query).await?;
+
+                println!("Missing:");
+                for ranked in ranking.ranked {
+                    let missing = ranked.missing.join(", ");
+                    println!("  {} ({}): {missing}", ranked.recipe, ranked.cost);
+                }
+
+                println!("Shopping suggestions:");
+                for step in ranking.shopping {
+                    let buy = step.buy.join(", ");
+                    let unlocks = step.unlocks.join(", ");
+                    println!("  buy {buy} to make {unlocks}");
+                }
+            }
+            RecipeCommands::Dietary => {
+                let check = client.check_diet().await?;
+                println!("Allowed: {}", check.allowed.join(", "));
+
+                println!("Ruled out:");
+                let mut forbidden = check.forbidden.into_iter().collect::<Vec<_>>();
+                forbidden.sort_by(|a, b| a.0.cmp(&b.0));
+                for (recipe, ingredients) in forbidden {
+                    let ingredients = ingredients
+                        .iter()
+                        .map(|forbidden| format!("{} ({})", forbidden.ingredient, forbidden.tag))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    println!("  {recipe}: {ingredients}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl DietCommands {
+    async fn run(&self, client: &Client) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = match self {
+            DietCommands::Show => client.dietary_profile().await?,
+            DietCommands::Set { diet, avoid } => {
+                let profile = DietaryProfile {
+                    diets: diet.clone(),
+                    avoid: avoid.clone(),
+                };
+                client.update_dietary_profile(&profile).await?
+            }
+        };
+
+        let diets = profile.diets.iter().map(Diet::as_str).collect::<Vec<_>>();
+        let avoid = profile.avoid.iter().map(Tag::as_str).collect::<Vec<_>>();
+        println!("diets: {}", diets.join(", "));
+        println!("avoid: {}", avoid.join(", "));
+
+        Ok(())
+    }
+}
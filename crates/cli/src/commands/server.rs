@@ -1,6 +1,8 @@
 use annapurna::config::Config;
-use annapurna_data::Facts;
+use annapurna_data::{nutrition, Facts};
+use annapurna_models::nutrition::IngredientNutrition;
 use lockpad_auth::PublicKey;
+use std::path::{Path, PathBuf};
 
 #[derive(clap::Args, Debug)]
 pub(crate) struct ServerCommand {
@@ -16,6 +18,11 @@ pub(crate) struct ServerCommand {
 pub(crate) enum ServerCommands {
     /// start the http server
     Http,
+    /// store the nutrition of ingredients from a CSV file, replacing what was stored for them
+    ImportNutrition {
+        /// a CSV file with an `ingredient` column and nutrients per 100 g, like `facts/nutrition.csv`
+        path: PathBuf,
+    },
 }
 
 impl ServerCommand {
@@ -27,6 +34,17 @@ impl ServerCommand {
             .connect(&config.postgres_url)
             .await?;
 
+        match &self.command {
+            ServerCommands::Http => self.serve(config, pg_pool).await,
+            ServerCommands::ImportNutrition { path } => import_nutrition(&pg_pool, path).await,
+        }
+    }
+
+    async fn serve(
+        &self,
+        config: Config,
+        pg_pool: sqlx::pool::Pool<sqlx::Postgres>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // Load auth keys
         let auth_url = config.auth_url;
         let client = reqwest::Client::new();
@@ -49,12 +67,28 @@ impl ServerCommand {
             .auth_url(auth_url.clone())
             .auth_app_id(config.auth_app_id.clone())
             .facts(facts)
+            .require_known_ingredients(config.require_known_ingredients)
             .build()?;
 
-        match self.command {
-            ServerCommands::Http => server.run().await?,
-        };
+        server.run().await?;
 
         Ok(())
     }
 }
+
+async fn import_nutrition(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dataset = nutrition::read_csv(&std::fs::read_to_string(path)?)?;
+
+    let updated_at = chrono::Utc::now();
+    let items = dataset
+        .into_iter()
+        .map(|(ingredient, nutrition)| IngredientNutrition::new(ingredient, nutrition, updated_at))
+        .collect::<Vec<_>>();
+    IngredientNutrition::save_all(pg_pool, &items).await?;
+
+    println!("imported the nutrition of {} ingredients", items.len());
+    Ok(())
+}
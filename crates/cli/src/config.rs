@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use annapurna_client::types::Ulid;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -15,6 +16,9 @@ pub struct Config {
     pub facts_path: PathBuf,
     /// URL to postgres database
     pub postgres_url: String,
+    /// Whether to reject requests referring to ingredients missing from the facts and recipes
+    #[serde(default)]
+    pub require_known_ingredients: bool,
 }
 
 impl Config {
@@ -27,6 +31,32 @@ impl Config {
     }
 }
 
+/// Settings for talking to a running server, loaded from the same environment as [`Config`].
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ClientConfig {
+    /// URL the API is served at
+    #[serde(default = "default_api_url")]
+    pub api_url: String,
+    /// Token to authenticate with
+    pub token: Option<String>,
+    /// Household to act on instead of the personal one
+    pub household_id: Option<Ulid>,
+}
+
+impl ClientConfig {
+    pub fn load() -> Result<Self, config::ConfigError> {
+        let config = config::Config::builder()
+            .add_source(config::Environment::with_prefix("ANNAPURNA"))
+            .build()?;
+
+        config.try_deserialize()
+    }
+}
+
+fn default_api_url() -> String {
+    "http://localhost:3000/api".to_string()
+}
+
 fn default_static_path() -> PathBuf {
     PathBuf::from(".")
 }
@@ -1,5 +1,10 @@
-use annapurna_data::Facts;
-use annapurna_logic::recipe;
+use annapurna_data::{dietary::DietaryProfile, Facts};
+use annapurna_logic::{
+    expiration::use_it_up,
+    program::AscentProgram,
+    ranking::{rank, shopping_suggestions},
+    recipe::RecipeManager,
+};
 use clap::Parser;
 
 pub mod commands;
@@ -12,17 +17,100 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = commands::Args::parse();
     match args.command {
         Commands::Command(command) => match command.command {
-            BasicCommands::Run => {
+            BasicCommands::Run {
+                approximate,
+                max_items,
+                diet,
+                avoid,
+            } => {
                 let facts = Facts::read_from_directory("facts")?;
 
-                let res = recipe(facts.recipes, facts.inventory);
+                let profile = DietaryProfile { diets: diet, avoid };
+                let res = RecipeManager::from_facts(&facts, facts.inventory.clone())
+                    .approximate(approximate)
+                    .profile(profile)
+                    .process();
+                let ranked = rank(&res, &facts.prices);
+                let shopping = shopping_suggestions(&res, &facts.prices, max_items);
+                let approximate = res
+                    .can_make
+                    .iter()
+                    .filter(|recipe| res.is_approximate(recipe))
+                    .cloned()
+                    .collect::<Vec<_>>();
                 let can_make = res.can_make;
-                let missing = res.missing;
+                let production_chains = res.production_chains;
+                let substitutions = res.substitutions;
                 println!("Can make: {can_make:?}");
-                println!("Missing: {missing:?}");
+                if !approximate.is_empty() {
+                    println!("Approximately: {approximate:?}");
+                }
+                println!("Prepare first: {production_chains:?}");
+                println!("With substitutions: {substitutions:?}");
+                if !res.forbidden.is_empty() {
+                    println!("Ruled out:");
+                    let mut forbidden = res.forbidden.into_iter().collect::<Vec<_>>();
+                    forbidden.sort_by(|a, b| a.0.cmp(&b.0));
+                    for (recipe, ingredients) in forbidden {
+                        let ingredients = ingredients
+                            .iter()
+                            .map(|forbidden| {
+                                format!("{} ({})", forbidden.ingredient, forbidden.tag)
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        println!("  {recipe}: {ingredients}");
+                    }
+                }
+
+                println!("Missing:");
+                for ranked in ranked {
+                    let missing = ranked.missing.join(", ");
+                    println!("  {} ({}): {missing}", ranked.recipe, ranked.cost);
+                }
+
+                println!("Shopping suggestions:");
+                for step in shopping {
+                    let buy = step.buy.join(", ");
+                    let unlocks = step.unlocks.join(", ");
+                    println!("  buy {buy} to make {unlocks}");
+                }
+            }
+            BasicCommands::UseItUp { days, max_missing } => {
+                let facts = Facts::read_from_directory("facts")?;
+
+                let cutoff = chrono::Local::now().date_naive() + chrono::Duration::days(days);
+                let expiring = facts
+                    .inventory
+                    .iter()
+                    .filter(|ingredient| {
+                        facts
+                            .expirations
+                            .get(&ingredient.name)
+                            .is_some_and(|expiration| *expiration <= cutoff)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let manager = RecipeManager::from_facts(&facts, facts.inventory.clone());
+                let suggestions = use_it_up(&manager, &expiring, max_missing);
+
+                println!("Use it up:");
+                for suggestion in suggestions {
+                    let uses = suggestion.uses.join(", ");
+                    print!(
+                        "  {} ({:.2}): uses {uses}",
+                        suggestion.recipe, suggestion.score
+                    );
+                    if !suggestion.missing.is_empty() {
+                        print!(", needs {}", suggestion.missing.join(", "));
+                    }
+                    println!();
+                }
             }
         },
         Commands::Server(server) => server.run().await?,
+        Commands::Remote(remote) => remote.run().await?,
     }
 
     Ok(())
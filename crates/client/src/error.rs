@@ -0,0 +1,32 @@
+use crate::types::{ErrorBody, ErrorCode, FieldError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("invalid base url: {0}")]
+    InvalidUrl(String),
+    /// The server answered with an error.
+    #[error("{status} {}: {}", .body.code, .body.message)]
+    Api { status: u16, body: ErrorBody },
+}
+
+impl Error {
+    /// The reason the server gave for rejecting the request, if it answered at all.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::Api { body, .. } => Some(body.code),
+            _ => None,
+        }
+    }
+
+    /// The problems with each field of the request, for validation errors.
+    pub fn fields(&self) -> &[FieldError] {
+        match self {
+            Error::Api { body, .. } => &body.fields,
+            _ => &[],
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
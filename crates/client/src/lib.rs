@@ -0,0 +1,606 @@
+//! A typed client for the annapurna API, usable both natively and from wasm32.
+//!
+//! The routes meant for browsers (`/`, `/login` and `/submit`) are not wrapped.
+use reqwest::{Method, RequestBuilder, Response, Url};
+use serde::de::DeserializeOwned;
+
+pub mod error;
+pub mod types;
+
+use error::{Error, Result};
+use types::*;
+
+/// The header selecting the household a request acts on.
+pub const HOUSEHOLD_HEADER: &str = "x-household-id";
+
+#[derive(Clone, Debug)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    token: Option<String>,
+    household_id: Option<Ulid>,
+}
+
+impl Client {
+    /// Creates a client for the API served at `base_url`, such as `https://example.com/api`.
+    pub fn new(base_url: &str) -> Result<Self> {
+        let base_url =
+            Url::parse(base_url).map_err(|error| Error::InvalidUrl(error.to_string()))?;
+        if base_url.cannot_be_a_base() {
+            return Err(Error::InvalidUrl(base_url.to_string()));
+        }
+
+        Ok(Self {
+            http: reqwest::Client::new(),
+            base_url,
+            token: None,
+            household_id: None,
+        })
+    }
+
+    /// Sends the token as a bearer token with every request.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Acts on the given household, instead of the caller's personal one.
+    pub fn household(mut self, household_id: Ulid) -> Self {
+        self.household_id = Some(household_id);
+        self
+    }
+
+    pub fn set_token(&mut self, token: Option<String>) {
+        self.token = token;
+    }
+
+    pub fn set_household(&mut self, household_id: Option<Ulid>) {
+        self.household_id = household_id;
+    }
+
+    /// The URL of a route, with each segment percent-encoded.
+    pub fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("base urls are checked when the client is created")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    fn request(&self, method: Method, segments: &[&str]) -> RequestBuilder {
+        let mut request = self.http.request(method, self.url(segments));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(household_id) = self.household_id {
+            request = request.header(HOUSEHOLD_HEADER, household_id.to_string());
+        }
+        request
+    }
+
+    pub async fn health(&self) -> Result<String> {
+        let response = send(self.request(Method::GET, &["health"])).await?;
+        Ok(response.text().await?)
+    }
+
+    /// The OpenAPI document describing the API.
+    pub async fn openapi(&self) -> Result<serde_json::Value> {
+        json(self.request(Method::GET, &["openapi.json"])).await
+    }
+
+    /// The ingredients of the shared recipes.
+    pub async fn ingredients(&self) -> Result<Vec<annapurna_data::types::Ingredient>> {
+        json(self.request(Method::GET, &["ingredients"])).await
+    }
+
+    /// The nutrition of the ingredients known to the server.
+    pub async fn nutrition(&self) -> Result<Vec<IngredientNutrition>> {
+        json(self.request(Method::GET, &["nutrition"])).await
+    }
+
+    pub async fn list_inventory(&self, query: &InventoryQuery) -> Result<Page<Inventory>> {
+        json(self.request(Method::GET, &["inventory"]).query(query)).await
+    }
+
+    pub async fn create_inventory(&self, item: &CreateInventory) -> Result<Inventory> {
+        json(self.request(Method::POST, &["inventory"]).json(item)).await
+    }
+
+    /// Applies every operation, or none of them if any fails.
+    /// Either way the outcome of each operation is returned.
+    pub async fn bulk_inventory(&self, operations: &[BulkOperation]) -> Result<BulkResponse> {
+        let response = self
+            .request(Method::POST, &["inventory", "bulk"])
+            .json(operations)
+            .send()
+            .await?;
+
+        // a rolled back batch still describes what happened to each operation
+        match response.status().as_u16() {
+            200 | 422 => Ok(response.json().await?),
+            _ => Err(api_error(response).await),
+        }
+    }
+
+    pub async fn get_inventory(&self, inventory_id: Ulid) -> Result<Inventory> {
+        let id = inventory_id.to_string();
+        json(self.request(Method::GET, &["inventory", &id])).await
+    }
+
+    pub async fn update_inventory(
+        &self,
+        inventory_id: Ulid,
+        changes: &UpdateInventory,
+    ) -> Result<Inventory> {
+        let id = inventory_id.to_string();
+        json(
+            self.request(Method::PATCH, &["inventory", &id])
+                .json(changes),
+        )
+        .await
+    }
+
+    pub async fn delete_inventory(&self, inventory_id: Ulid) -> Result<()> {
+        let id = inventory_id.to_string();
+        empty(self.request(Method::DELETE, &["inventory", &id])).await
+    }
+
+    /// A page of the shared recipes along with those of the household.
+    pub async fn list_recipes(&self, query: &PageQuery) -> Result<Page<Recipe>> {
+        json(self.request(Method::GET, &["recipes"]).query(query)).await
+    }
+
+    pub async fn create_recipe(&self, recipe: &CreateRecipe) -> Result<Recipe> {
+        json(self.request(Method::POST, &["recipes"]).json(recipe)).await
+    }
+
+    pub async fn rank_recipes(&self, query: &RankingQuery) -> Result<Ranking> {
+        json(
+            self.request(Method::GET, &["recipes", "ranking"])
+                .query(query),
+        )
+        .await
+    }
+
+    /// Plans the steps of the recipes backwards from when they are served,
+    /// adding a task for each step to the household's todo list.
+    pub async fn schedule_recipes(&self, meal: &ScheduleMeal) -> Result<Vec<Task>> {
+        json(
+            self.request(Method::POST, &["recipes", "schedule"])
+                .json(meal),
+        )
+        .await
+    }
+
+    pub async fn suggest_recipes(&self, query: &SuggestionQuery) -> Result<Vec<Suggestion>> {
+        json(
+            self.request(Method::GET, &["recipes", "suggestions"])
+                .query(query),
+        )
+        .await
+    }
+
+    pub async fn get_recipe(&self, name: &str) -> Result<Recipe> {
+        json(self.request(Method::GET, &["recipes", name])).await
+    }
+
+    pub async fn update_recipe(&self, name: &str, recipe: &UpdateRecipe) -> Result<Recipe> {
+        json(self.request(Method::PUT, &["recipes", name]).json(recipe)).await
+    }
+
+    pub async fn delete_recipe(&self, name: &str) -> Result<()> {
+        empty(self.request(Method::DELETE, &["recipes", name])).await
+    }
+
+    /// Cooks a recipe, taking its ingredients out of the inventory.
+    pub async fn cook_recipe(&self, name: &str, cook: &CookRecipe) -> Result<Cooked> {
+        json(
+            self.request(Method::POST, &["recipes", name, "cook"])
+                .json(cook),
+        )
+        .await
+    }
+
+    /// Splits the recipes into those that suit the caller's dietary profile and those it rules out.
+    pub async fn check_diet(&self) -> Result<DietCheck> {
+        json(self.request(Method::GET, &["recipes", "dietary"])).await
+    }
+
+    /// Works out the nutrition of a serving of a recipe.
+    pub async fn recipe_nutrition(&self, name: &str) -> Result<RecipeNutrition> {
+        json(self.request(Method::GET, &["recipes", name, "nutrition"])).await
+    }
+
+    pub async fn list_meal_plans(&self, range: &DateRange) -> Result<Vec<MealPlan>> {
+        json(self.request(Method::GET, &["meal-plans"]).query(range)).await
+    }
+
+    pub async fn create_meal_plan(&self, meal_plan: &CreateMealPlan) -> Result<MealPlan> {
+        json(self.request(Method::POST, &["meal-plans"]).json(meal_plan)).await
+    }
+
+    /// Checks whether the inventory covers the meals planned for each day of the range.
+    pub async fn check_meal_plans(&self, range: &DateRange) -> Result<Vec<DayCheck>> {
+        json(
+            self.request(Method::GET, &["meal-plans", "check"])
+                .query(range),
+        )
+        .await
+    }
+
+    pub async fn get_meal_plan(&self, meal_plan_id: Ulid) -> Result<MealPlan> {
+        let id = meal_plan_id.to_string();
+        json(self.request(Method::GET, &["meal-plans", &id])).await
+    }
+
+    pub async fn update_meal_plan(
+        &self,
+        meal_plan_id: Ulid,
+        meal_plan: &UpdateMealPlan,
+    ) -> Result<MealPlan> {
+        let id = meal_plan_id.to_string();
+        json(
+            self.request(Method::PUT, &["meal-plans", &id])
+                .json(meal_plan),
+        )
+        .await
+    }
+
+    pub async fn delete_meal_plan(&self, meal_plan_id: Ulid) -> Result<()> {
+        let id = meal_plan_id.to_string();
+        empty(self.request(Method::DELETE, &["meal-plans", &id])).await
+    }
+
+    pub async fn list_shopping_lists(&self, query: &PageQuery) -> Result<Page<ShoppingList>> {
+        json(self.request(Method::GET, &["shopping-lists"]).query(query)).await
+    }
+
+    pub async fn create_shopping_list(
+        &self,
+        shopping_list: &CreateShoppingList,
+    ) -> Result<ShoppingList> {
+        json(
+            self.request(Method::POST, &["shopping-lists"])
+                .json(shopping_list),
+        )
+        .await
+    }
+
+    pub async fn get_shopping_list(&self, shopping_list_id: Ulid) -> Result<ShoppingList> {
+        let id = shopping_list_id.to_string();
+        json(self.request(Method::GET, &["shopping-lists", &id])).await
+    }
+
+    pub async fn delete_shopping_list(&self, shopping_list_id: Ulid) -> Result<()> {
+        let id = shopping_list_id.to_string();
+        empty(self.request(Method::DELETE, &["shopping-lists", &id])).await
+    }
+
+    /// Marks an item of a shopping list as bought, or not.
+    pub async fn check_shopping_list_item(
+        &self,
+        shopping_list_id: Ulid,
+        item_id: Ulid,
+        checked: bool,
+    ) -> Result<ShoppingListItem> {
+        let id = shopping_list_id.to_string();
+        let item_id = item_id.to_string();
+        let body = serde_json::json!({ "checked": checked });
+        json(
+            self.request(Method::PATCH, &["shopping-lists", &id, "items", &item_id])
+                .json(&body),
+        )
+        .await
+    }
+
+    pub async fn list_tasks(&self, query: &PageQuery) -> Result<Page<Task>> {
+        json(self.request(Method::GET, &["tasks"]).query(query)).await
+    }
+
+    pub async fn create_task(&self, task: &CreateTask) -> Result<Task> {
+        json(self.request(Method::POST, &["tasks"]).json(task)).await
+    }
+
+    /// Adds the todo list the server starts households out with, skipping tasks already on it.
+    /// Returns only the tasks that were added.
+    pub async fn import_tasks(&self) -> Result<Vec<Task>> {
+        json(self.request(Method::POST, &["tasks", "import"])).await
+    }
+
+    /// The occurrences of the household's recurring tasks over the range, by default the coming week.
+    pub async fn list_occurrences(&self, range: &DateRange) -> Result<Vec<ScheduledTask>> {
+        json(
+            self.request(Method::GET, &["tasks", "occurrences"])
+                .query(range),
+        )
+        .await
+    }
+
+    pub async fn get_task(&self, task_id: Ulid) -> Result<Task> {
+        let id = task_id.to_string();
+        json(self.request(Method::GET, &["tasks", &id])).await
+    }
+
+    pub async fn update_task(&self, task_id: Ulid, changes: &UpdateTask) -> Result<Task> {
+        let id = task_id.to_string();
+        json(self.request(Method::PATCH, &["tasks", &id]).json(changes)).await
+    }
+
+    pub async fn delete_task(&self, task_id: Ulid) -> Result<()> {
+        let id = task_id.to_string();
+        empty(self.request(Method::DELETE, &["tasks", &id])).await
+    }
+
+    /// Marks one occurrence of a recurring task as done, or not.
+    pub async fn complete_occurrence(
+        &self,
+        task_id: Ulid,
+        occurrence: &CompleteOccurrence,
+    ) -> Result<ScheduledTask> {
+        let id = task_id.to_string();
+        json(
+            self.request(Method::PUT, &["tasks", &id, "occurrences"])
+                .json(occurrence),
+        )
+        .await
+    }
+
+    pub async fn task_streak(&self, task_id: Ulid) -> Result<Streak> {
+        let id = task_id.to_string();
+        json(self.request(Method::GET, &["tasks", &id, "streak"])).await
+    }
+
+    /// The households the caller belongs to, starting with their personal one.
+    pub async fn dietary_profile(&self) -> Result<DietaryProfile> {
+        json(self.request(Method::GET, &["dietary-profile"])).await
+    }
+
+    /// Replaces the caller's dietary profile, which rules out the recipes suggested to them.
+    pub async fn update_dietary_profile(&self, profile: &DietaryProfile) -> Result<DietaryProfile> {
+        json(
+            self.request(Method::PUT, &["dietary-profile"])
+                .json(profile),
+        )
+        .await
+    }
+
+    pub async fn list_households(&self) -> Result<Vec<Membership>> {
+        json(self.request(Method::GET, &["households"])).await
+    }
+
+    pub async fn create_household(&self, household: &CreateHousehold) -> Result<Household> {
+        json(self.request(Method::POST, &["households"]).json(household)).await
+    }
+
+    pub async fn delete_household(&self, household_id: Ulid) -> Result<()> {
+        let id = household_id.to_string();
+        empty(self.request(Method::DELETE, &["households", &id])).await
+    }
+
+    pub async fn list_members(&self, household_id: Ulid) -> Result<Vec<Member>> {
+        let id = household_id.to_string();
+        json(self.request(Method::GET, &["households", &id, "members"])).await
+    }
+
+    pub async fn update_member(
+        &self,
+        household_id: Ulid,
+        user_id: Ulid,
+        role: Role,
+    ) -> Result<Member> {
+        let id = household_id.to_string();
+        let user_id = user_id.to_string();
+        let body = serde_json::json!({ "role": role });
+        json(
+            self.request(Method::PUT, &["households", &id, "members", &user_id])
+                .json(&body),
+        )
+        .await
+    }
+
+    pub async fn remove_member(&self, household_id: Ulid, user_id: Ulid) -> Result<()> {
+        let id = household_id.to_string();
+        let user_id = user_id.to_string();
+        empty(self.request(Method::DELETE, &["households", &id, "members", &user_id])).await
+    }
+
+    pub async fn invite_member(
+        &self,
+        household_id: Ulid,
+        invitation: &CreateInvitation,
+    ) -> Result<Invitation> {
+        let id = household_id.to_string();
+        json(
+            self.request(Method::POST, &["households", &id, "invitations"])
+                .json(invitation),
+        )
+        .await
+    }
+
+    /// The invitations addressed to the caller.
+    pub async fn list_invitations(&self) -> Result<Vec<Invitation>> {
+        json(self.request(Method::GET, &["invitations"])).await
+    }
+
+    pub async fn decline_invitation(&self, invitation_id: Ulid) -> Result<()> {
+        let id = invitation_id.to_string();
+        empty(self.request(Method::DELETE, &["invitations", &id])).await
+    }
+
+    pub async fn accept_invitation(&self, invitation_id: Ulid) -> Result<Member> {
+        let id = invitation_id.to_string();
+        json(self.request(Method::POST, &["invitations", &id, "accept"])).await
+    }
+}
+
+/// Sends a request, turning error responses into [`Error::Api`].
+async fn send(request: RequestBuilder) -> Result<Response> {
+    let response = request.send().await?;
+    match response.status().is_success() {
+        true => Ok(response),
+        false => Err(api_error(response).await),
+    }
+}
+
+async fn json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    Ok(send(request).await?.json().await?)
+}
+
+async fn empty(request: RequestBuilder) -> Result<()> {
+    send(request).await.map(|_| ())
+}
+
+/// Reads the error the server answered with.
+/// Responses rejected before reaching a handler may not carry a JSON body, in which case the
+/// code is derived from the status and the body becomes the message.
+async fn api_error(response: Response) -> Error {
+    let status = response.status().as_u16();
+    let text = match response.text().await {
+        Ok(text) => text,
+        Err(error) => return Error::Reqwest(error),
+    };
+
+    let body = serde_json::from_str(&text).unwrap_or_else(|_| ErrorBody {
+        code: ErrorCode::from_status(status),
+        message: text,
+        fields: Vec::new(),
+        shortfalls: Vec::new(),
+    });
+    Error::Api { status, body }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode},
+        routing::{get, post},
+        Json, Router,
+    };
+
+    const ULID: &str = "01ARZ3NDEKTSV4RRFFQ69G5FAV";
+
+    /// Serves a few routes that echo what they received, and returns the base url.
+    async fn serve() -> String {
+        let app = Router::new()
+            .route(
+                "/api/health",
+                get(|headers: HeaderMap| async move {
+                    let header = |name: &str| {
+                        headers
+                            .get(name)
+                            .map(|value| value.to_str().unwrap().to_string())
+                            .unwrap_or_default()
+                    };
+                    format!("{} {}", header("authorization"), header(HOUSEHOLD_HEADER))
+                }),
+            )
+            .route(
+                "/api/recipes/:name",
+                get(|Path(name): Path<String>| async move {
+                    (
+                        StatusCode::NOT_FOUND,
+                        Json(serde_json::json!({
+                            "code": "not_found",
+                            "message": format!("no recipe named {name}"),
+                        })),
+                    )
+                }),
+            )
+            .route(
+                "/api/inventory",
+                get(|| async { (StatusCode::UNAUTHORIZED, "missing token") }),
+            )
+            .route(
+                "/api/inventory/bulk",
+                post(|| async {
+                    (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        Json(serde_json::json!({
+                            "committed": false,
+                            "results": [{
+                                "status": "failed",
+                                "inventory_id": null,
+                                "error": {
+                                    "code": "validation",
+                                    "message": "invalid",
+                                    "fields": [{ "field": "quantity", "message": "must not be blank" }],
+                                },
+                            }],
+                        })),
+                    )
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{addr}/api")
+    }
+
+    #[test]
+    fn encodes_path_segments() {
+        let client = Client::new("http://localhost:3000/api/").unwrap();
+
+        assert_eq!(
+            client.url(&["recipes", "egg/yolk", "cook"]).as_str(),
+            "http://localhost:3000/api/recipes/egg%2Fyolk/cook"
+        );
+        assert!(matches!(
+            Client::new("not a url"),
+            Err(Error::InvalidUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn sends_token_and_household() {
+        let client = Client::new(&serve().await)
+            .unwrap()
+            .token("secret")
+            .household(ULID.parse().unwrap());
+
+        assert_eq!(
+            client.health().await.unwrap(),
+            format!("Bearer secret {ULID}")
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_typed_errors() {
+        let client = Client::new(&serve().await).unwrap();
+
+        let error = client.get_recipe("egg/yolk").await.unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::NotFound));
+        assert_eq!(error.to_string(), "404 not_found: no recipe named egg/yolk");
+
+        // rejected before reaching a handler, without a JSON body
+        let error = client
+            .list_inventory(&InventoryQuery::default())
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::Unauthorized));
+        assert_eq!(error.to_string(), "401 unauthorized: missing token");
+    }
+
+    #[tokio::test]
+    async fn returns_rolled_back_batches() {
+        let client = Client::new(&serve().await).unwrap();
+
+        let operation = BulkOperation::Delete {
+            inventory_id: ULID.parse().unwrap(),
+        };
+        let response = client.bulk_inventory(&[operation]).await.unwrap();
+
+        assert!(!response.committed);
+        match &response.results[0] {
+            BulkResult::Failed { error, .. } => {
+                assert_eq!(error.code, ErrorCode::Validation);
+                assert_eq!(error.fields[0].field, "quantity");
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,619 @@
+//! The bodies and query parameters exchanged with the API, as they appear on the wire.
+use annapurna_data::types::Quantity;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
+
+pub use annapurna_data::{
+    dietary::{Diet, DietaryProfile, Tag},
+    nutrition::{Nutrients, Nutrition},
+    types::{Recurrence, Repeat, Step},
+};
+pub use rusty_ulid::Ulid;
+
+/// Why the server rejected a request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    MethodNotAllowed,
+    Validation,
+    Unauthorized,
+    Forbidden,
+    Conflict,
+    Internal,
+}
+
+impl ErrorCode {
+    /// The code matching a status, for error responses that came without a body.
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            400 | 422 => ErrorCode::Validation,
+            401 => ErrorCode::Unauthorized,
+            403 => ErrorCode::Forbidden,
+            404 => ErrorCode::NotFound,
+            405 => ErrorCode::MethodNotAllowed,
+            409 => ErrorCode::Conflict,
+            _ => ErrorCode::Internal,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = match self {
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::MethodNotAllowed => "method_not_allowed",
+            ErrorCode::Validation => "validation",
+            ErrorCode::Unauthorized => "unauthorized",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::Internal => "internal",
+        };
+        write!(f, "{code}")
+    }
+}
+
+/// The JSON body of every error response.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+    /// The problems with each field of the request, for validation errors.
+    #[serde(default)]
+    pub fields: Vec<FieldError>,
+    /// What the inventory is short of, when there is not enough of it to do what was asked.
+    #[serde(default)]
+    pub shortfalls: Vec<Shortfall>,
+}
+
+/// A problem with a single field of a request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// The path to the field, such as `ingredients[1].quantity`.
+    pub field: String,
+    pub message: String,
+}
+
+/// A page of a list, along with the cursor to pass as `after` to fetch the next one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Missing on the last page.
+    pub next: Option<Ulid>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Inventory {
+    pub inventory_id: Ulid,
+    /// The user who added the item.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
+    pub ingredient_type: String,
+    pub quantity: String,
+    pub created_at: DateTime<Utc>,
+    pub expiration_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    #[default]
+    Added,
+    Expiration,
+    IngredientType,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Selects, orders and pages the inventory. Everything left out matches every item.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct InventoryQuery {
+    /// An ingredient, or a whole category such as `cheese/*`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingredient_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiring_before: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiring_after: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortBy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<SortOrder>,
+    /// The cursor returned with the previous page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Ulid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateInventory {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventory_id: Option<Ulid>,
+    pub ingredient_type: String,
+    pub quantity: String,
+    pub expiration_date: Option<DateTime<Utc>>,
+}
+
+/// Changes to an inventory item. Fields left as `None` are kept as they are.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UpdateInventory {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingredient_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<String>,
+    /// `Some(None)` removes the expiration date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_date: Option<Option<DateTime<Utc>>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BulkOperation {
+    Create(CreateInventory),
+    Update {
+        inventory_id: Ulid,
+        #[serde(flatten)]
+        changes: UpdateInventory,
+    },
+    Delete {
+        inventory_id: Ulid,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum BulkResult {
+    Created {
+        item: Inventory,
+    },
+    Updated {
+        item: Inventory,
+    },
+    Deleted {
+        inventory_id: Ulid,
+    },
+    Failed {
+        inventory_id: Option<Ulid>,
+        error: ErrorBody,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BulkResponse {
+    /// Whether the changes were saved, which only happens when every one of them succeeds.
+    pub committed: bool,
+    pub results: Vec<BulkResult>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recipe {
+    pub recipe_id: Ulid,
+    /// The household the recipe belongs to, or none if it is part of the shared catalogue.
+    pub household_id: Option<Ulid>,
+    pub name: String,
+    pub servings: i32,
+    pub created_at: DateTime<Utc>,
+    pub ingredients: Vec<RecipeIngredient>,
+    /// How to make the recipe, in order.
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeIngredient {
+    pub ingredient_type: String,
+    /// The amount the recipe needs, if it specifies one.
+    pub quantity: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateRecipe {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipe_id: Option<Ulid>,
+    pub name: String,
+    /// The number of servings the recipe makes, which defaults to one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servings: Option<u32>,
+    pub ingredients: Vec<RecipeIngredient>,
+    /// Steps may only wait on the steps before them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateRecipe {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servings: Option<u32>,
+    pub ingredients: Vec<RecipeIngredient>,
+    /// Steps may only wait on the steps before them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankBy {
+    #[default]
+    Count,
+    Cost,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RankingQuery {
+    pub by: RankBy,
+    /// The most ingredients to suggest buying.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Ranking {
+    pub ranked: Vec<RankedRecipe>,
+    pub shopping: Vec<ShoppingStep>,
+}
+
+/// A recipe that cannot be made yet, and what it would take to make it.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RankedRecipe {
+    pub recipe: String,
+    pub missing: Vec<String>,
+    pub cost: f64,
+}
+
+/// Ingredients to buy, and the recipes that can be made once they have been bought.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ShoppingStep {
+    pub buy: Vec<String>,
+    pub unlocks: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SuggestionQuery {
+    /// How many days ahead to look for expiring inventory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<i64>,
+    /// The most ingredients a suggested recipe may be missing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_missing: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Suggestion {
+    pub recipe: String,
+    /// The expiring ingredients the recipe uses.
+    pub uses: Vec<String>,
+    /// The ingredients that would need to be bought first.
+    pub missing: Vec<String>,
+    pub score: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CookRecipe {
+    /// The number of servings cooked, which defaults to everything the recipe makes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servings: Option<u32>,
+    /// Cook the recipe even though the inventory is short of some of its ingredients.
+    pub force: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Cooked {
+    pub log: CookingLog,
+    /// The inventory items that were partially used.
+    pub updated: Vec<Inventory>,
+    /// The inventory items that were used up.
+    pub deleted: Vec<Ulid>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CookingLog {
+    pub cooking_log_id: Ulid,
+    /// The user who cooked the recipe.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
+    pub recipe: String,
+    pub servings: i32,
+    pub cooked_at: DateTime<Utc>,
+}
+
+/// The recipes that suit the caller's dietary profile, and those it rules out.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DietCheck {
+    pub allowed: Vec<String>,
+    /// The ingredients that rule out each recipe.
+    pub forbidden: HashMap<String, Vec<ForbiddenIngredient>>,
+}
+
+/// An ingredient that rules a recipe out, and what it contains.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ForbiddenIngredient {
+    pub ingredient: String,
+    pub tag: Tag,
+}
+
+/// The nutrition of a serving of a recipe.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RecipeNutrition {
+    pub recipe: String,
+    pub servings: u32,
+    pub per_serving: Nutrients,
+    /// The ingredients left out, because their amount or nutrition is unknown.
+    pub unknown: Vec<String>,
+}
+
+/// The nutrition known for an ingredient, per 100 g.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IngredientNutrition {
+    pub ingredient_type: String,
+    #[serde(flatten)]
+    pub nutrition: Nutrition,
+    /// Missing for nutrition taken from the server's facts.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Recipes to have ready at the same time, for [`crate::Client::schedule_recipes`].
+#[derive(Clone, Debug, Serialize)]
+pub struct ScheduleMeal {
+    pub recipes: Vec<String>,
+    pub serve_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MealSlot {
+    Breakfast,
+    Lunch,
+    Dinner,
+    Snack,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MealPlan {
+    pub meal_plan_id: Ulid,
+    /// The user who planned the meal.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
+    pub date: NaiveDate,
+    pub meal: MealSlot,
+    pub recipe: String,
+    pub servings: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The days to list meal plans for. Either end may be left open.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DateRange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateMealPlan {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meal_plan_id: Option<Ulid>,
+    pub date: NaiveDate,
+    pub meal: MealSlot,
+    pub recipe: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servings: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateMealPlan {
+    pub date: NaiveDate,
+    pub meal: MealSlot,
+    pub recipe: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servings: Option<u32>,
+}
+
+/// Whether the meals planned for a day can be made from the inventory projected for that day.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DayCheck {
+    pub date: NaiveDate,
+    pub recipes: Vec<String>,
+    /// Everything the day's meals need that will not be available.
+    pub shortfalls: Vec<Shortfall>,
+}
+
+/// An ingredient a recipe needs more of than is available.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Shortfall {
+    pub ingredient: String,
+    /// The outstanding amount, if the recipe specifies one.
+    pub amount: Option<Quantity>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShoppingList {
+    pub shopping_list_id: Ulid,
+    /// The user who created the list.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
+    pub name: String,
+    /// The recipes the list was generated for.
+    pub recipes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub items: Vec<ShoppingListItem>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShoppingListItem {
+    pub shopping_list_item_id: Ulid,
+    pub shopping_list_id: Ulid,
+    pub ingredient_type: String,
+    /// The amount to buy, if it is known.
+    pub quantity: Option<String>,
+    /// Whether the item has been bought.
+    pub checked: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateShoppingList {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shopping_list_id: Option<Ulid>,
+    pub name: String,
+    /// The recipes to shop for.
+    pub recipes: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Task {
+    pub task_id: Ulid,
+    /// The user who added the task.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
+    pub description: String,
+    pub completed: bool,
+    /// How long the task takes.
+    pub duration: Option<Duration>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub completion_time: Option<DateTime<Utc>>,
+    /// Recurring tasks are completed one occurrence at a time.
+    pub recurrence: Option<Recurrence>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Selects a page of a list, such as the tasks or the recipes.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PageQuery {
+    /// The cursor returned with the previous page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Ulid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateTask {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<Ulid>,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UpdateTask {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<bool>,
+    /// `Some(None)` removes the duration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Option<Duration>>,
+    /// `Some(None)` removes the start time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<Option<DateTime<Utc>>>,
+    /// `Some(None)` stops the task from recurring.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Option<Recurrence>>,
+}
+
+/// An occurrence of a recurring task.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    pub task_id: Ulid,
+    pub description: String,
+    pub date: NaiveDate,
+    /// Missing when the task is due at no particular time of the day.
+    pub time: Option<NaiveTime>,
+    pub scheduled_at: DateTime<Utc>,
+    pub completed: bool,
+    pub completion_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CompleteOccurrence {
+    pub date: NaiveDate,
+    /// Required when the task is due at particular times of the day.
+    pub time: Option<NaiveTime>,
+    pub completed: bool,
+}
+
+/// How consistently a recurring task has been done since it was added.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Streak {
+    /// The occurrences done in a row, up to the latest.
+    pub current: usize,
+    pub longest: usize,
+    pub completed: usize,
+    pub missed: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Editor,
+    Owner,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Household {
+    pub household_id: Ulid,
+    pub name: String,
+    /// The user this household was created for, if it is someone's personal household.
+    pub personal_for: Option<Ulid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A household the caller belongs to, along with their role in it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Membership {
+    #[serde(flatten)]
+    pub household: Household,
+    pub role: Role,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Member {
+    pub household_id: Ulid,
+    pub user_id: Ulid,
+    pub identifier: String,
+    pub role: Role,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Invitation {
+    pub household_invitation_id: Ulid,
+    pub household_id: Ulid,
+    pub identifier: String,
+    pub role: Role,
+    pub invited_by: Ulid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateHousehold {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub household_id: Option<Ulid>,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CreateInvitation {
+    /// The identifier of the user to invite, as it appears in their auth token.
+    pub identifier: String,
+    pub role: Role,
+}
@@ -0,0 +1,189 @@
+//! Dietary constraints: what ingredients contain, and what people will not eat.
+//!
+//! Ingredients are tagged with what they contain, such as `gluten` or `dairy`. A tag on a category
+//! applies to every kind within it, so tagging `flour` also tags `flour/wheat`.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// Something an ingredient contains that some diets or allergies rule out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tag {
+    Meat,
+    Fish,
+    Shellfish,
+    Dairy,
+    Egg,
+    Honey,
+    Gluten,
+    Nut,
+    Peanut,
+    Soy,
+    Sesame,
+}
+
+impl Tag {
+    pub const ALL: [Tag; 11] = [
+        Tag::Meat,
+        Tag::Fish,
+        Tag::Shellfish,
+        Tag::Dairy,
+        Tag::Egg,
+        Tag::Honey,
+        Tag::Gluten,
+        Tag::Nut,
+        Tag::Peanut,
+        Tag::Soy,
+        Tag::Sesame,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tag::Meat => "meat",
+            Tag::Fish => "fish",
+            Tag::Shellfish => "shellfish",
+            Tag::Dairy => "dairy",
+            Tag::Egg => "egg",
+            Tag::Honey => "honey",
+            Tag::Gluten => "gluten",
+            Tag::Nut => "nut",
+            Tag::Peanut => "peanut",
+            Tag::Soy => "soy",
+            Tag::Sesame => "sesame",
+        }
+    }
+}
+
+impl std::str::FromStr for Tag {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let tag = match s.trim().to_lowercase().as_str() {
+            "meat" => Tag::Meat,
+            "fish" => Tag::Fish,
+            "shellfish" => Tag::Shellfish,
+            "dairy" => Tag::Dairy,
+            "egg" => Tag::Egg,
+            "honey" => Tag::Honey,
+            "gluten" => Tag::Gluten,
+            "nut" => Tag::Nut,
+            "peanut" => Tag::Peanut,
+            "soy" => Tag::Soy,
+            "sesame" => Tag::Sesame,
+            other => return Err(Error::UnknownTag(other.to_string())),
+        };
+        Ok(tag)
+    }
+}
+
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A way of eating that rules out whole groups of ingredients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Diet {
+    Vegetarian,
+    Pescatarian,
+    Vegan,
+}
+
+impl Diet {
+    pub const ALL: [Diet; 3] = [Diet::Vegetarian, Diet::Pescatarian, Diet::Vegan];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Diet::Vegetarian => "vegetarian",
+            Diet::Pescatarian => "pescatarian",
+            Diet::Vegan => "vegan",
+        }
+    }
+
+    /// The tags of the ingredients the diet rules out.
+    pub fn excludes(&self) -> &'static [Tag] {
+        match self {
+            Diet::Vegetarian => &[Tag::Meat, Tag::Fish, Tag::Shellfish],
+            Diet::Pescatarian => &[Tag::Meat],
+            Diet::Vegan => &[
+                Tag::Meat,
+                Tag::Fish,
+                Tag::Shellfish,
+                Tag::Dairy,
+                Tag::Egg,
+                Tag::Honey,
+            ],
+        }
+    }
+}
+
+impl std::str::FromStr for Diet {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let diet = match s.trim().to_lowercase().as_str() {
+            "vegetarian" => Diet::Vegetarian,
+            "pescatarian" => Diet::Pescatarian,
+            "vegan" => Diet::Vegan,
+            other => return Err(Error::UnknownDiet(other.to_string())),
+        };
+        Ok(diet)
+    }
+}
+
+impl std::fmt::Display for Diet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// What a person will not eat: the diets they follow, and anything else they avoid,
+/// such as the ingredients they are allergic to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DietaryProfile {
+    #[serde(default)]
+    pub diets: Vec<Diet>,
+    #[serde(default)]
+    pub avoid: Vec<Tag>,
+}
+
+impl DietaryProfile {
+    /// Every tag the profile rules out, in order and without repeats.
+    pub fn forbidden_tags(&self) -> Vec<Tag> {
+        let mut tags = self
+            .diets
+            .iter()
+            .flat_map(|diet| diet.excludes().iter().copied())
+            .chain(self.avoid.iter().copied())
+            .collect::<Vec<_>>();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combines_diets_and_avoided_tags() {
+        let profile = DietaryProfile {
+            diets: vec![Diet::Vegetarian],
+            avoid: vec![Tag::Nut, Tag::Fish],
+        };
+        assert_eq!(
+            profile.forbidden_tags(),
+            vec![Tag::Meat, Tag::Fish, Tag::Shellfish, Tag::Nut]
+        );
+
+        assert_eq!("Vegan".parse::<Diet>().unwrap(), Diet::Vegan);
+        for tag in Tag::ALL {
+            assert_eq!(tag.as_str().parse::<Tag>().unwrap(), tag);
+        }
+        assert!("paleo".parse::<Diet>().is_err());
+    }
+}
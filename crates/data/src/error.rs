@@ -0,0 +1,23 @@
+use crate::units::Dimension;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("unable to parse quantity: {0}")]
+    UnparseableQuantity(String),
+    #[error("unknown unit: {0}")]
+    UnknownUnit(String),
+    #[error("cannot convert {from} to {to}")]
+    IncompatibleDimensions { from: Dimension, to: Dimension },
+    #[error("density must be a positive number, not {0}")]
+    InvalidDensity(f64),
+    #[error("the substitution for {0} has no replacements")]
+    EmptySubstitution(String),
+    #[error("unknown dietary tag: {0}")]
+    UnknownTag(String),
+    #[error("unknown diet: {0}")]
+    UnknownDiet(String),
+    #[error("invalid nutrition data on line {line}: {message}")]
+    InvalidCsv { line: usize, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
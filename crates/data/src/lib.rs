@@ -1,7 +1,16 @@
-use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::Path};
+use chrono::NaiveDate;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
+pub mod dietary;
+pub mod error;
+pub mod nutrition;
+pub mod taxonomy;
 pub mod types;
+pub mod units;
 
 /// A collection of all known recipes and ingredients.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -9,6 +18,44 @@ pub struct Facts {
     pub inventory: Vec<types::Ingredient>,
 
     pub recipes: Vec<types::Recipe>,
+
+    /// Densities of ingredients, used to convert their quantities between mass and volume.
+    #[serde(default)]
+    pub densities: HashMap<String, units::Density>,
+
+    /// Ingredients that may be used in place of others.
+    #[serde(default)]
+    pub substitutions: Vec<types::Substitution>,
+
+    /// The price of buying each ingredient, used to rank what to shop for.
+    #[serde(default)]
+    pub prices: HashMap<String, f64>,
+
+    /// The number of servings each recipe makes. Recipes without one make a single serving.
+    #[serde(default)]
+    pub servings: HashMap<String, u32>,
+
+    /// When the ingredients in the inventory expire, used to suggest recipes that use them up.
+    #[serde(default)]
+    pub expirations: HashMap<String, NaiveDate>,
+
+    /// The todo list households start out with.
+    #[serde(default)]
+    pub tasks: Vec<types::Task>,
+
+    /// The nutrition of ingredients, used to work out the nutrition of recipes.
+    #[serde(default)]
+    pub nutrition: HashMap<String, nutrition::Nutrition>,
+
+    /// What ingredients contain, used to rule out recipes that do not suit a diet.
+    #[serde(default)]
+    pub tags: HashMap<String, Vec<dietary::Tag>>,
+}
+
+/// The layout of `todos.ron`.
+#[derive(Default, Deserialize)]
+struct TaskList {
+    todos: Vec<types::Task>,
 }
 
 impl Facts {
@@ -20,18 +67,94 @@ impl Facts {
         let recipes_path = path.join("recipes.ron");
 
         let inventory_contents = std::fs::read_to_string(inventory_path)?;
-        let inventory_data: Vec<String> = ron::from_str(&inventory_contents)?;
-        let inventory: Vec<types::Ingredient> = inventory_data
-            .into_iter()
-            .map(types::Ingredient::new)
-            .collect();
+        let inventory: Vec<types::Ingredient> = ron::from_str(&inventory_contents)?;
 
         let recipes_contents = std::fs::read_to_string(recipes_path)?;
-        let recipes_data: HashMap<String, Vec<String>> = ron::from_str(&recipes_contents)?;
+        let recipes_data: HashMap<String, Vec<types::Ingredient>> =
+            ron::from_str(&recipes_contents)?;
+
+        // the remaining facts are optional, as recipes can be resolved without them
+        let mut steps: HashMap<String, Vec<types::Step>> = read_optional(path.join("steps.ron"))?;
         let recipes = types::Recipe::from_hashmap(recipes_data)
             .into_keys()
+            .map(|recipe| {
+                let recipe_steps = steps.remove(&recipe.name).unwrap_or_default();
+                recipe.with_steps(recipe_steps)
+            })
             .collect();
+        let densities = read_optional(path.join("densities.ron"))?;
+        let substitutions: Vec<types::Substitution> =
+            read_optional(path.join("substitutions.ron"))?;
+        if let Some(empty) = substitutions
+            .iter()
+            .find(|substitution| substitution.replacements.is_empty())
+        {
+            return Err(error::Error::EmptySubstitution(empty.ingredient.clone()).into());
+        }
+        let prices = read_optional(path.join("prices.ron"))?;
+        let servings = read_optional(path.join("servings.ron"))?;
+        let expirations = read_optional(path.join("expirations.ron"))?;
+        let tasks = read_optional::<TaskList>(path.join("todos.ron"))?.todos;
+        let tags = read_optional(path.join("tags.ron"))?;
+        let nutrition_path = path.join("nutrition.csv");
+        let nutrition = match nutrition_path.exists() {
+            true => nutrition::read_csv(&std::fs::read_to_string(nutrition_path)?)?,
+            false => HashMap::new(),
+        };
+
+        Ok(Self {
+            inventory,
+            recipes,
+            densities,
+            substitutions,
+            prices,
+            servings,
+            expirations,
+            tasks,
+            nutrition,
+            tags,
+        })
+    }
+
+    /// Every ingredient the facts mention, including recipes that are used as ingredients.
+    pub fn ingredient_names(&self) -> HashSet<&str> {
+        let inventory = self
+            .inventory
+            .iter()
+            .map(|ingredient| ingredient.name.as_str());
+        let recipes = self.recipes.iter().flat_map(|recipe| {
+            std::iter::once(recipe.name.as_str()).chain(
+                recipe
+                    .ingredients
+                    .iter()
+                    .map(|ingredient| ingredient.name.as_str()),
+            )
+        });
+        let substitutions = self.substitutions.iter().flat_map(|substitution| {
+            std::iter::once(substitution.ingredient.as_str())
+                .chain(substitution.replacements.iter().map(String::as_str))
+        });
+        let densities = self.densities.keys().map(String::as_str);
+        let prices = self.prices.keys().map(String::as_str);
+        let tags = self.tags.keys().map(String::as_str);
+
+        inventory
+            .chain(recipes)
+            .chain(substitutions)
+            .chain(densities)
+            .chain(prices)
+            .chain(tags)
+            .collect()
+    }
+}
 
-        Ok(Self { inventory, recipes })
+/// Reads a facts file if it exists, otherwise returning the default value.
+fn read_optional<T: DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    match path.exists() {
+        true => Ok(ron::from_str(&std::fs::read_to_string(path)?)?),
+        false => Ok(T::default()),
     }
 }
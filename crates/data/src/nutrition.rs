@@ -0,0 +1,251 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    ops::{Add, AddAssign},
+};
+
+/// Amounts of nutrients. Energy is in kcal, macronutrients in grams and micronutrients in milligrams.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Nutrients {
+    pub calories: f64,
+    pub protein: f64,
+    pub fat: f64,
+    pub saturated_fat: f64,
+    pub carbohydrates: f64,
+    pub sugar: f64,
+    pub fiber: f64,
+    pub sodium: f64,
+    pub calcium: f64,
+    pub iron: f64,
+    pub potassium: f64,
+    pub vitamin_c: f64,
+}
+
+/// The columns of a nutrition dataset, in the order of the fields of [`Nutrients`].
+pub const NUTRIENT_COLUMNS: [&str; 12] = [
+    "calories",
+    "protein",
+    "fat",
+    "saturated_fat",
+    "carbohydrates",
+    "sugar",
+    "fiber",
+    "sodium",
+    "calcium",
+    "iron",
+    "potassium",
+    "vitamin_c",
+];
+
+impl Nutrients {
+    pub fn scale(&self, factor: f64) -> Self {
+        let mut values = self.values();
+        values.iter_mut().for_each(|value| *value *= factor);
+        Self::from_values(values)
+    }
+
+    /// The amount of each nutrient, in the order of [`NUTRIENT_COLUMNS`].
+    pub fn values(&self) -> [f64; 12] {
+        [
+            self.calories,
+            self.protein,
+            self.fat,
+            self.saturated_fat,
+            self.carbohydrates,
+            self.sugar,
+            self.fiber,
+            self.sodium,
+            self.calcium,
+            self.iron,
+            self.potassium,
+            self.vitamin_c,
+        ]
+    }
+
+    pub fn from_values(values: [f64; 12]) -> Self {
+        let [calories, protein, fat, saturated_fat, carbohydrates, sugar, fiber, sodium, calcium, iron, potassium, vitamin_c] =
+            values;
+        Self {
+            calories,
+            protein,
+            fat,
+            saturated_fat,
+            carbohydrates,
+            sugar,
+            fiber,
+            sodium,
+            calcium,
+            iron,
+            potassium,
+            vitamin_c,
+        }
+    }
+}
+
+impl Add for Nutrients {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let mut values = self.values();
+        for (value, other) in values.iter_mut().zip(other.values()) {
+            *value += other;
+        }
+        Self::from_values(values)
+    }
+}
+
+impl AddAssign for Nutrients {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+/// What is known about the nutrition of an ingredient.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Nutrition {
+    /// The nutrients in 100 g of the ingredient.
+    pub per_100g: Nutrients,
+    /// The weight of a single piece in grams, such as an egg, so that counted amounts can be weighed.
+    pub piece_weight: Option<f64>,
+}
+
+/// Reads a nutrition dataset, one ingredient per row.
+///
+/// The header names the columns: `ingredient`, any of [`NUTRIENT_COLUMNS`] and `piece_weight`.
+/// Other columns are ignored, and nutrients that are missing or left empty count as none.
+/// Fields may be quoted, with `""` standing for a quote.
+pub fn read_csv(contents: &str) -> Result<HashMap<String, Nutrition>> {
+    let mut rows = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let header = match rows.next() {
+        Some((index, row)) => split_row(row, index + 1)?,
+        None => return Ok(HashMap::new()),
+    };
+    let column = |name: &str| header.iter().position(|column| column.trim() == name);
+
+    let ingredient = column("ingredient").ok_or_else(|| Error::InvalidCsv {
+        line: 1,
+        message: "missing the ingredient column".to_string(),
+    })?;
+    let nutrients = NUTRIENT_COLUMNS.map(column);
+    let piece_weight = column("piece_weight");
+
+    let mut dataset = HashMap::new();
+    for (index, row) in rows {
+        let line = index + 1;
+        let fields = split_row(row, line)?;
+        let field = |index: Option<usize>| {
+            let value = index
+                .and_then(|index| fields.get(index))
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty());
+            value
+                .map(|value| {
+                    value.parse::<f64>().map_err(|_| Error::InvalidCsv {
+                        line,
+                        message: format!("{value} is not a number"),
+                    })
+                })
+                .transpose()
+        };
+
+        let name = fields
+            .get(ingredient)
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| Error::InvalidCsv {
+                line,
+                message: "missing the ingredient".to_string(),
+            })?;
+        let mut values = [0.0; 12];
+        for (value, index) in values.iter_mut().zip(nutrients) {
+            *value = field(index)?.unwrap_or_default();
+        }
+
+        dataset.insert(
+            name.to_string(),
+            Nutrition {
+                per_100g: Nutrients::from_values(values),
+                piece_weight: field(piece_weight)?,
+            },
+        );
+    }
+
+    Ok(dataset)
+}
+
+fn split_row(line: &str, number: usize) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+
+    match quoted {
+        true => Err(Error::InvalidCsv {
+            line: number,
+            message: "unterminated quote".to_string(),
+        }),
+        false => {
+            fields.push(field);
+            Ok(fields)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_csv, Nutrients};
+
+    #[test]
+    fn reads_nutrition_datasets() {
+        let contents = "\
+ingredient,calories,protein,fat,piece_weight,source
+flour,364,10.3,1,,\"USDA, 2019\"
+egg,143,12.6,9.5,50,
+";
+        let dataset = read_csv(contents).unwrap();
+
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset["flour"].per_100g.calories, 364.0);
+        assert_eq!(dataset["flour"].per_100g.sodium, 0.0);
+        assert_eq!(dataset["flour"].piece_weight, None);
+        assert_eq!(dataset["egg"].piece_weight, Some(50.0));
+    }
+
+    #[test]
+    fn reports_the_line_of_bad_values() {
+        let contents = "ingredient,calories\nflour,364\nsalt,lots\n";
+
+        assert_eq!(
+            read_csv(contents).unwrap_err().to_string(),
+            "invalid nutrition data on line 3: lots is not a number"
+        );
+    }
+
+    #[test]
+    fn adds_and_scales_nutrients() {
+        let flour = Nutrients {
+            calories: 364.0,
+            protein: 10.0,
+            ..Default::default()
+        };
+
+        let total = flour.scale(5.0) + flour;
+        assert_eq!(total.calories, 364.0 * 6.0);
+        assert_eq!(total.protein, 60.0);
+    }
+}
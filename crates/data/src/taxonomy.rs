@@ -0,0 +1,37 @@
+//! Ingredient names form a hierarchy using `/` as a separator, so `oil/avocado` is a kind of `oil`
+//! and `onion/green` is a kind of `onion`.
+
+pub const SEPARATOR: char = '/';
+
+/// Whether the ingredient is the category itself or one of the kinds within it.
+pub fn is_within(ingredient: &str, category: &str) -> bool {
+    match ingredient.strip_prefix(category) {
+        Some(rest) => rest.is_empty() || rest.starts_with(SEPARATOR),
+        None => false,
+    }
+}
+
+/// The more general categories of an ingredient, from the nearest to the most general.
+/// `onion/green/scallion` yields `onion/green` and then `onion`.
+pub fn ancestors(ingredient: &str) -> impl Iterator<Item = &str> {
+    ingredient
+        .rmatch_indices(SEPARATOR)
+        .map(move |(index, _)| &ingredient[..index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ancestors, is_within};
+
+    #[test]
+    fn matches_categories() {
+        assert!(is_within("oil/avocado", "oil"));
+        assert!(is_within("oil", "oil"));
+        assert!(!is_within("oil", "oil/avocado"));
+        assert!(!is_within("oilseed", "oil"));
+
+        let names = ancestors("onion/green/scallion").collect::<Vec<_>>();
+        assert_eq!(names, vec!["onion/green", "onion"]);
+        assert_eq!(ancestors("onion").count(), 0);
+    }
+}
@@ -1,10 +1,54 @@
-use chrono::{DateTime, NaiveTime, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, time::Duration};
 
+/// An amount of something, such as `500 g` of flour.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: String,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: String) -> Self {
+        Self { value, unit }
+    }
+
+    /// Multiplies the amount by the given factor, keeping its unit.
+    pub fn scale(&self, factor: f64) -> Self {
+        Self::new(self.value * factor, self.unit.clone())
+    }
+}
+
+impl std::fmt::Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.value, self.unit)
+    }
+}
+
+impl std::hash::Hash for Quantity {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.to_bits().hash(state);
+        self.unit.hash(state);
+    }
+}
+
+impl PartialEq for Quantity {
+    fn eq(&self, other: &Self) -> bool {
+        self.value.to_bits() == other.value.to_bits() && self.unit == other.unit
+    }
+}
+impl Eq for Quantity {}
+
+/// An ingredient, optionally with the amount of it that is required or available.
+/// In the facts files an ingredient may be written as a bare name (`"flour"`) or with an amount
+/// (`(name: "flour", amount: (value: 500, unit: "g"))`).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(from = "IngredientEntry")]
 pub struct Ingredient {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Quantity>,
 }
 
 impl std::fmt::Display for Ingredient {
@@ -15,13 +59,41 @@ impl std::fmt::Display for Ingredient {
 
 impl Ingredient {
     pub fn new(name: String) -> Self {
-        Self { name }
+        Self { name, amount: None }
+    }
+
+    pub fn with_amount(name: String, amount: Quantity) -> Self {
+        Self {
+            name,
+            amount: Some(amount),
+        }
     }
 }
 
 impl From<String> for Ingredient {
     fn from(name: String) -> Self {
-        Self { name }
+        Self::new(name)
+    }
+}
+
+/// The accepted representations of an ingredient when deserializing.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IngredientEntry {
+    Name(String),
+    Detailed {
+        name: String,
+        #[serde(default)]
+        amount: Option<Quantity>,
+    },
+}
+
+impl From<IngredientEntry> for Ingredient {
+    fn from(entry: IngredientEntry) -> Self {
+        match entry {
+            IngredientEntry::Name(name) => Self::new(name),
+            IngredientEntry::Detailed { name, amount } => Self { name, amount },
+        }
     }
 }
 
@@ -29,26 +101,72 @@ impl From<String> for Ingredient {
 pub struct Recipe {
     pub name: String,
     pub ingredients: Vec<Ingredient>,
+    /// How to make the recipe, in order.
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+/// Something to do while making a recipe, such as proofing the dough for an hour.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Step {
+    pub description: String,
+    pub duration: Duration,
+    /// The positions of the steps that must be finished before this one can start.
+    /// When left out, a step follows the one before it.
+    #[serde(default)]
+    pub after: Option<Vec<usize>>,
+}
+
+impl Step {
+    /// The positions of the steps this one, at `position`, has to wait for.
+    pub fn dependencies(&self, position: usize) -> Vec<usize> {
+        match &self.after {
+            Some(after) => after.clone(),
+            None => position.checked_sub(1).into_iter().collect(),
+        }
+    }
 }
 
 impl Recipe {
     pub fn new(name: String, ingredients: Vec<Ingredient>) -> Self {
-        Self { name, ingredients }
+        Self {
+            name,
+            ingredients,
+            steps: Vec::new(),
+        }
     }
 
-    pub fn from_hashmap(items: HashMap<String, Vec<String>>) -> HashMap<Recipe, Vec<Ingredient>> {
+    pub fn with_steps(mut self, steps: Vec<Step>) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    pub fn from_hashmap(
+        items: HashMap<String, Vec<Ingredient>>,
+    ) -> HashMap<Recipe, Vec<Ingredient>> {
         items
             .into_iter()
-            .map(|(name, ingredients)| {
-                let ingredients = ingredients
-                    .into_iter()
-                    .map(Ingredient::new)
-                    .collect::<Vec<Ingredient>>();
-                (Recipe::new(name, ingredients.clone()), ingredients)
-            })
+            .map(|(name, ingredients)| (Recipe::new(name, ingredients.clone()), ingredients))
             .collect::<HashMap<Recipe, Vec<Ingredient>>>()
     }
 
+    /// Scales the amounts of every ingredient by the given factor, such as to make a double batch.
+    pub fn scaled(&self, factor: f64) -> Self {
+        let ingredients = self
+            .ingredients
+            .iter()
+            .map(|ingredient| Ingredient {
+                name: ingredient.name.clone(),
+                amount: ingredient
+                    .amount
+                    .as_ref()
+                    .map(|amount| amount.scale(factor)),
+            })
+            .collect();
+
+        Self::new(self.name.clone(), ingredients).with_steps(self.steps.clone())
+    }
+
     pub fn flatten(items: HashMap<Recipe, Vec<Ingredient>>) -> Vec<(String, String)> {
         items
             .into_iter()
@@ -92,11 +210,126 @@ impl PartialEq for Recipe {
 }
 impl Eq for Recipe {}
 
+/// A way to replace an ingredient that is not available, such as `milk` and `vinegar` for `buttermilk`.
+/// Substitutions only apply in one direction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Substitution {
+    pub ingredient: String,
+    pub replacements: Vec<String>,
+}
+
+/// Something to do. In the facts only the description is required, given as `name`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Task {
+    #[serde(alias = "name")]
     pub description: String,
+    #[serde(default)]
     pub completed: bool,
     pub duration: Option<Duration>,
     pub start_time: Option<NaiveTime>,
     pub completion_time: Option<DateTime<Utc>>,
+    /// Tasks without a recurrence are done once.
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+}
+
+/// When a task comes back, such as every weekday at `07:00` and `19:00`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Recurrence {
+    pub repeat: Repeat,
+    /// The times of day the task is due on the days it occurs.
+    /// Without any, it occurs once on each of those days, at no particular time.
+    #[serde(default)]
+    pub times: Vec<NaiveTime>,
+}
+
+/// The days a recurring task occurs on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Repeat {
+    Daily,
+    /// Monday through Friday.
+    Weekdays,
+    /// Every `days` days, counting from `starting`.
+    Every {
+        days: u32,
+        starting: NaiveDate,
+    },
+}
+
+impl Repeat {
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        match self {
+            Repeat::Daily => true,
+            Repeat::Weekdays => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+            Repeat::Every { days, starting } => {
+                let since = (date - *starting).num_days();
+                *days > 0 && since >= 0 && since % i64::from(*days) == 0
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ingredient, Quantity, Repeat, Task};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn reads_bare_and_measured_ingredients() {
+        let contents = r#"[
+            "salt",
+            (name: "flour", amount: (value: 500, unit: "g")),
+            (name: "yeast"),
+        ]"#;
+        let ingredients: Vec<Ingredient> = ron::from_str(contents).unwrap();
+
+        assert_eq!(
+            ingredients,
+            vec![
+                Ingredient::new("salt".to_string()),
+                Ingredient::with_amount("flour".to_string(), Quantity::new(500.0, "g".to_string())),
+                Ingredient::new("yeast".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_tasks_by_name() {
+        let task: Task = ron::from_str(r#"(name: "Feed guinea pigs")"#).unwrap();
+
+        assert_eq!(task.description, "Feed guinea pigs");
+        assert!(!task.completed);
+        assert_eq!(task.duration, None);
+    }
+
+    #[test]
+    fn reads_recurring_tasks() {
+        let contents = r#"(
+            name: "Take vitamins",
+            recurrence: Some((repeat: weekdays, times: ["08:00:00"])),
+        )"#;
+        let task: Task = ron::from_str(contents).unwrap();
+        let recurrence = task.recurrence.unwrap();
+
+        assert_eq!(recurrence.repeat, Repeat::Weekdays);
+        assert_eq!(recurrence.times.len(), 1);
+    }
+
+    #[test]
+    fn repeats_every_few_days_from_the_start() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+        let repeat = Repeat::Every {
+            days: 3,
+            starting: date(4),
+        };
+
+        assert!(!repeat.occurs_on(date(1)));
+        assert!(repeat.occurs_on(date(4)));
+        assert!(!repeat.occurs_on(date(5)));
+        assert!(repeat.occurs_on(date(10)));
+        // 2024-03-09 is a Saturday
+        assert!(!Repeat::Weekdays.occurs_on(date(9)));
+        assert!(Repeat::Weekdays.occurs_on(date(11)));
+    }
 }
@@ -0,0 +1,351 @@
+use crate::{
+    error::{Error, Result},
+    types::Quantity,
+};
+use serde::{Deserialize, Serialize};
+
+/// The kind of measurement a unit describes.
+/// Quantities can only be converted between units of the same dimension, unless a density is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Mass,
+    Volume,
+    Count,
+}
+
+impl std::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dimension::Mass => write!(f, "mass"),
+            Dimension::Volume => write!(f, "volume"),
+            Dimension::Count => write!(f, "count"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Milligram,
+    Gram,
+    Kilogram,
+    Ounce,
+    Pound,
+    Millilitre,
+    Litre,
+    Teaspoon,
+    Tablespoon,
+    FluidOunce,
+    Cup,
+    Count,
+    Dozen,
+}
+
+impl Unit {
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Milligram | Unit::Gram | Unit::Kilogram | Unit::Ounce | Unit::Pound => {
+                Dimension::Mass
+            }
+            Unit::Millilitre
+            | Unit::Litre
+            | Unit::Teaspoon
+            | Unit::Tablespoon
+            | Unit::FluidOunce
+            | Unit::Cup => Dimension::Volume,
+            Unit::Count | Unit::Dozen => Dimension::Count,
+        }
+    }
+
+    /// The canonical unit of the given dimension. All quantities are normalized to one of these.
+    pub fn canonical(dimension: Dimension) -> Self {
+        match dimension {
+            Dimension::Mass => Unit::Gram,
+            Dimension::Volume => Unit::Millilitre,
+            Dimension::Count => Unit::Count,
+        }
+    }
+
+    /// How many of the canonical unit of this unit's dimension make up one of this unit.
+    pub fn factor(&self) -> f64 {
+        match self {
+            Unit::Milligram => 0.001,
+            Unit::Gram => 1.0,
+            Unit::Kilogram => 1000.0,
+            Unit::Ounce => 28.349523125,
+            Unit::Pound => 453.59237,
+            Unit::Millilitre => 1.0,
+            Unit::Litre => 1000.0,
+            Unit::Teaspoon => 4.92892159375,
+            Unit::Tablespoon => 14.78676478125,
+            Unit::FluidOunce => 29.5735295625,
+            Unit::Cup => 236.5882365,
+            Unit::Count => 1.0,
+            Unit::Dozen => 12.0,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Milligram => "mg",
+            Unit::Gram => "g",
+            Unit::Kilogram => "kg",
+            Unit::Ounce => "oz",
+            Unit::Pound => "lb",
+            Unit::Millilitre => "ml",
+            Unit::Litre => "l",
+            Unit::Teaspoon => "tsp",
+            Unit::Tablespoon => "tbsp",
+            Unit::FluidOunce => "fl oz",
+            Unit::Cup => "cup",
+            Unit::Count => "count",
+            Unit::Dozen => "dozen",
+        }
+    }
+}
+
+impl std::str::FromStr for Unit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let unit = match s.trim().to_lowercase().as_str() {
+            "mg" | "milligram" | "milligrams" => Unit::Milligram,
+            "g" | "gram" | "grams" => Unit::Gram,
+            "kg" | "kilogram" | "kilograms" => Unit::Kilogram,
+            "oz" | "ounce" | "ounces" => Unit::Ounce,
+            "lb" | "lbs" | "pound" | "pounds" => Unit::Pound,
+            "ml" | "millilitre" | "millilitres" | "milliliter" | "milliliters" => Unit::Millilitre,
+            "l" | "litre" | "litres" | "liter" | "liters" => Unit::Litre,
+            "tsp" | "teaspoon" | "teaspoons" => Unit::Teaspoon,
+            "tbsp" | "tablespoon" | "tablespoons" => Unit::Tablespoon,
+            "fl oz" | "fluid ounce" | "fluid ounces" => Unit::FluidOunce,
+            "cup" | "cups" => Unit::Cup,
+            "" | "count" | "piece" | "pieces" | "whole" => Unit::Count,
+            "dozen" => Unit::Dozen,
+            other => return Err(Error::UnknownUnit(other.to_string())),
+        };
+
+        Ok(unit)
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// The density of an ingredient in grams per millilitre.
+/// This allows an ingredient's quantity to be converted between mass and volume.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Density(pub f64);
+
+impl Density {
+    /// Checks the density can be converted with, as converting with zero or a negative density
+    /// would give infinite or negative quantities.
+    fn checked(self) -> Result<Self> {
+        match self.0.is_finite() && self.0 > 0.0 {
+            true => Ok(self),
+            false => Err(Error::InvalidDensity(self.0)),
+        }
+    }
+}
+
+impl TryFrom<f64> for Density {
+    type Error = Error;
+
+    fn try_from(value: f64) -> Result<Self> {
+        Density(value).checked()
+    }
+}
+
+impl From<Density> for f64 {
+    fn from(density: Density) -> Self {
+        density.0
+    }
+}
+
+impl std::hash::Hash for Density {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl PartialEq for Density {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+impl Eq for Density {}
+
+impl std::str::FromStr for Quantity {
+    type Err = Error;
+
+    /// Parses quantities such as `2 cups`, `250ml`, `1 1/2 tbsp` or `3`.
+    /// A quantity without a unit is a count.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '/' || c.is_whitespace()))
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+
+        let mut parts = number.split_whitespace().peekable();
+        if parts.peek().is_none() {
+            return Err(Error::UnparseableQuantity(s.to_string()));
+        }
+        let value = parts.try_fold(0.0, |total, part| {
+            parse_number(part)
+                .map(|value| total + value)
+                .ok_or_else(|| Error::UnparseableQuantity(s.to_string()))
+        })?;
+
+        let unit: Unit = unit.parse()?;
+        Ok(Quantity::new(value, unit.symbol().to_string()))
+    }
+}
+
+/// Parses a decimal number or a fraction such as `1/2`.
+fn parse_number(s: &str) -> Option<f64> {
+    match s.split_once('/') {
+        Some((numerator, denominator)) => {
+            let numerator: f64 = numerator.parse().ok()?;
+            let denominator: f64 = denominator.parse().ok()?;
+            if denominator == 0.0 {
+                return None;
+            }
+            Some(numerator / denominator)
+        }
+        None => s.parse().ok(),
+    }
+}
+
+impl Quantity {
+    pub fn unit(&self) -> Result<Unit> {
+        self.unit.parse()
+    }
+
+    pub fn dimension(&self) -> Result<Dimension> {
+        self.unit().map(|unit| unit.dimension())
+    }
+
+    /// Converts the quantity to the canonical unit of its dimension.
+    pub fn normalize(&self) -> Result<Quantity> {
+        let unit = self.unit()?;
+        let canonical = Unit::canonical(unit.dimension());
+        Ok(Quantity::new(
+            self.value * unit.factor(),
+            canonical.symbol().to_string(),
+        ))
+    }
+
+    /// Converts the quantity to the given unit.
+    /// Conversions between mass and volume are only possible when the density is known.
+    pub fn convert(&self, unit: &str, density: Option<Density>) -> Result<Quantity> {
+        if self.unit == unit {
+            return Ok(self.clone());
+        }
+
+        let from: Unit = self.unit()?;
+        let to: Unit = unit.parse()?;
+        let value = self.value * from.factor();
+        let density = density.map(Density::checked).transpose()?;
+        let value = match (from.dimension(), to.dimension(), density) {
+            (from, to, _) if from == to => value,
+            (Dimension::Mass, Dimension::Volume, Some(Density(density))) => value / density,
+            (Dimension::Volume, Dimension::Mass, Some(Density(density))) => value * density,
+            (from, to, _) => return Err(Error::IncompatibleDimensions { from, to }),
+        };
+
+        Ok(Quantity::new(value / to.factor(), to.symbol().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Density, Dimension, Unit};
+    use crate::{error::Error, types::Quantity};
+
+    fn quantity(value: f64, unit: &str) -> Quantity {
+        Quantity::new(value, unit.to_string())
+    }
+
+    #[test]
+    fn parses_quantities() {
+        let parsed: Quantity = "2 cups".parse().unwrap();
+        assert_eq!(parsed, quantity(2.0, "cup"));
+
+        let parsed: Quantity = "250ml".parse().unwrap();
+        assert_eq!(parsed, quantity(250.0, "ml"));
+
+        let parsed: Quantity = "1 1/2 tbsp".parse().unwrap();
+        assert_eq!(parsed, quantity(1.5, "tbsp"));
+
+        let parsed: Quantity = "3".parse().unwrap();
+        assert_eq!(parsed, quantity(3.0, "count"));
+
+        assert!(matches!(
+            "some flour".parse::<Quantity>(),
+            Err(Error::UnparseableQuantity(_))
+        ));
+        assert!(matches!(
+            "2 handfuls".parse::<Quantity>(),
+            Err(Error::UnknownUnit(_))
+        ));
+    }
+
+    #[test]
+    fn converts_within_dimension() {
+        let cups = quantity(2.0, "cup");
+        assert_eq!(cups.dimension().unwrap(), Dimension::Volume);
+
+        let ml = cups.normalize().unwrap();
+        assert_eq!(ml.unit, "ml");
+        assert!((ml.value - 473.176473).abs() < 1e-6);
+
+        let kg = quantity(1500.0, "g").convert("kg", None).unwrap();
+        assert_eq!(kg, quantity(1.5, "kg"));
+
+        assert_eq!(Unit::canonical(Dimension::Count), Unit::Count);
+    }
+
+    #[test]
+    fn converts_between_mass_and_volume_with_density() {
+        let flour = quantity(1.0, "cup");
+        assert!(matches!(
+            flour.convert("g", None),
+            Err(Error::IncompatibleDimensions {
+                from: Dimension::Volume,
+                to: Dimension::Mass
+            })
+        ));
+
+        let grams = flour.convert("g", Some(Density(0.5))).unwrap();
+        assert!((grams.value - 118.29411825).abs() < 1e-6);
+
+        let ml = quantity(100.0, "g")
+            .convert("ml", Some(Density(0.5)))
+            .unwrap();
+        assert_eq!(ml, quantity(200.0, "ml"));
+
+        assert!(quantity(2.0, "count")
+            .convert("g", Some(Density(0.5)))
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_densities_that_are_not_positive() {
+        for density in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                quantity(100.0, "g").convert("ml", Some(Density(density))),
+                Err(Error::InvalidDensity(_))
+            ));
+        }
+
+        let densities: std::collections::HashMap<String, Density> =
+            ron::from_str(r#"{"flour": 0.5}"#).unwrap();
+        assert_eq!(densities["flour"], Density(0.5));
+        assert!(ron::from_str::<Density>("0.0").is_err());
+        assert!(ron::from_str::<Density>("-0.5").is_err());
+    }
+}
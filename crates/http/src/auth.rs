@@ -0,0 +1,119 @@
+use crate::{error::Error, ServerState};
+use annapurna_models::{
+    household::{Household, Member, Role},
+    User,
+};
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use lockpad_auth::{Claims, PublicKey};
+use sqlx_ulid::Ulid;
+
+/// The header used to pick which of the caller's households a request acts on.
+pub(crate) const HOUSEHOLD_HEADER: &str = "x-household-id";
+
+/// The user making a request, identified by the `sub` of their token.
+/// Requests without a valid token are rejected as unauthorized.
+#[derive(Debug)]
+pub(crate) struct CurrentUser(pub User);
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    S: Send + Sync,
+    ServerState: FromRef<S>,
+    PublicKey: FromRef<S>,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state)
+            .await
+            .map_err(|_| Error::Unauthorized)?;
+
+        let ServerState { pg_pool, .. } = ServerState::from_ref(state);
+        let user = User::find_or_create(&pg_pool, &claims.sub).await?;
+
+        Ok(CurrentUser(user))
+    }
+}
+
+/// The household a request acts on, along with the caller's role in it.
+/// Requests act on the household named by the `x-household-id` header, or the caller's
+/// personal household without one. Households the caller does not belong to are not found.
+/// The access is kept with the request, so extracting it again does not repeat the lookups.
+#[derive(Clone, Debug)]
+pub(crate) struct Access {
+    pub user: User,
+    pub household_id: Ulid,
+    pub role: Role,
+}
+
+impl Access {
+    /// Rejects the request unless the caller has at least the given role.
+    pub(crate) fn require(&self, role: Role) -> crate::error::Result<()> {
+        match self.role >= role {
+            true => Ok(()),
+            false => Err(Error::Forbidden),
+        }
+    }
+
+    /// Finds the household the request acts on and the caller's role in it.
+    async fn lookup<S>(parts: &mut Parts, state: &S) -> Result<Self, Error>
+    where
+        S: Send + Sync,
+        ServerState: FromRef<S>,
+        PublicKey: FromRef<S>,
+    {
+        let CurrentUser(user) = CurrentUser::from_request_parts(parts, state).await?;
+        let ServerState { pg_pool, .. } = ServerState::from_ref(state);
+
+        let household_id = match parts.headers.get(HOUSEHOLD_HEADER) {
+            Some(value) => value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<Ulid>().ok())
+                .ok_or(Error::InvalidHeader(HOUSEHOLD_HEADER))?,
+            None => {
+                let household = Household::personal(&pg_pool, &user).await?;
+                return Ok(Access {
+                    user,
+                    household_id: household.household_id,
+                    role: Role::Owner,
+                });
+            }
+        };
+
+        let member = Member::find(&pg_pool, &household_id, &user.user_id)
+            .await?
+            .ok_or(Error::NotFound)?;
+
+        Ok(Access {
+            user,
+            household_id,
+            role: member.role,
+        })
+    }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for Access
+where
+    S: Send + Sync,
+    ServerState: FromRef<S>,
+    PublicKey: FromRef<S>,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(access) = parts.extensions.get::<Access>() {
+            return Ok(access.clone());
+        }
+
+        let access = Self::lookup(parts, state).await?;
+        parts.extensions.insert(access.clone());
+
+        Ok(access)
+    }
+}
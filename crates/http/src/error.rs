@@ -1,3 +1,9 @@
+use crate::validation::FieldError;
+use annapurna_logic::{error::Error as LogicError, recipe::Shortfall};
+use annapurna_models::error::Error as ModelsError;
+use axum::{http::StatusCode, Json};
+use sqlx::error::ErrorKind;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error(transparent)]
@@ -10,26 +16,219 @@ pub enum Error {
     FromUtf8(#[from] std::string::FromUtf8Error),
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
 
     #[error(transparent)]
     AnnupurnaModels(#[from] annapurna_models::error::Error),
+    #[error(transparent)]
+    AnnapurnaData(#[from] annapurna_data::error::Error),
+    #[error(transparent)]
+    AnnapurnaLogic(#[from] annapurna_logic::error::Error),
 
     #[error("Failed to build server struct")]
     ServerBuilder,
     #[error("Invalid html file")]
     InvalidHtml(String),
+    #[error("Not found")]
+    NotFound,
+    #[error("Method not allowed")]
+    MethodNotAllowed,
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Forbidden")]
+    Forbidden,
+    #[error("Invalid {0} header")]
+    InvalidHeader(&'static str),
+    #[error("Invalid request")]
+    Validation(Vec<FieldError>),
+    #[error("Not enough ingredients")]
+    Shortfalls(Vec<Shortfall>),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The kinds of error the API reports. Each has a stable code that clients can match on,
+/// independently of the message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    /// The route does not handle the request's method.
+    MethodNotAllowed,
+    /// The request was malformed or asked for something impossible.
+    Validation,
+    Unauthorized,
+    /// The caller is signed in, but is not allowed to do what they asked.
+    Forbidden,
+    /// The request clashes with data that already exists.
+    Conflict,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::Validation => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// The JSON body of every error response.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+    /// The problems with each field of the request, for validation errors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    /// What the inventory is short of, when there is not enough of it to do what was asked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shortfalls: Vec<Shortfall>,
+}
+
+impl Error {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::NotFound => ErrorCode::NotFound,
+            Error::MethodNotAllowed => ErrorCode::MethodNotAllowed,
+            Error::Unauthorized | Error::LockpadAuth(_) => ErrorCode::Unauthorized,
+            Error::Forbidden => ErrorCode::Forbidden,
+            Error::Shortfalls(_) => ErrorCode::Conflict,
+            Error::AnnapurnaLogic(LogicError::UnknownRecipe(_)) => ErrorCode::NotFound,
+            // quantities that cannot be parsed or converted are the caller's fault
+            Error::AnnapurnaData(_)
+            | Error::AnnapurnaLogic(_)
+            | Error::InvalidHeader(_)
+            | Error::Validation(_) => ErrorCode::Validation,
+            Error::AnnupurnaModels(error) => match error {
+                ModelsError::Sqlx(error) => sqlx_code(error),
+                ModelsError::InvalidUniqueField => ErrorCode::Conflict,
+                ModelsError::ModelFieldsMissing(_)
+                | ModelsError::InvalidMealSlot(_)
+                | ModelsError::InvalidRole(_) => ErrorCode::Validation,
+                ModelsError::SerdeJson(_) | ModelsError::Data(_) => ErrorCode::Internal,
+            },
+            Error::Sqlx(error) => sqlx_code(error),
+            _ => ErrorCode::Internal,
+        }
+    }
+}
+
+/// Classifies database errors caused by the request, such as inserting a duplicate key.
+fn sqlx_code(error: &sqlx::Error) -> ErrorCode {
+    match error {
+        sqlx::Error::RowNotFound => ErrorCode::NotFound,
+        sqlx::Error::Database(error) => match error.kind() {
+            ErrorKind::UniqueViolation => ErrorCode::Conflict,
+            ErrorKind::ForeignKeyViolation
+            | ErrorKind::NotNullViolation
+            | ErrorKind::CheckViolation => ErrorCode::Validation,
+            _ => ErrorCode::Internal,
+        },
+        _ => ErrorCode::Internal,
+    }
+}
+
+impl Error {
+    /// The body reporting the error to the caller.
+    /// Internal details are logged rather than shown to the caller.
+    pub fn body(&self) -> ErrorBody {
+        let code = self.code();
+        let message = match code {
+            ErrorCode::Internal => {
+                tracing::error!(?self, "internal error");
+                "Internal server error".to_string()
+            }
+            _ => {
+                tracing::info!(?self, "request error");
+                self.to_string()
+            }
+        };
+
+        let fields = match self {
+            Error::Validation(fields) => fields.clone(),
+            _ => Vec::new(),
+        };
+        let shortfalls = match self {
+            Error::Shortfalls(shortfalls) => shortfalls.clone(),
+            _ => Vec::new(),
+        };
+
+        ErrorBody {
+            code,
+            message,
+            fields,
+            shortfalls,
+        }
+    }
+}
+
 impl axum::response::IntoResponse for Error {
     fn into_response(self) -> axum::response::Response {
-        tracing::info!(?self, "error response");
-        #[allow(clippy::match_single_binding)]
-        let status = match self {
-            _ => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-        };
+        let body = self.body();
+
+        (body.code.status(), Json(body)).into_response()
+    }
+}
 
-        (status, self.to_string()).into_response()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_errors_to_codes() {
+        assert_eq!(Error::NotFound.code(), ErrorCode::NotFound);
+        assert_eq!(
+            Error::Sqlx(sqlx::Error::RowNotFound).code(),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            Error::from(ModelsError::ModelFieldsMissing("name")).code(),
+            ErrorCode::Validation
+        );
+        assert_eq!(
+            Error::from(ModelsError::InvalidUniqueField).code(),
+            ErrorCode::Conflict
+        );
+        assert_eq!(
+            Error::from(LogicError::UnknownRecipe("bread".to_string()))
+                .code()
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            Error::from(LogicError::CyclicSteps("bread".to_string())).code(),
+            ErrorCode::Validation
+        );
+        assert_eq!(
+            Error::Shortfalls(Vec::new()).code().status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(ErrorCode::Validation.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            Error::ServerBuilder.code().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn uses_stable_codes() {
+        let body = ErrorBody {
+            code: ErrorCode::NotFound,
+            message: "Not found".to_string(),
+            fields: Vec::new(),
+            shortfalls: Vec::new(),
+        };
+        assert_eq!(
+            serde_json::to_string(&body).unwrap(),
+            r#"{"code":"not_found","message":"Not found"}"#
+        );
     }
 }
@@ -0,0 +1,212 @@
+//! Extractors that report a request they cannot read as an [`Error`], so the caller gets the
+//! same JSON body as for any other error rather than axum's plain text.
+
+use crate::{error::Error, validation::FieldError};
+use axum::{
+    body::Bytes,
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRequest, FromRequestParts, Request,
+    },
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+
+/// A JSON body, which is also sent as JSON when used as a response.
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(Error))]
+pub(crate) struct Json<T>(pub T);
+
+impl<T: serde::Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+/// A JSON body that may be left out, in which case the value's default is used.
+pub(crate) struct OptionalJson<T>(pub T);
+
+#[axum::async_trait]
+impl<S, T> FromRequest<S> for OptionalJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Default,
+{
+    type Rejection = Error;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Error> {
+        if request.headers().contains_key(header::CONTENT_TYPE) {
+            let Json(value) = Json::from_request(request, state).await?;
+            return Ok(OptionalJson(value));
+        }
+
+        let body = Bytes::from_request(request, state)
+            .await
+            .map_err(|rejection| unreadable("body", rejection.body_text()))?;
+        match body.is_empty() {
+            true => Ok(OptionalJson(T::default())),
+            false => Err(unreadable(
+                "body",
+                "Expected request with `Content-Type: application/json`".to_string(),
+            )),
+        }
+    }
+}
+
+/// The query string, deserialized.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(Error))]
+pub(crate) struct Query<T>(pub T);
+
+/// The parameters captured from the path, deserialized.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(Error))]
+pub(crate) struct Path<T>(pub T);
+
+/// Reports a part of the request that cannot be read as a problem with that part.
+fn unreadable(field: &str, message: String) -> Error {
+    Error::Validation(vec![FieldError {
+        field: field.to_string(),
+        message,
+    }])
+}
+
+impl From<JsonRejection> for Error {
+    fn from(rejection: JsonRejection) -> Self {
+        unreadable("body", rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for Error {
+    fn from(rejection: QueryRejection) -> Self {
+        unreadable("query", rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for Error {
+    fn from(rejection: PathRejection) -> Self {
+        unreadable("path", rejection.body_text())
+    }
+}
+
+/// Answers requests that do not match any route.
+pub(crate) async fn not_found() -> Error {
+    Error::NotFound
+}
+
+/// Gives the responses axum makes for methods a route does not handle the JSON error body,
+/// keeping the `Allow` header that lists the methods it does.
+pub(crate) async fn method_not_allowed(response: Response) -> Response {
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED
+        || response.headers().contains_key(header::CONTENT_TYPE)
+    {
+        return response;
+    }
+
+    let allow = response.headers().get(header::ALLOW).cloned();
+    let mut response = Error::MethodNotAllowed.into_response();
+    if let Some(allow) = allow {
+        response.headers_mut().insert(header::ALLOW, allow);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ErrorBody, ErrorCode};
+    use axum::{
+        body::Body,
+        http::{HeaderValue, Request},
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    #[derive(serde::Deserialize)]
+    struct Params {
+        #[allow(dead_code)]
+        limit: usize,
+    }
+
+    fn router() -> Router {
+        Router::new()
+            .route(
+                "/items/:id",
+                get(|Path(_): Path<u32>, Query(_): Query<Params>| async {}),
+            )
+            .fallback(not_found)
+            .layer(axum::middleware::map_response(method_not_allowed))
+    }
+
+    async fn error(method: &str, uri: &str) -> (StatusCode, Option<HeaderValue>, ErrorBody) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let response = router().oneshot(request).await.unwrap();
+
+        let status = response.status();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let allow = response.headers().get(header::ALLOW).cloned();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, allow, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[derive(Debug, Default, PartialEq, serde::Deserialize)]
+    struct Options {
+        #[serde(default)]
+        force: bool,
+    }
+
+    async fn options(content_type: Option<&str>, body: &'static str) -> Result<Options, Error> {
+        let mut request = Request::builder().method("POST").uri("/");
+        if let Some(content_type) = content_type {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+        let request = request.body(Body::from(body)).unwrap();
+
+        let OptionalJson(options) = OptionalJson::from_request(request, &()).await?;
+        Ok(options)
+    }
+
+    #[tokio::test]
+    async fn defaults_bodies_that_are_left_out() {
+        assert_eq!(options(None, "").await.unwrap(), Options::default());
+        assert_eq!(
+            options(Some("application/json"), r#"{"force":true}"#)
+                .await
+                .unwrap(),
+            Options { force: true }
+        );
+
+        assert!(options(None, r#"{"force":true}"#).await.is_err());
+        assert!(options(Some("application/json"), r#"{"force":"yes"}"#)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn reports_every_unreadable_request_as_json() {
+        let (status, _, body) = error("GET", "/items/1?limit=lots").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.fields[0].field, "query");
+
+        let (status, _, body) = error("GET", "/items/one?limit=1").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.fields[0].field, "path");
+
+        let (status, _, body) = error("GET", "/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body.code, ErrorCode::NotFound);
+
+        let (status, allow, body) = error("POST", "/items/1").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(body.code, ErrorCode::MethodNotAllowed);
+        assert!(allow.is_some());
+    }
+}
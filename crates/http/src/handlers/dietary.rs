@@ -0,0 +1,39 @@
+use crate::{auth::CurrentUser, error::Result, extract::Json, ServerState};
+use annapurna_data::dietary::DietaryProfile;
+use annapurna_models::dietary_profile::UserDietaryProfile;
+use axum::extract::State;
+use sqlx_ulid::Ulid;
+
+/// The caller's dietary profile. Users who have not set one eat anything.
+pub(crate) async fn get_dietary_profile(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+) -> Result<Json<DietaryProfile>> {
+    Ok(Json(dietary_profile(&pg_pool, &user.user_id).await?))
+}
+
+/// Replaces the caller's dietary profile, which rules out the recipes suggested to them.
+pub(crate) async fn update_dietary_profile(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Json(mut payload): Json<DietaryProfile>,
+) -> Result<Json<DietaryProfile>> {
+    payload.diets.sort();
+    payload.diets.dedup();
+    payload.avoid.sort();
+    payload.avoid.dedup();
+
+    let profile = UserDietaryProfile::new(user.user_id, payload, chrono::Utc::now());
+    profile.save(&pg_pool).await?;
+
+    Ok(Json(profile.profile))
+}
+
+pub(crate) async fn dietary_profile(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    user_id: &Ulid,
+) -> Result<DietaryProfile> {
+    let profile = UserDietaryProfile::by_user(pg_pool, user_id).await?;
+
+    Ok(profile.map(|profile| profile.profile).unwrap_or_default())
+}
@@ -0,0 +1,254 @@
+use crate::{
+    auth::CurrentUser,
+    error::{Error, Result},
+    extract::{Json, Path},
+    validation::{Valid, Validate, Validator},
+    ServerState,
+};
+use annapurna_models::{
+    entity::Builder,
+    household::{Household, Invitation, Member, Membership, Role},
+};
+use axum::{extract::State, http::StatusCode};
+use sqlx::types::chrono;
+use sqlx_ulid::Ulid;
+
+/// Lists the households the caller belongs to, including their personal household.
+pub(crate) async fn list_households(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+) -> Result<Json<Vec<Membership>>> {
+    Household::personal(&pg_pool, &user).await?;
+    let memberships = Household::for_user(&pg_pool, &user.user_id).await?;
+
+    Ok(Json(memberships))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct CreateHousehold {
+    pub household_id: Option<Ulid>,
+    pub name: String,
+}
+
+impl Validate for CreateHousehold {
+    fn validate(&self, validator: &mut Validator) {
+        validator.not_blank("name", &self.name);
+    }
+}
+
+/// Creates a household with the caller as its owner.
+pub(crate) async fn create_household(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Valid(payload): Valid<CreateHousehold>,
+) -> Result<Json<Household>> {
+    let household = Household::builder()
+        .household_id(payload.household_id.unwrap_or_else(Ulid::generate))
+        .name(payload.name)
+        .created_at(chrono::Utc::now())
+        .build()?;
+
+    household.create(&pg_pool, &user.user_id).await?;
+
+    Ok(Json(household))
+}
+
+/// Deletes a household along with everything in it. Personal households cannot be deleted.
+pub(crate) async fn delete_household(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Ulid>,
+) -> Result<StatusCode> {
+    require_role(&pg_pool, &id, &user.user_id, Role::Owner).await?;
+
+    let household = Household::by_id(&pg_pool, &id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if household.personal_for.is_some() {
+        return Err(Error::Forbidden);
+    }
+
+    match Household::delete(&pg_pool, &id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(Error::NotFound),
+    }
+}
+
+pub(crate) async fn list_members(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Ulid>,
+) -> Result<Json<Vec<Member>>> {
+    require_role(&pg_pool, &id, &user.user_id, Role::Viewer).await?;
+
+    let members = Member::for_household(&pg_pool, &id).await?;
+
+    Ok(Json(members))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct UpdateMember {
+    pub role: Role,
+}
+
+/// Changes a member's role. Owners cannot change their own role, so that a household
+/// is never left without an owner.
+pub(crate) async fn update_member(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Path((id, user_id)): Path<(Ulid, Ulid)>,
+    Json(payload): Json<UpdateMember>,
+) -> Result<Json<Member>> {
+    require_role(&pg_pool, &id, &user.user_id, Role::Owner).await?;
+    if user_id == user.user_id {
+        return Err(Error::Forbidden);
+    }
+
+    if !Member::set_role(&pg_pool, &id, &user_id, payload.role).await? {
+        return Err(Error::NotFound);
+    }
+
+    let member = Member::find(&pg_pool, &id, &user_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(member))
+}
+
+/// Removes a member from a household. Owners may remove anyone else,
+/// and everyone but owners may leave by removing themselves.
+pub(crate) async fn remove_member(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Path((id, user_id)): Path<(Ulid, Ulid)>,
+) -> Result<StatusCode> {
+    let role = require_role(&pg_pool, &id, &user.user_id, Role::Viewer).await?;
+    let allowed = match user_id == user.user_id {
+        true => !role.can_manage(),
+        false => role.can_manage(),
+    };
+    if !allowed {
+        return Err(Error::Forbidden);
+    }
+
+    match Member::delete(&pg_pool, &id, &user_id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(Error::NotFound),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct CreateInvitation {
+    /// The identifier of the user to invite, as it appears in their auth token.
+    pub identifier: String,
+    pub role: Role,
+}
+
+impl Validate for CreateInvitation {
+    fn validate(&self, validator: &mut Validator) {
+        validator.not_blank("identifier", &self.identifier);
+    }
+}
+
+/// Invites a user to join a household with the given role.
+/// Members already belong to it, so their roles are changed with [`update_member`] instead.
+pub(crate) async fn invite_member(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Ulid>,
+    Valid(payload): Valid<CreateInvitation>,
+) -> Result<Json<Invitation>> {
+    require_role(&pg_pool, &id, &user.user_id, Role::Owner).await?;
+
+    let members = Member::for_household(&pg_pool, &id).await?;
+    let mut validator = Validator::new(None);
+    validator.check(
+        "identifier",
+        !members
+            .iter()
+            .any(|member| member.identifier == payload.identifier),
+        "already belongs to the household",
+    );
+    validator.finish()?;
+
+    let invitation = Invitation::builder()
+        .household_id(id)
+        .identifier(payload.identifier)
+        .role(payload.role)
+        .invited_by(user.user_id)
+        .created_at(chrono::Utc::now())
+        .build()?;
+
+    invitation.create(&pg_pool).await?;
+
+    Ok(Json(invitation))
+}
+
+/// Lists the invitations addressed to the caller.
+pub(crate) async fn list_invitations(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+) -> Result<Json<Vec<Invitation>>> {
+    let invitations = Invitation::for_identifier(&pg_pool, &user.identifier).await?;
+
+    Ok(Json(invitations))
+}
+
+/// Joins the household the caller was invited to.
+pub(crate) async fn accept_invitation(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Ulid>,
+) -> Result<Json<Member>> {
+    let invitation = addressed_invitation(&pg_pool, &id, &user.identifier).await?;
+    invitation.accept(&pg_pool, &user.user_id).await?;
+
+    let member = Member::find(&pg_pool, &invitation.household_id, &user.user_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(member))
+}
+
+pub(crate) async fn decline_invitation(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Ulid>,
+) -> Result<StatusCode> {
+    addressed_invitation(&pg_pool, &id, &user.identifier).await?;
+
+    match Invitation::delete(&pg_pool, &id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(Error::NotFound),
+    }
+}
+
+/// Looks up the user's role in a household, rejecting them unless it is at least the given one.
+/// Households the user does not belong to are treated as missing.
+async fn require_role(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    household_id: &Ulid,
+    user_id: &Ulid,
+    role: Role,
+) -> Result<Role> {
+    let member = Member::find(pg_pool, household_id, user_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    match member.role >= role {
+        true => Ok(member.role),
+        false => Err(Error::Forbidden),
+    }
+}
+
+/// Fetches an invitation, treating invitations addressed to someone else as missing.
+async fn addressed_invitation(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    id: &Ulid,
+    identifier: &str,
+) -> Result<Invitation> {
+    Invitation::by_id(pg_pool, id)
+        .await?
+        .filter(|invitation| invitation.identifier == identifier)
+        .ok_or(Error::NotFound)
+}
@@ -1,24 +1,60 @@
-use crate::{error::Result, ServerState};
+use crate::{
+    auth::Access,
+    error::{Error, ErrorBody, Result},
+    extract::{Json, Path, Query},
+    pagination::{Page, PageParams},
+    validation::{Valid, Validate, Validator},
+    ServerState,
+};
+use annapurna_data::types::{Ingredient, Quantity};
 use annapurna_models::{
     entity::Builder,
-    inventory::{Builder as InventoryBuilder, Inventory},
+    household::Role,
+    inventory::{Builder as InventoryBuilder, Filter, Inventory, Sort},
 };
-use axum::{extract::State, Json};
+use axum::{extract::State, http::StatusCode};
 use sqlx::types::chrono::{self, DateTime, Utc};
 use sqlx_ulid::Ulid;
 
+/// Lists a page of the household's inventory matching the filter, in the requested order.
 pub(crate) async fn list_inventory(
     State(ServerState { pg_pool, .. }): State<ServerState>,
-) -> Result<Json<Vec<Inventory>>> {
-    let pagination = annapurna_models::Pagination {
-        last_key: None,
-        count: 10,
-    };
+    access: Access,
+    Query(filter): Query<Filter>,
+    Query(sort): Query<Sort>,
+    Query(params): Query<PageParams>,
+) -> Result<Json<Page<Inventory>>> {
+    let query =
+        Inventory::query(&pg_pool, &access.household_id, &filter, sort, params.into()).await?;
 
-    let query = Inventory::query(&pg_pool, pagination).await?;
-    let (items, _pagination) = query;
+    Ok(Json(query.into()))
+}
 
-    Ok(Json(items))
+/// Lists the household's whole inventory as ingredients, so that recipes can be checked against it.
+/// Quantities that cannot be parsed are treated as unknown amounts.
+pub(crate) async fn available_ingredients(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    household_id: &Ulid,
+) -> Result<Vec<Ingredient>> {
+    let pagination = annapurna_models::Pagination::all();
+    let (items, _pagination) = Inventory::query(
+        pg_pool,
+        household_id,
+        &Filter::default(),
+        Sort::default(),
+        pagination,
+    )
+    .await?;
+
+    Ok(items.iter().map(as_ingredient).collect())
+}
+
+/// The inventory item as an ingredient, with an unknown amount if its quantity cannot be parsed.
+pub(crate) fn as_ingredient(item: &Inventory) -> Ingredient {
+    match item.quantity.parse::<Quantity>() {
+        Ok(amount) => Ingredient::with_amount(item.ingredient_type.clone(), amount),
+        Err(_) => Ingredient::new(item.ingredient_type.clone()),
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -29,22 +65,291 @@ pub(crate) struct CreateInventory {
     pub expiration_date: Option<DateTime<Utc>>,
 }
 
+impl Validate for CreateInventory {
+    const CHECKS_INGREDIENTS: bool = true;
+
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .ingredient("ingredient_type", &self.ingredient_type)
+            .quantity("quantity", &self.quantity)
+            .not_in_past("expiration_date", self.expiration_date);
+    }
+}
+
 pub(crate) async fn create_inventory(
     State(ServerState { pg_pool, .. }): State<ServerState>,
-    payload: axum::extract::Json<CreateInventory>,
+    access: Access,
+    Valid(payload): Valid<CreateInventory>,
 ) -> Result<Json<Inventory>> {
+    access.require(Role::Editor)?;
+
+    let item = new_item(&access, payload)?;
+    item.create(&pg_pool).await?;
+
+    Ok(Json(item))
+}
+
+fn new_item(access: &Access, payload: CreateInventory) -> Result<Inventory> {
     let created_at = chrono::Utc::now();
     let inventory_id = payload.inventory_id.unwrap_or_else(Ulid::generate);
+    // store quantities in a consistent format so they can be compared later
+    let quantity: Quantity = payload.quantity.parse()?;
 
     let item = InventoryBuilder::default()
         .inventory_id(inventory_id)
-        .ingredient_type(payload.ingredient_type.clone())
-        .quantity(payload.quantity.clone())
+        .owner_id(access.user.user_id)
+        .household_id(access.household_id)
+        .ingredient_type(payload.ingredient_type)
+        .quantity(quantity.to_string())
         .created_at(created_at)
         .expiration_date(payload.expiration_date)
         .build()?;
 
-    item.create(&pg_pool).await?;
+    Ok(item)
+}
+
+pub(crate) async fn get_inventory(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<Json<Inventory>> {
+    let item = household_item(&pg_pool, &access, &id).await?;
 
     Ok(Json(item))
 }
+
+/// Changes to an inventory item. Fields that are left out are kept as they are,
+/// and the expiration date is removed when it is given as `null`.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct UpdateInventory {
+    pub ingredient_type: Option<String>,
+    pub quantity: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub expiration_date: Option<Option<DateTime<Utc>>>,
+}
+
+/// Deserializes a field that is present, even when it is `null`, so that it can be told apart
+/// from a field that was left out.
+pub(crate) fn present<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl Validate for UpdateInventory {
+    const CHECKS_INGREDIENTS: bool = true;
+
+    fn validate(&self, validator: &mut Validator) {
+        if let Some(ingredient_type) = &self.ingredient_type {
+            validator.ingredient("ingredient_type", ingredient_type);
+        }
+        if let Some(quantity) = &self.quantity {
+            validator.quantity("quantity", quantity);
+        }
+        if let Some(expiration_date) = self.expiration_date {
+            validator.not_in_past("expiration_date", expiration_date);
+        }
+    }
+}
+
+impl UpdateInventory {
+    fn apply(self, item: &mut Inventory) -> Result<()> {
+        if let Some(ingredient_type) = self.ingredient_type {
+            item.ingredient_type = ingredient_type;
+        }
+        if let Some(quantity) = self.quantity {
+            item.quantity = quantity.parse::<Quantity>()?.to_string();
+        }
+        if let Some(expiration_date) = self.expiration_date {
+            item.expiration_date = expiration_date;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) async fn update_inventory(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+    Valid(payload): Valid<UpdateInventory>,
+) -> Result<Json<Inventory>> {
+    access.require(Role::Editor)?;
+
+    let mut item = household_item(&pg_pool, &access, &id).await?;
+    payload.apply(&mut item)?;
+
+    match item.update(&pg_pool).await? {
+        true => Ok(Json(item)),
+        false => Err(Error::NotFound),
+    }
+}
+
+pub(crate) async fn delete_inventory(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<StatusCode> {
+    access.require(Role::Editor)?;
+
+    household_item(&pg_pool, &access, &id).await?;
+
+    match Inventory::delete(&pg_pool, &id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(Error::NotFound),
+    }
+}
+
+/// A single change within a bulk request.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub(crate) enum BulkOperation {
+    Create(CreateInventory),
+    Update {
+        inventory_id: Ulid,
+        #[serde(flatten)]
+        changes: UpdateInventory,
+    },
+    Delete {
+        inventory_id: Ulid,
+    },
+}
+
+impl Validate for BulkOperation {
+    const CHECKS_INGREDIENTS: bool = true;
+
+    fn validate(&self, validator: &mut Validator) {
+        match self {
+            BulkOperation::Create(payload) => payload.validate(validator),
+            BulkOperation::Update { changes, .. } => changes.validate(validator),
+            BulkOperation::Delete { .. } => {}
+        }
+    }
+}
+
+impl BulkOperation {
+    fn inventory_id(&self) -> Option<Ulid> {
+        match self {
+            BulkOperation::Create(payload) => payload.inventory_id,
+            BulkOperation::Update { inventory_id, .. } => Some(*inventory_id),
+            BulkOperation::Delete { inventory_id } => Some(*inventory_id),
+        }
+    }
+}
+
+/// The outcome of a single change within a bulk request, in the same position as the change.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub(crate) enum BulkResult {
+    Created {
+        item: Inventory,
+    },
+    Updated {
+        item: Inventory,
+    },
+    Deleted {
+        inventory_id: Ulid,
+    },
+    Failed {
+        inventory_id: Option<Ulid>,
+        error: ErrorBody,
+    },
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct BulkResponse {
+    /// Whether the changes were saved, which only happens when every one of them succeeds.
+    pub committed: bool,
+    pub results: Vec<BulkResult>,
+}
+
+/// Applies a list of creates, updates and deletes in a single transaction.
+/// Every change is attempted so that each one gets a result, but nothing is saved
+/// unless all of them succeed.
+pub(crate) async fn bulk_inventory(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Valid(operations): Valid<Vec<BulkOperation>>,
+) -> Result<(StatusCode, Json<BulkResponse>)> {
+    access.require(Role::Editor)?;
+
+    let mut transaction = pg_pool.begin().await?;
+    let mut results = Vec::with_capacity(operations.len());
+    let mut committed = true;
+
+    for operation in operations {
+        let inventory_id = operation.inventory_id();
+
+        // each change gets a savepoint, so a failing one does not abort the others
+        let mut savepoint = sqlx::Connection::begin(&mut *transaction).await?;
+        match apply_operation(&mut savepoint, &access, operation).await {
+            Ok(result) => {
+                savepoint.commit().await?;
+                results.push(result);
+            }
+            Err(error) => {
+                savepoint.rollback().await?;
+                committed = false;
+                results.push(BulkResult::Failed {
+                    inventory_id,
+                    error: error.body(),
+                });
+            }
+        }
+    }
+
+    let status = match committed {
+        true => {
+            transaction.commit().await?;
+            StatusCode::OK
+        }
+        false => {
+            transaction.rollback().await?;
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+    };
+
+    Ok((status, Json(BulkResponse { committed, results })))
+}
+
+async fn apply_operation(
+    conn: &mut sqlx::PgConnection,
+    access: &Access,
+    operation: BulkOperation,
+) -> Result<BulkResult> {
+    match operation {
+        BulkOperation::Create(payload) => {
+            let item = new_item(access, payload)?;
+            item.create(&mut *conn).await?;
+            Ok(BulkResult::Created { item })
+        }
+        BulkOperation::Update {
+            inventory_id,
+            changes,
+        } => {
+            let mut item = household_item(&mut *conn, access, &inventory_id).await?;
+            changes.apply(&mut item)?;
+            item.update(&mut *conn).await?;
+            Ok(BulkResult::Updated { item })
+        }
+        BulkOperation::Delete { inventory_id } => {
+            household_item(&mut *conn, access, &inventory_id).await?;
+            Inventory::delete(&mut *conn, &inventory_id).await?;
+            Ok(BulkResult::Deleted { inventory_id })
+        }
+    }
+}
+
+/// Fetches an inventory item, treating items that belong to other households as missing.
+async fn household_item<'c, E: sqlx::PgExecutor<'c>>(
+    executor: E,
+    access: &Access,
+    id: &Ulid,
+) -> Result<Inventory> {
+    Inventory::by_id(executor, id)
+        .await?
+        .filter(|item| item.household_id == access.household_id)
+        .ok_or(Error::NotFound)
+}
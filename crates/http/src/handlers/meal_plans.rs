@@ -0,0 +1,191 @@
+use crate::{
+    auth::Access,
+    error::{Error, Result},
+    extract::{Json, Path, Query},
+    handlers::{inventory::available_ingredients, recipes::recipe_manager, servings},
+    validation::{Valid, Validate, Validator},
+    ServerState,
+};
+use annapurna_logic::planning::{check_plan, DayCheck, PlannedMeal};
+use annapurna_models::{
+    entity::Builder,
+    household::Role,
+    meal_plan::{MealPlan, MealSlot},
+    recipe::Recipe,
+};
+use axum::{extract::State, http::StatusCode};
+use sqlx::types::chrono::{self, NaiveDate};
+use sqlx_ulid::Ulid;
+use std::num::NonZeroU32;
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+pub(crate) async fn list_meal_plans(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Query(range): Query<DateRange>,
+) -> Result<Json<Vec<MealPlan>>> {
+    let meal_plans =
+        MealPlan::query(&pg_pool, &access.household_id, range.from, range.until).await?;
+
+    Ok(Json(meal_plans))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct CreateMealPlan {
+    pub meal_plan_id: Option<Ulid>,
+    pub date: NaiveDate,
+    pub meal: MealSlot,
+    pub recipe: String,
+    pub servings: Option<NonZeroU32>,
+}
+
+impl Validate for CreateMealPlan {
+    fn validate(&self, validator: &mut Validator) {
+        validator.not_blank("recipe", &self.recipe);
+    }
+}
+
+pub(crate) async fn create_meal_plan(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Valid(payload): Valid<CreateMealPlan>,
+) -> Result<Json<MealPlan>> {
+    access.require(Role::Editor)?;
+    ensure_recipe_exists(&pg_pool, &access, &payload.recipe).await?;
+
+    let meal_plan = MealPlan::builder()
+        .meal_plan_id(payload.meal_plan_id.unwrap_or_else(Ulid::generate))
+        .owner_id(access.user.user_id)
+        .household_id(access.household_id)
+        .date(payload.date)
+        .meal(payload.meal)
+        .recipe(payload.recipe)
+        .servings(servings(payload.servings))
+        .created_at(chrono::Utc::now())
+        .build()?;
+
+    meal_plan.create(&pg_pool).await?;
+
+    Ok(Json(meal_plan))
+}
+
+pub(crate) async fn get_meal_plan(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<Json<MealPlan>> {
+    let meal_plan = household_meal_plan(&pg_pool, &access, &id).await?;
+
+    Ok(Json(meal_plan))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct UpdateMealPlan {
+    pub date: NaiveDate,
+    pub meal: MealSlot,
+    pub recipe: String,
+    pub servings: Option<NonZeroU32>,
+}
+
+impl Validate for UpdateMealPlan {
+    fn validate(&self, validator: &mut Validator) {
+        validator.not_blank("recipe", &self.recipe);
+    }
+}
+
+pub(crate) async fn update_meal_plan(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+    Valid(payload): Valid<UpdateMealPlan>,
+) -> Result<Json<MealPlan>> {
+    access.require(Role::Editor)?;
+    ensure_recipe_exists(&pg_pool, &access, &payload.recipe).await?;
+
+    let mut meal_plan = household_meal_plan(&pg_pool, &access, &id).await?;
+    meal_plan.date = payload.date;
+    meal_plan.meal = payload.meal;
+    meal_plan.recipe = payload.recipe;
+    meal_plan.servings = servings(payload.servings);
+
+    match meal_plan.update(&pg_pool).await? {
+        true => Ok(Json(meal_plan)),
+        false => Err(Error::NotFound),
+    }
+}
+
+pub(crate) async fn delete_meal_plan(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<StatusCode> {
+    access.require(Role::Editor)?;
+    household_meal_plan(&pg_pool, &access, &id).await?;
+
+    match MealPlan::delete(&pg_pool, &id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(Error::NotFound),
+    }
+}
+
+/// Checks each of the household's planned days from today on against the household's inventory
+/// projected to be left for it, flagging the days that will be short of ingredients.
+pub(crate) async fn check_meal_plans(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+    access: Access,
+    Query(range): Query<DateRange>,
+) -> Result<Json<Vec<DayCheck>>> {
+    // the inventory is as it is today: meals planned before then have already been cooked or
+    // skipped, while the ones planned before the range still use it up
+    let today = chrono::Utc::now().date_naive();
+    let meal_plans =
+        MealPlan::query(&pg_pool, &access.household_id, Some(today), range.until).await?;
+    let meals = meal_plans
+        .into_iter()
+        .map(|meal_plan| PlannedMeal {
+            date: meal_plan.date,
+            recipe: meal_plan.recipe,
+            servings: meal_plan.servings.max(1) as u32,
+        })
+        .collect::<Vec<_>>();
+
+    let inventory = available_ingredients(&pg_pool, &access.household_id).await?;
+    let manager = recipe_manager(&pg_pool, &facts, &access, inventory).await?;
+    let checks = check_plan(&manager, &meals)?
+        .into_iter()
+        .filter(|check| check.date >= range.from.map_or(today, |from| from.max(today)))
+        .collect();
+
+    Ok(Json(checks))
+}
+
+/// Fetches a meal plan, treating plans that belong to other households as missing.
+async fn household_meal_plan(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    access: &Access,
+    id: &Ulid,
+) -> Result<MealPlan> {
+    MealPlan::by_id(pg_pool, id)
+        .await?
+        .filter(|meal_plan| meal_plan.household_id == access.household_id)
+        .ok_or(Error::NotFound)
+}
+
+async fn ensure_recipe_exists(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    access: &Access,
+    recipe: &str,
+) -> Result<()> {
+    match Recipe::by_name(pg_pool, &access.household_id, recipe)
+        .await?
+        .is_some()
+    {
+        true => Ok(()),
+        false => Err(annapurna_logic::error::Error::UnknownRecipe(recipe.to_string()).into()),
+    }
+}
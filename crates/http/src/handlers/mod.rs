@@ -1 +1,16 @@
+pub mod dietary;
+pub mod households;
 pub mod inventory;
+pub mod meal_plans;
+pub mod nutrition;
+pub mod recipes;
+pub mod shopping_lists;
+pub mod tasks;
+
+use std::num::NonZeroU32;
+
+/// The servings to store for a recipe or meal plan, which default to one and are capped at the
+/// most the database can hold.
+pub(crate) fn servings(servings: Option<NonZeroU32>) -> i32 {
+    servings.map_or(1, |servings| servings.get().min(i32::MAX as u32) as i32)
+}
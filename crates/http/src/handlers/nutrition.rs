@@ -0,0 +1,57 @@
+use crate::{error::Result, extract::Json, ServerState};
+use annapurna_data::{nutrition::Nutrition, Facts};
+use annapurna_models::nutrition::IngredientNutrition;
+use axum::extract::State;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// The nutrition known for an ingredient.
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct KnownNutrition {
+    pub ingredient_type: String,
+    #[serde(flatten)]
+    pub nutrition: Nutrition,
+    /// When the nutrition was imported, or nothing when it comes from the facts.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+pub(crate) async fn list_nutrition(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+) -> Result<Json<Vec<KnownNutrition>>> {
+    let mut known = facts
+        .nutrition
+        .iter()
+        .map(|(name, nutrition)| {
+            let item = KnownNutrition {
+                ingredient_type: name.clone(),
+                nutrition: *nutrition,
+                updated_at: None,
+            };
+            (name.clone(), item)
+        })
+        .collect::<BTreeMap<_, _>>();
+    for item in IngredientNutrition::all(&pg_pool).await? {
+        let item = KnownNutrition {
+            ingredient_type: item.ingredient_type,
+            nutrition: item.nutrition,
+            updated_at: Some(item.updated_at),
+        };
+        known.insert(item.ingredient_type.clone(), item);
+    }
+
+    Ok(Json(known.into_values().collect()))
+}
+
+/// The nutrition of every ingredient, from the facts and what has been imported.
+/// Imported nutrition takes the place of the facts for the same ingredient.
+pub(crate) async fn nutrition_table(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    facts: &Facts,
+) -> Result<HashMap<String, Nutrition>> {
+    let mut table = facts.nutrition.clone();
+    for item in IngredientNutrition::all(pg_pool).await? {
+        table.insert(item.ingredient_type, item.nutrition);
+    }
+
+    Ok(table)
+}
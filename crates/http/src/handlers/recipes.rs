@@ -0,0 +1,635 @@
+use crate::{
+    auth::Access,
+    error::{Error, Result},
+    extract::{Json, OptionalJson, Path, Query},
+    handlers::{
+        dietary::dietary_profile,
+        inventory::{as_ingredient, available_ingredients},
+        nutrition::nutrition_table,
+        servings,
+    },
+    pagination::{Page, PageParams},
+    validation::{Valid, Validate, Validator},
+    ServerState,
+};
+use annapurna_data::{
+    types::{self, Ingredient, Quantity},
+    Facts,
+};
+use annapurna_logic::{
+    consumption::{cook, shortfalls},
+    expiration::{use_it_up, Suggestion},
+    nutrition::{recipe_nutrition as nutrition_per_serving, RecipeNutrition},
+    program::AscentProgram,
+    ranking::{rank, shopping_suggestions, RankedRecipe, ShoppingStep},
+    recipe::{ForbiddenIngredient, RecipeManager},
+    scheduling::schedule,
+};
+use annapurna_models::{
+    cooking_log::CookingLog,
+    entity::Builder,
+    household::Role,
+    inventory::{Filter, Inventory, Sort},
+    recipe::{Recipe, RecipeIngredient, RecipeStep},
+    task::Task,
+};
+use axum::{extract::State, http::StatusCode};
+use chrono::{DateTime, Utc};
+use sqlx_ulid::Ulid;
+use std::{
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU32,
+};
+
+/// Lists a page of the shared recipes along with those of the household, in the order they
+/// were added.
+pub(crate) async fn list_recipes(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Query(params): Query<PageParams>,
+) -> Result<Json<Page<Recipe>>> {
+    let page = Recipe::query(&pg_pool, Some(&access.household_id), params.into()).await?;
+
+    Ok(Json(page.into()))
+}
+
+pub(crate) async fn get_recipe(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(name): Path<String>,
+) -> Result<Json<Recipe>> {
+    let recipe = visible_recipe(&pg_pool, &access, &name).await?;
+
+    Ok(Json(recipe))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct CreateRecipe {
+    pub recipe_id: Option<Ulid>,
+    pub name: String,
+    /// The number of servings the recipe makes, which defaults to one.
+    pub servings: Option<NonZeroU32>,
+    pub ingredients: Vec<RecipeIngredient>,
+    #[serde(default)]
+    pub steps: Vec<RecipeStep>,
+}
+
+/// The names of the routes under `/recipes` that would hide a recipe with the same name.
+pub(crate) const RESERVED_NAMES: &[&str] = &["dietary", "ranking", "schedule", "suggestions"];
+
+impl Validate for CreateRecipe {
+    const CHECKS_INGREDIENTS: bool = true;
+
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .not_blank("name", &self.name)
+            .nested("ingredients", &self.ingredients);
+        validate_name(validator, &self.name);
+        validate_steps(validator, &self.steps);
+    }
+}
+
+impl Validate for RecipeIngredient {
+    const CHECKS_INGREDIENTS: bool = true;
+
+    fn validate(&self, validator: &mut Validator) {
+        validator.ingredient("ingredient_type", &self.ingredient_type);
+        if let Some(quantity) = &self.quantity {
+            validator.quantity("quantity", quantity);
+        }
+    }
+}
+
+fn validate_name(validator: &mut Validator, name: &str) {
+    validator.check(
+        "name",
+        !RESERVED_NAMES.contains(&name.trim()),
+        "is reserved for another route",
+    );
+}
+
+/// Checks each step has a description and only waits on the steps before it,
+/// so that the steps can always be scheduled.
+fn validate_steps(validator: &mut Validator, steps: &[RecipeStep]) {
+    for (position, step) in steps.iter().enumerate() {
+        validator.not_blank(&format!("steps[{position}].description"), &step.description);
+        validator.duration(&format!("steps[{position}].duration"), Some(step.duration));
+        let earlier = step
+            .after
+            .iter()
+            .flatten()
+            .all(|&dependency| dependency < position);
+        validator.check(
+            &format!("steps[{position}].after"),
+            earlier,
+            "must only refer to earlier steps",
+        );
+    }
+}
+
+pub(crate) async fn create_recipe(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Valid(payload): Valid<CreateRecipe>,
+) -> Result<Json<Recipe>> {
+    access.require(Role::Editor)?;
+
+    let recipe = payload.ingredients.into_iter().try_fold(
+        Recipe::builder()
+            .recipe_id(payload.recipe_id.unwrap_or_else(Ulid::generate))
+            .household_id(Some(access.household_id))
+            .name(payload.name)
+            .servings(servings(payload.servings))
+            .created_at(chrono::Utc::now()),
+        |builder, ingredient| {
+            Ok::<_, Error>(
+                builder.ingredient(ingredient.ingredient_type, quantity(ingredient.quantity)?),
+            )
+        },
+    )?;
+    let recipe = payload
+        .steps
+        .into_iter()
+        .fold(recipe, |builder, step| builder.step(step))
+        .build()?;
+
+    recipe.create(&pg_pool).await?;
+
+    Ok(Json(recipe))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct UpdateRecipe {
+    pub name: String,
+    pub servings: Option<NonZeroU32>,
+    pub ingredients: Vec<RecipeIngredient>,
+    #[serde(default)]
+    pub steps: Vec<RecipeStep>,
+}
+
+impl Validate for UpdateRecipe {
+    const CHECKS_INGREDIENTS: bool = true;
+
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .not_blank("name", &self.name)
+            .nested("ingredients", &self.ingredients);
+        validate_name(validator, &self.name);
+        validate_steps(validator, &self.steps);
+    }
+}
+
+pub(crate) async fn update_recipe(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(name): Path<String>,
+    Valid(payload): Valid<UpdateRecipe>,
+) -> Result<Json<Recipe>> {
+    let mut recipe = editable_recipe(&pg_pool, &access, &name).await?;
+    recipe.name = payload.name;
+    recipe.servings = servings(payload.servings);
+    recipe.ingredients = payload
+        .ingredients
+        .into_iter()
+        .map(|ingredient| {
+            Ok(RecipeIngredient::new(
+                ingredient.ingredient_type,
+                quantity(ingredient.quantity)?,
+            ))
+        })
+        .collect::<Result<_>>()?;
+    recipe.steps = payload.steps;
+
+    match recipe.update(&pg_pool).await? {
+        true => Ok(Json(recipe)),
+        false => Err(Error::NotFound),
+    }
+}
+
+pub(crate) async fn delete_recipe(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(name): Path<String>,
+) -> Result<StatusCode> {
+    let recipe = editable_recipe(&pg_pool, &access, &name).await?;
+
+    match Recipe::delete(&pg_pool, &recipe.recipe_id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(Error::NotFound),
+    }
+}
+
+/// Fetches a recipe from the household or the shared catalogue, treating the recipes
+/// of other households as missing.
+async fn visible_recipe(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    access: &Access,
+    name: &str,
+) -> Result<Recipe> {
+    Recipe::by_name(pg_pool, &access.household_id, name)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+/// Fetches a recipe the caller may change: one belonging to the household, as an editor.
+/// The shared catalogue cannot be changed.
+async fn editable_recipe(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    access: &Access,
+    name: &str,
+) -> Result<Recipe> {
+    access.require(Role::Editor)?;
+
+    let recipe = visible_recipe(pg_pool, access, name).await?;
+    match recipe.household_id == Some(access.household_id) {
+        true => Ok(recipe),
+        false => Err(Error::Forbidden),
+    }
+}
+
+/// Stores quantities in a consistent format so they can be compared later.
+fn quantity(quantity: Option<String>) -> Result<Option<String>> {
+    match quantity {
+        Some(quantity) => Ok(Some(quantity.parse::<Quantity>()?.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// The stored recipe in the form used to check what can be made.
+/// Quantities that cannot be parsed are treated as unknown amounts.
+pub(crate) fn as_recipe(recipe: &Recipe) -> types::Recipe {
+    let ingredients = recipe
+        .ingredients
+        .iter()
+        .map(|ingredient| {
+            match ingredient
+                .quantity
+                .as_ref()
+                .and_then(|quantity| quantity.parse::<Quantity>().ok())
+            {
+                Some(amount) => Ingredient::with_amount(ingredient.ingredient_type.clone(), amount),
+                None => Ingredient::new(ingredient.ingredient_type.clone()),
+            }
+        })
+        .collect();
+    let steps = recipe
+        .steps
+        .iter()
+        .map(|step| types::Step {
+            description: step.description.clone(),
+            duration: step.duration,
+            after: step.after.clone(),
+        })
+        .collect();
+
+    types::Recipe::new(recipe.name.clone(), ingredients).with_steps(steps)
+}
+
+/// Stores the recipes from the facts when there are no stored recipes yet,
+/// so that a new database starts out with the same recipes as the CLI.
+pub(crate) async fn seed_recipes(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    facts: &Facts,
+) -> Result<()> {
+    let pagination = annapurna_models::Pagination::all();
+    let (recipes, _pagination) = Recipe::query(pg_pool, None, pagination).await?;
+    if !recipes.is_empty() {
+        return Ok(());
+    }
+
+    let created_at = chrono::Utc::now();
+    for recipe in &facts.recipes {
+        let servings = facts.servings.get(&recipe.name).copied().unwrap_or(1);
+        let stored = recipe.ingredients.iter().fold(
+            Recipe::builder()
+                .name(recipe.name.clone())
+                .servings(servings.min(i32::MAX as u32) as i32)
+                .created_at(created_at),
+            |builder, ingredient| {
+                builder.ingredient(
+                    ingredient.name.clone(),
+                    ingredient.amount.as_ref().map(Quantity::to_string),
+                )
+            },
+        );
+        let stored = recipe
+            .steps
+            .iter()
+            .fold(stored, |builder, step| {
+                builder.step(RecipeStep::new(
+                    step.description.clone(),
+                    step.duration,
+                    step.after.clone(),
+                ))
+            })
+            .build()?;
+        stored.create(pg_pool).await?;
+    }
+
+    Ok(())
+}
+
+/// Creates a manager for the recipes the household can see, using the facts for everything else
+/// that is known about ingredients. Recipes the caller's dietary profile rules out are not suggested.
+pub(crate) async fn recipe_manager(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    facts: &Facts,
+    access: &Access,
+    available_ingredients: Vec<Ingredient>,
+) -> Result<RecipeManager> {
+    let pagination = annapurna_models::Pagination::all();
+    let (recipes, _pagination) =
+        Recipe::query(pg_pool, Some(&access.household_id), pagination).await?;
+    let profile = dietary_profile(pg_pool, &access.user.user_id).await?;
+
+    let servings = recipes
+        .iter()
+        .map(|recipe| (recipe.name.clone(), recipe.servings.max(1) as u32))
+        .collect();
+    let recipes = recipes.iter().map(as_recipe).collect();
+
+    Ok(RecipeManager::from_facts(facts, available_ingredients)
+        .recipes(recipes)
+        .servings(servings)
+        .profile(profile))
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RankBy {
+    #[default]
+    Count,
+    Cost,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct RankingParams {
+    #[serde(default)]
+    pub by: RankBy,
+    pub max_items: Option<usize>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct Ranking {
+    pub ranked: Vec<RankedRecipe>,
+    pub shopping: Vec<ShoppingStep>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct SuggestionParams {
+    /// How many days ahead to look for expiring inventory.
+    #[serde(default = "default_days")]
+    pub days: i64,
+    /// The most ingredients a suggested recipe may be missing.
+    #[serde(default = "default_max_missing")]
+    pub max_missing: usize,
+}
+
+fn default_days() -> i64 {
+    3
+}
+
+fn default_max_missing() -> usize {
+    1
+}
+
+/// Suggests recipes that use up the inventory expiring within the next few days.
+pub(crate) async fn suggest_recipes(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+    access: Access,
+    Query(params): Query<SuggestionParams>,
+) -> Result<Json<Vec<Suggestion>>> {
+    let pagination = annapurna_models::Pagination::all();
+    let (items, _pagination) = Inventory::query(
+        &pg_pool,
+        &access.household_id,
+        &Filter::default(),
+        Sort::default(),
+        pagination,
+    )
+    .await?;
+
+    let cutoff = chrono::Utc::now() + chrono::Duration::days(params.days);
+    let expiring = items
+        .iter()
+        .filter(|item| {
+            item.expiration_date
+                .is_some_and(|expiration| expiration <= cutoff)
+        })
+        .map(as_ingredient)
+        .collect::<Vec<_>>();
+
+    let inventory = items.iter().map(as_ingredient).collect();
+    let manager = recipe_manager(&pg_pool, &facts, &access, inventory).await?;
+
+    Ok(Json(use_it_up(&manager, &expiring, params.max_missing)))
+}
+
+/// Ranks the recipes that cannot be made from the inventory, and suggests what to buy.
+pub(crate) async fn rank_recipes(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+    access: Access,
+    Query(params): Query<RankingParams>,
+) -> Result<Json<Ranking>> {
+    let inventory = available_ingredients(&pg_pool, &access.household_id).await?;
+    let result = recipe_manager(&pg_pool, &facts, &access, inventory)
+        .await?
+        .process();
+
+    let prices = match params.by {
+        RankBy::Count => HashMap::new(),
+        RankBy::Cost => facts.prices,
+    };
+
+    Ok(Json(Ranking {
+        ranked: rank(&result, &prices),
+        shopping: shopping_suggestions(&result, &prices, params.max_items),
+    }))
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct DietCheck {
+    pub allowed: Vec<String>,
+    pub forbidden: BTreeMap<String, Vec<ForbiddenIngredient>>,
+}
+
+/// Splits the recipes the household can see into those that suit the caller's dietary profile
+/// and those it rules out, naming the ingredients that rule them out.
+pub(crate) async fn check_diet(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+    access: Access,
+) -> Result<Json<DietCheck>> {
+    let result = recipe_manager(&pg_pool, &facts, &access, Vec::new())
+        .await?
+        .process();
+
+    Ok(Json(DietCheck {
+        allowed: result.allowed,
+        forbidden: result.forbidden.into_iter().collect(),
+    }))
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct CookRecipe {
+    /// The number of servings cooked, which defaults to everything the recipe makes.
+    pub servings: Option<NonZeroU32>,
+    /// Cook the recipe even though the inventory is short of some of its ingredients.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct Cooked {
+    pub log: CookingLog,
+    /// The inventory items that were partially used.
+    pub updated: Vec<Inventory>,
+    /// The inventory items that were used up.
+    pub deleted: Vec<Ulid>,
+}
+
+/// Uses up the inventory needed to cook a recipe, starting with the items that expire soonest.
+/// The inventory and the cooking log are updated in a single transaction.
+/// Unless it is forced, cooking is refused when the inventory is short of any ingredient.
+pub(crate) async fn cook_recipe(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+    access: Access,
+    Path(name): Path<String>,
+    OptionalJson(payload): OptionalJson<CookRecipe>,
+) -> Result<Json<Cooked>> {
+    access.require(Role::Editor)?;
+
+    let manager = recipe_manager(&pg_pool, &facts, &access, Vec::new()).await?;
+    let servings = payload
+        .servings
+        .map_or_else(|| manager.servings_of(&name), NonZeroU32::get);
+
+    let mut transaction = pg_pool.begin().await?;
+
+    let items = Inventory::by_expiration_for_update(&mut transaction, &access.household_id).await?;
+    let stock = items.iter().map(as_ingredient).collect::<Vec<_>>();
+    if !payload.force {
+        let shortfalls = shortfalls(&manager, &name, servings, &stock)?;
+        if !shortfalls.is_empty() {
+            return Err(Error::Shortfalls(shortfalls));
+        }
+    }
+    let remaining = cook(&manager, &name, servings, &stock)?;
+
+    let mut updated = Vec::new();
+    let mut deleted = Vec::new();
+    for ((mut item, before), after) in items.into_iter().zip(stock).zip(remaining) {
+        match after {
+            None => {
+                Inventory::delete(&mut *transaction, &item.inventory_id).await?;
+                deleted.push(item.inventory_id);
+            }
+            Some(after) if after != before => {
+                if let Some(amount) = after.amount {
+                    item.quantity = amount.to_string();
+                    Inventory::update_quantity(
+                        &mut *transaction,
+                        &item.inventory_id,
+                        &item.quantity,
+                    )
+                    .await?;
+                    updated.push(item);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    let log = CookingLog::builder()
+        .owner_id(access.user.user_id)
+        .household_id(access.household_id)
+        .recipe(name)
+        .servings(servings.min(i32::MAX as u32) as i32)
+        .cooked_at(chrono::Utc::now())
+        .build()?;
+    log.create(&mut *transaction).await?;
+
+    transaction.commit().await?;
+
+    Ok(Json(Cooked {
+        log,
+        updated,
+        deleted,
+    }))
+}
+
+/// Works out the nutrition of a serving of the recipe, listing the ingredients it could not
+/// account for.
+pub(crate) async fn recipe_nutrition(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+    access: Access,
+    Path(name): Path<String>,
+) -> Result<Json<RecipeNutrition>> {
+    let recipe = visible_recipe(&pg_pool, &access, &name).await?;
+    let nutrition = nutrition_table(&pg_pool, &facts).await?;
+
+    Ok(Json(nutrition_per_serving(
+        &as_recipe(&recipe),
+        recipe.servings.max(1) as u32,
+        &nutrition,
+        &facts.densities,
+    )))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ScheduleMeal {
+    /// The recipes to have ready at the same time.
+    pub recipes: Vec<String>,
+    pub serve_at: DateTime<Utc>,
+}
+
+impl Validate for ScheduleMeal {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .check("recipes", !self.recipes.is_empty(), "must not be empty")
+            .not_in_past("serve_at", Some(self.serve_at));
+        for (index, recipe) in self.recipes.iter().enumerate() {
+            validator.not_blank(&format!("recipes[{index}]"), recipe);
+        }
+    }
+}
+
+/// Plans the steps of the recipes backwards from when the meal is served, adding a task for each
+/// step to the household's todo list. Steps that do not wait on each other overlap, across
+/// recipes as well. Returns the tasks in the order they start.
+pub(crate) async fn schedule_meal(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Valid(payload): Valid<ScheduleMeal>,
+) -> Result<Json<Vec<Task>>> {
+    access.require(Role::Editor)?;
+
+    let mut recipes = Vec::with_capacity(payload.recipes.len());
+    for name in &payload.recipes {
+        recipes.push(as_recipe(&visible_recipe(&pg_pool, &access, name).await?));
+    }
+    let steps = schedule(&recipes, payload.serve_at)?;
+
+    // ids are sorted so the tasks are listed in the order they start
+    let mut ids = steps.iter().map(|_| Ulid::generate()).collect::<Vec<_>>();
+    ids.sort();
+
+    let created_at = chrono::Utc::now();
+    let mut transaction = pg_pool.begin().await?;
+    let mut tasks = Vec::with_capacity(steps.len());
+    for (task_id, step) in ids.into_iter().zip(steps) {
+        let task = Task::builder()
+            .task_id(task_id)
+            .owner_id(access.user.user_id)
+            .household_id(access.household_id)
+            .description(step.task().description)
+            .duration(Some(step.duration))
+            .start_time(Some(step.start_time))
+            .created_at(created_at)
+            .build()?;
+        task.create(&mut *transaction).await?;
+        tasks.push(task);
+    }
+    transaction.commit().await?;
+
+    Ok(Json(tasks))
+}
@@ -0,0 +1,143 @@
+use crate::{
+    auth::Access,
+    error::{Error, Result},
+    extract::{Json, Path, Query},
+    handlers::{inventory::available_ingredients, recipes::recipe_manager},
+    pagination::{Page, PageParams},
+    validation::{Valid, Validate, Validator},
+    ServerState,
+};
+use annapurna_logic::shopping::shopping_list;
+use annapurna_models::{
+    entity::Builder,
+    household::Role,
+    shopping_list::{ShoppingList, ShoppingListItem},
+};
+use axum::{extract::State, http::StatusCode};
+use sqlx::types::chrono;
+use sqlx_ulid::Ulid;
+
+/// Lists a page of the household's shopping lists, in the order they were made.
+pub(crate) async fn list_shopping_lists(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Query(params): Query<PageParams>,
+) -> Result<Json<Page<ShoppingList>>> {
+    let page = ShoppingList::query(&pg_pool, &access.household_id, params.into()).await?;
+
+    Ok(Json(page.into()))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct CreateShoppingList {
+    pub shopping_list_id: Option<Ulid>,
+    pub name: String,
+    /// The recipes to shop for.
+    pub recipes: Vec<String>,
+}
+
+impl Validate for CreateShoppingList {
+    fn validate(&self, validator: &mut Validator) {
+        validator.not_blank("name", &self.name).check(
+            "recipes",
+            !self.recipes.is_empty(),
+            "must not be empty",
+        );
+        for (index, recipe) in self.recipes.iter().enumerate() {
+            validator.not_blank(&format!("recipes[{index}]"), recipe);
+        }
+    }
+}
+
+/// Creates a shopping list containing everything missing from the inventory
+/// that is needed to make the selected recipes.
+pub(crate) async fn create_shopping_list(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+    access: Access,
+    Valid(payload): Valid<CreateShoppingList>,
+) -> Result<Json<ShoppingList>> {
+    access.require(Role::Editor)?;
+
+    let inventory = available_ingredients(&pg_pool, &access.household_id).await?;
+    let manager = recipe_manager(&pg_pool, &facts, &access, inventory).await?;
+    let items = shopping_list(&manager, &payload.recipes)?;
+
+    let list = items.into_iter().fold(
+        ShoppingList::builder()
+            .shopping_list_id(payload.shopping_list_id.unwrap_or_else(Ulid::generate))
+            .owner_id(access.user.user_id)
+            .household_id(access.household_id)
+            .name(payload.name)
+            .recipes(payload.recipes)
+            .created_at(chrono::Utc::now()),
+        |builder, item| {
+            builder.item(
+                item.ingredient,
+                item.amount.map(|amount| amount.to_string()),
+            )
+        },
+    );
+    let list = list.build()?;
+
+    list.create(&pg_pool).await?;
+
+    Ok(Json(list))
+}
+
+pub(crate) async fn get_shopping_list(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<Json<ShoppingList>> {
+    let list = household_shopping_list(&pg_pool, &access, &id).await?;
+
+    Ok(Json(list))
+}
+
+pub(crate) async fn delete_shopping_list(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<StatusCode> {
+    access.require(Role::Editor)?;
+    household_shopping_list(&pg_pool, &access, &id).await?;
+
+    match ShoppingList::delete(&pg_pool, &id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(Error::NotFound),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct CheckShoppingListItem {
+    pub checked: bool,
+}
+
+/// Checks an item off of a shopping list once it has been bought.
+pub(crate) async fn check_shopping_list_item(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path((id, item_id)): Path<(Ulid, Ulid)>,
+    Json(payload): Json<CheckShoppingListItem>,
+) -> Result<Json<ShoppingListItem>> {
+    access.require(Role::Editor)?;
+    household_shopping_list(&pg_pool, &access, &id).await?;
+
+    let item = ShoppingListItem::set_checked(&pg_pool, &id, &item_id, payload.checked)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(item))
+}
+
+/// Fetches a shopping list, treating lists that belong to other households as missing.
+async fn household_shopping_list(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    access: &Access,
+    id: &Ulid,
+) -> Result<ShoppingList> {
+    ShoppingList::by_id(pg_pool, id)
+        .await?
+        .filter(|list| list.household_id == access.household_id)
+        .ok_or(Error::NotFound)
+}
@@ -0,0 +1,427 @@
+use crate::{
+    auth::Access,
+    error::{Error, Result},
+    extract::{Json, Path, Query},
+    handlers::{inventory::present, meal_plans::DateRange},
+    pagination::{Page, PageParams},
+    validation::{Valid, Validate, Validator},
+    ServerState,
+};
+use annapurna_data::types::{Recurrence, Repeat};
+use annapurna_logic::routine::{occurrences, streak, Occurrence, Streak};
+use annapurna_models::{
+    entity::Builder,
+    household::Role,
+    task::{Task, TaskOccurrence},
+    Pagination,
+};
+use axum::{extract::State, http::StatusCode};
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
+use sqlx_ulid::Ulid;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+/// The most days of occurrences listed at once.
+const MAX_OCCURRENCE_DAYS: i64 = 366;
+
+/// Lists a page of the household's tasks, in the order they were added.
+pub(crate) async fn list_tasks(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Query(params): Query<PageParams>,
+) -> Result<Json<Page<Task>>> {
+    let page = Task::query(&pg_pool, &access.household_id, params.into()).await?;
+
+    Ok(Json(page.into()))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct CreateTask {
+    pub task_id: Option<Ulid>,
+    pub description: String,
+    pub duration: Option<Duration>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub recurrence: Option<Recurrence>,
+}
+
+impl Validate for CreateTask {
+    fn validate(&self, validator: &mut Validator) {
+        validator
+            .not_blank("description", &self.description)
+            .duration("duration", self.duration);
+        if let Some(recurrence) = &self.recurrence {
+            validator.nested("recurrence.", recurrence);
+        }
+    }
+}
+
+impl Validate for Recurrence {
+    fn validate(&self, validator: &mut Validator) {
+        if let Repeat::Every { days, .. } = self.repeat {
+            validator.check("repeat.every.days", days > 0, "must be at least 1");
+        }
+    }
+}
+
+pub(crate) async fn create_task(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Valid(payload): Valid<CreateTask>,
+) -> Result<Json<Task>> {
+    access.require(Role::Editor)?;
+
+    let task = Task::builder()
+        .task_id(payload.task_id.unwrap_or_else(Ulid::generate))
+        .owner_id(access.user.user_id)
+        .household_id(access.household_id)
+        .description(payload.description)
+        .duration(payload.duration)
+        .start_time(payload.start_time)
+        .recurrence(payload.recurrence)
+        .created_at(Utc::now())
+        .build()?;
+
+    task.create(&pg_pool).await?;
+
+    Ok(Json(task))
+}
+
+/// Adds the tasks from the facts that the household does not have yet, returning the new ones.
+pub(crate) async fn import_tasks(
+    State(ServerState { pg_pool, facts, .. }): State<ServerState>,
+    access: Access,
+) -> Result<Json<Vec<Task>>> {
+    access.require(Role::Editor)?;
+
+    let (existing, _pagination) =
+        Task::query(&pg_pool, &access.household_id, Pagination::all()).await?;
+    let existing = existing
+        .iter()
+        .map(|task| task.description.as_str())
+        .collect::<HashSet<_>>();
+    let missing = facts
+        .tasks
+        .iter()
+        .filter(|task| !existing.contains(task.description.as_str()))
+        .collect::<Vec<_>>();
+
+    // tasks are listed by their ids, which are only ordered across milliseconds,
+    // so they are sorted to keep the tasks in the order the facts give them
+    let mut ids = missing.iter().map(|_| Ulid::generate()).collect::<Vec<_>>();
+    ids.sort();
+
+    let created_at = Utc::now();
+    let today = created_at.date_naive();
+    let mut transaction = pg_pool.begin().await?;
+    let mut tasks = Vec::with_capacity(missing.len());
+    for (task_id, task) in ids.into_iter().zip(missing) {
+        let task = Task::builder()
+            .task_id(task_id)
+            .owner_id(access.user.user_id)
+            .household_id(access.household_id)
+            .description(task.description.clone())
+            .duration(task.duration)
+            .start_time(task.start_time.map(|time| today.and_time(time).and_utc()))
+            .recurrence(task.recurrence.clone())
+            .created_at(created_at)
+            .build()?;
+        task.create(&mut *transaction).await?;
+        tasks.push(task);
+    }
+    transaction.commit().await?;
+
+    Ok(Json(tasks))
+}
+
+pub(crate) async fn get_task(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<Json<Task>> {
+    let task = household_task(&pg_pool, &access, &id).await?;
+
+    Ok(Json(task))
+}
+
+/// Changes to a task. Fields that are left out are kept as they are,
+/// and the duration, start time and recurrence are removed when given as `null`.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct UpdateTask {
+    pub description: Option<String>,
+    /// Completing a task records when it was completed.
+    pub completed: Option<bool>,
+    #[serde(default, deserialize_with = "present")]
+    pub duration: Option<Option<Duration>>,
+    #[serde(default, deserialize_with = "present")]
+    pub start_time: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "present")]
+    pub recurrence: Option<Option<Recurrence>>,
+}
+
+impl Validate for UpdateTask {
+    fn validate(&self, validator: &mut Validator) {
+        if let Some(description) = &self.description {
+            validator.not_blank("description", description);
+        }
+        if let Some(duration) = self.duration {
+            validator.duration("duration", duration);
+        }
+        if let Some(Some(recurrence)) = &self.recurrence {
+            validator.nested("recurrence.", recurrence);
+        }
+    }
+}
+
+impl UpdateTask {
+    fn apply(self, task: &mut Task) {
+        if let Some(description) = self.description {
+            task.description = description;
+        }
+        if let Some(duration) = self.duration {
+            task.duration = duration;
+        }
+        if let Some(start_time) = self.start_time {
+            task.start_time = start_time;
+        }
+        if let Some(recurrence) = self.recurrence {
+            task.recurrence = recurrence;
+        }
+        match self.completed {
+            Some(completed) if completed != task.completed => {
+                task.complete(completed.then(Utc::now));
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) async fn update_task(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+    Valid(payload): Valid<UpdateTask>,
+) -> Result<Json<Task>> {
+    access.require(Role::Editor)?;
+
+    let mut task = household_task(&pg_pool, &access, &id).await?;
+    payload.apply(&mut task);
+
+    match task.update(&pg_pool).await? {
+        true => Ok(Json(task)),
+        false => Err(Error::NotFound),
+    }
+}
+
+pub(crate) async fn delete_task(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<StatusCode> {
+    access.require(Role::Editor)?;
+    household_task(&pg_pool, &access, &id).await?;
+
+    match Task::delete(&pg_pool, &id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(Error::NotFound),
+    }
+}
+
+/// An occurrence of one of the household's recurring tasks.
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct ScheduledTask {
+    pub task_id: Ulid,
+    pub description: String,
+    #[serde(flatten)]
+    pub occurrence: Occurrence,
+    pub scheduled_at: DateTime<Utc>,
+    pub completed: bool,
+    pub completion_time: Option<DateTime<Utc>>,
+}
+
+/// Lists the occurrences of the household's recurring tasks over a range of days, in the order
+/// they are due. The range defaults to the week starting today.
+pub(crate) async fn list_occurrences(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Query(range): Query<DateRange>,
+) -> Result<Json<Vec<ScheduledTask>>> {
+    let from = range.from.unwrap_or_else(|| Utc::now().date_naive());
+    let until = range.until.unwrap_or(from + Days::new(6));
+    let mut validator = Validator::new(None);
+    validator.check(
+        "until",
+        (until - from).num_days() < MAX_OCCURRENCE_DAYS,
+        &format!("must be less than {MAX_OCCURRENCE_DAYS} days after from"),
+    );
+    validator.finish()?;
+
+    let (tasks, _pagination) =
+        Task::query(&pg_pool, &access.household_id, Pagination::all()).await?;
+    let completions = completions(&pg_pool, &access, from, until).await?;
+
+    let mut scheduled = tasks
+        .into_iter()
+        .filter_map(|task| {
+            let recurrence = task.recurrence.as_ref()?;
+            // tasks do not occur before they were added
+            let start = from.max(task.created_at.date_naive());
+            let found = occurrences(recurrence, start, until);
+            Some(
+                found
+                    .into_iter()
+                    .map(|occurrence| scheduled_task(&task, occurrence, &completions))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+    scheduled.sort_by_key(|scheduled| (scheduled.scheduled_at, scheduled.task_id));
+
+    Ok(Json(scheduled))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct CompleteOccurrence {
+    pub date: NaiveDate,
+    /// Required when the task is due at particular times of the day.
+    pub time: Option<NaiveTime>,
+    pub completed: bool,
+}
+
+impl Validate for CompleteOccurrence {
+    fn validate(&self, _validator: &mut Validator) {}
+}
+
+/// Marks a single occurrence of a recurring task as done, or as not done.
+pub(crate) async fn complete_occurrence(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+    Valid(payload): Valid<CompleteOccurrence>,
+) -> Result<Json<ScheduledTask>> {
+    access.require(Role::Editor)?;
+
+    let task = household_task(&pg_pool, &access, &id).await?;
+    let occurrence = Occurrence {
+        date: payload.date,
+        time: payload.time,
+    };
+    let mut validator = Validator::new(None);
+    match &task.recurrence {
+        Some(recurrence) => {
+            let due = occurrences(recurrence, payload.date, payload.date);
+            validator.check(
+                "date",
+                !due.is_empty(),
+                "the task does not occur on this day",
+            );
+            validator.check(
+                "time",
+                due.is_empty() || due.contains(&occurrence),
+                "the task is not due at this time",
+            );
+        }
+        None => {
+            validator.check("date", false, "the task does not recur");
+        }
+    }
+    validator.finish()?;
+
+    let scheduled_at = occurrence.scheduled_at();
+    let mut completions = HashMap::new();
+    if payload.completed {
+        let completed = TaskOccurrence {
+            task_id: task.task_id,
+            scheduled_at,
+            completion_time: Utc::now(),
+        };
+        completed.save(&pg_pool).await?;
+        completions.insert((completed.task_id, scheduled_at), completed.completion_time);
+    } else {
+        TaskOccurrence::delete(&pg_pool, &task.task_id, scheduled_at).await?;
+    }
+
+    Ok(Json(scheduled_task(&task, occurrence, &completions)))
+}
+
+/// Tallies how many occurrences of a recurring task have been done in a row, since it was added.
+pub(crate) async fn task_streak(
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+    access: Access,
+    Path(id): Path<Ulid>,
+) -> Result<Json<Streak>> {
+    let task = household_task(&pg_pool, &access, &id).await?;
+    let Some(recurrence) = &task.recurrence else {
+        return Ok(Json(Streak::default()));
+    };
+
+    let today = Utc::now().date_naive();
+    let start = task.created_at.date_naive();
+    let completions = completions(&pg_pool, &access, start, today).await?;
+    let history = occurrences(recurrence, start, today)
+        .into_iter()
+        .map(|occurrence| {
+            let done = completions.contains_key(&(task.task_id, occurrence.scheduled_at()));
+            (occurrence, done)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(streak(&history, today)))
+}
+
+/// The completion times of the household's occurrences due within the range of days,
+/// by task and the moment they were scheduled for.
+async fn completions(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    access: &Access,
+    from: NaiveDate,
+    until: NaiveDate,
+) -> Result<HashMap<(Ulid, DateTime<Utc>), DateTime<Utc>>> {
+    let from = from.and_time(NaiveTime::MIN).and_utc();
+    let until = (until + Days::new(1)).and_time(NaiveTime::MIN).and_utc();
+    let completed = TaskOccurrence::query(pg_pool, &access.household_id, Some(from), Some(until))
+        .await?
+        .into_iter()
+        .filter(|occurrence| occurrence.scheduled_at < until)
+        .map(|occurrence| {
+            (
+                (occurrence.task_id, occurrence.scheduled_at),
+                occurrence.completion_time,
+            )
+        })
+        .collect();
+
+    Ok(completed)
+}
+
+fn scheduled_task(
+    task: &Task,
+    occurrence: Occurrence,
+    completions: &HashMap<(Ulid, DateTime<Utc>), DateTime<Utc>>,
+) -> ScheduledTask {
+    let scheduled_at = occurrence.scheduled_at();
+    let completion_time = completions.get(&(task.task_id, scheduled_at)).copied();
+
+    ScheduledTask {
+        task_id: task.task_id,
+        description: task.description.clone(),
+        occurrence,
+        scheduled_at,
+        completed: completion_time.is_some(),
+        completion_time,
+    }
+}
+
+/// Fetches a task, treating tasks that belong to other households as missing.
+async fn household_task(
+    pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+    access: &Access,
+    id: &Ulid,
+) -> Result<Task> {
+    Task::by_id(pg_pool, id)
+        .await?
+        .filter(|task| task.household_id == access.household_id)
+        .ok_or(Error::NotFound)
+}
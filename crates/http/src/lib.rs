@@ -1,12 +1,9 @@
-use annapurna_data::{
-    types::{Ingredient, Recipe},
-    Facts,
-};
+use annapurna_data::{types::Ingredient, Facts};
 use axum::{
     extract::{FromRef, State},
     middleware,
     response::{Html, IntoResponse, Redirect},
-    routing::{get, get_service, post},
+    routing::{delete, get, get_service, patch, post, put, MethodRouter},
     Form, Router,
 };
 use axum_extra::TypedHeader;
@@ -18,12 +15,17 @@ use std::{
 };
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
-use tower_http::services::ServeDir;
+use tower_http::services::{ServeDir, ServeFile};
 use tracing::info;
 
+mod auth;
 pub mod error;
+mod extract;
 mod handlers;
+mod openapi;
+mod pagination;
 mod serve;
+mod validation;
 
 use error::Result;
 use serve::{inject_variables_into_html, InjectorState};
@@ -40,6 +42,7 @@ pub struct Server {
     static_path: PathBuf,
 
     facts: Facts,
+    require_known_ingredients: bool,
 }
 
 #[derive(Clone)]
@@ -51,6 +54,8 @@ pub struct ServerState {
     pub auth_app_id: String,
 
     pub facts: Facts,
+    /// Whether requests may only refer to ingredients from the facts or the recipes.
+    pub require_known_ingredients: bool,
 }
 
 impl FromRef<ServerState> for PublicKey {
@@ -78,17 +83,143 @@ where
     ServerState: axum::extract::FromRef<S>,
     PublicKey: axum::extract::FromRef<S>,
 {
-    Router::new()
-        .route("/", get(root))
-        .route(
+    routes()
+        .into_iter()
+        .fold(Router::new(), |router, (path, route)| {
+            router.route(path, route)
+        })
+        .fallback(extract::not_found)
+        .layer(middleware::map_response(extract::method_not_allowed))
+}
+
+/// Every API route along with the path it is served at.
+/// [`openapi::OPERATIONS`] documents each of them.
+fn routes<S: std::clone::Clone + Send + Sync + 'static>() -> Vec<(&'static str, MethodRouter<S>)>
+where
+    ServerState: axum::extract::FromRef<S>,
+    PublicKey: axum::extract::FromRef<S>,
+{
+    vec![
+        ("/", get(root)),
+        (
             "/inventory",
             get(handlers::inventory::list_inventory).post(handlers::inventory::create_inventory),
-        )
-        .route("/login", get(login_redirect))
-        .route("/submit", post(dummy_form))
-        .route("/recipes", get(get_recipes))
-        .route("/ingredients", get(get_ingredients))
-        .route("/health", get(health))
+        ),
+        ("/inventory/bulk", post(handlers::inventory::bulk_inventory)),
+        (
+            "/inventory/:id",
+            get(handlers::inventory::get_inventory)
+                .patch(handlers::inventory::update_inventory)
+                .delete(handlers::inventory::delete_inventory),
+        ),
+        ("/login", get(login_redirect)),
+        ("/submit", post(dummy_form)),
+        (
+            "/recipes",
+            get(handlers::recipes::list_recipes).post(handlers::recipes::create_recipe),
+        ),
+        ("/recipes/dietary", get(handlers::recipes::check_diet)),
+        ("/recipes/ranking", get(handlers::recipes::rank_recipes)),
+        ("/recipes/schedule", post(handlers::recipes::schedule_meal)),
+        (
+            "/recipes/suggestions",
+            get(handlers::recipes::suggest_recipes),
+        ),
+        (
+            "/recipes/:name",
+            get(handlers::recipes::get_recipe)
+                .put(handlers::recipes::update_recipe)
+                .delete(handlers::recipes::delete_recipe),
+        ),
+        ("/recipes/:name/cook", post(handlers::recipes::cook_recipe)),
+        (
+            "/recipes/:name/nutrition",
+            get(handlers::recipes::recipe_nutrition),
+        ),
+        (
+            "/meal-plans",
+            get(handlers::meal_plans::list_meal_plans).post(handlers::meal_plans::create_meal_plan),
+        ),
+        (
+            "/meal-plans/check",
+            get(handlers::meal_plans::check_meal_plans),
+        ),
+        (
+            "/meal-plans/:id",
+            get(handlers::meal_plans::get_meal_plan)
+                .put(handlers::meal_plans::update_meal_plan)
+                .delete(handlers::meal_plans::delete_meal_plan),
+        ),
+        (
+            "/shopping-lists",
+            get(handlers::shopping_lists::list_shopping_lists)
+                .post(handlers::shopping_lists::create_shopping_list),
+        ),
+        (
+            "/shopping-lists/:id",
+            get(handlers::shopping_lists::get_shopping_list)
+                .delete(handlers::shopping_lists::delete_shopping_list),
+        ),
+        (
+            "/shopping-lists/:id/items/:item_id",
+            patch(handlers::shopping_lists::check_shopping_list_item),
+        ),
+        (
+            "/households",
+            get(handlers::households::list_households).post(handlers::households::create_household),
+        ),
+        (
+            "/households/:id",
+            delete(handlers::households::delete_household),
+        ),
+        (
+            "/households/:id/members",
+            get(handlers::households::list_members),
+        ),
+        (
+            "/households/:id/members/:user_id",
+            put(handlers::households::update_member).delete(handlers::households::remove_member),
+        ),
+        (
+            "/households/:id/invitations",
+            post(handlers::households::invite_member),
+        ),
+        ("/invitations", get(handlers::households::list_invitations)),
+        (
+            "/invitations/:id",
+            delete(handlers::households::decline_invitation),
+        ),
+        (
+            "/invitations/:id/accept",
+            post(handlers::households::accept_invitation),
+        ),
+        (
+            "/tasks",
+            get(handlers::tasks::list_tasks).post(handlers::tasks::create_task),
+        ),
+        ("/tasks/import", post(handlers::tasks::import_tasks)),
+        ("/tasks/occurrences", get(handlers::tasks::list_occurrences)),
+        (
+            "/tasks/:id",
+            get(handlers::tasks::get_task)
+                .patch(handlers::tasks::update_task)
+                .delete(handlers::tasks::delete_task),
+        ),
+        (
+            "/tasks/:id/occurrences",
+            put(handlers::tasks::complete_occurrence),
+        ),
+        ("/tasks/:id/streak", get(handlers::tasks::task_streak)),
+        (
+            "/dietary-profile",
+            get(handlers::dietary::get_dietary_profile)
+                .put(handlers::dietary::update_dietary_profile),
+        ),
+        ("/ingredients", get(get_ingredients)),
+        ("/nutrition", get(handlers::nutrition::list_nutrition)),
+        ("/health", get(health)),
+        ("/openapi.json", get(openapi::openapi)),
+    ]
 }
 
 impl Server {
@@ -108,12 +239,14 @@ impl Server {
         let cors = tower_http::cors::CorsLayer::permissive();
 
         let public_key = self.public_keys[0].clone();
+        handlers::recipes::seed_recipes(&self.pg_pool, &self.facts).await?;
         let state = ServerState {
             pg_pool: self.pg_pool,
             public_key,
             auth_url: self.auth_url.clone(),
             auth_app_id: self.auth_app_id.clone(),
             facts: self.facts.clone(),
+            require_known_ingredients: self.require_known_ingredients,
         };
 
         // TODO: populate frontend_state with necessary variables
@@ -127,7 +260,9 @@ impl Server {
         // serve all files from the static directory
         // if the file contains html, it will be edited to include runtime environment variables
         // these may be loaded by the client-side javascript
-        let serve_dir = ServeDir::new(self.static_path);
+        // paths that are not files, such as the login callback, are the app's own routes
+        let index = ServeFile::new(self.static_path.join("index.html"));
+        let serve_dir = ServeDir::new(self.static_path).fallback(index);
         let serve_service = get_service(serve_dir).layer(ServiceBuilder::new().layer(
             middleware::from_fn_with_state(frontend_state, inject_variables_into_html),
         ));
@@ -155,12 +290,14 @@ impl Server {
         let auth_app_id = self.auth_app_id.clone();
         let public_key = self.public_keys[0].clone();
         let facts = self.facts.clone();
+        handlers::recipes::seed_recipes(&self.pg_pool, &facts).await?;
         let server_state = ServerState {
             pg_pool: self.pg_pool,
             public_key,
             auth_url,
             auth_app_id,
             facts,
+            require_known_ingredients: self.require_known_ingredients,
         };
 
         let app = Router::new()
@@ -185,6 +322,7 @@ pub struct Builder {
     public_keys: Option<Vec<PublicKey>>,
     static_path: Option<PathBuf>,
     facts: Option<Facts>,
+    require_known_ingredients: bool,
 }
 
 impl Builder {
@@ -197,6 +335,7 @@ impl Builder {
             public_keys: None,
             static_path: None,
             facts: None,
+            require_known_ingredients: false,
         }
     }
 
@@ -235,6 +374,12 @@ impl Builder {
         self
     }
 
+    /// Rejects requests that refer to ingredients missing from the facts and recipes.
+    pub fn require_known_ingredients(mut self, require_known_ingredients: bool) -> Self {
+        self.require_known_ingredients = require_known_ingredients;
+        self
+    }
+
     pub fn build(self) -> Result<Server> {
         let addr = self.addr.ok_or(error::Error::ServerBuilder)?;
         let auth_url = self.auth_url.ok_or(error::Error::ServerBuilder)?;
@@ -252,6 +397,7 @@ impl Builder {
             public_keys,
             static_path,
             facts,
+            require_known_ingredients: self.require_known_ingredients,
         })
     }
 }
@@ -266,6 +412,7 @@ impl Default for Builder {
             public_keys: None,
             static_path: None,
             facts: None,
+            require_known_ingredients: false,
         }
     }
 }
@@ -311,24 +458,22 @@ async fn dummy_form(
     Redirect::to(&format!("/?name={}", payload.name))
 }
 
-async fn get_recipes(
-    State(ServerState { facts, .. }): State<ServerState>,
-) -> axum::Json<Vec<Recipe>> {
-    axum::Json(facts.recipes)
-}
-
 async fn get_ingredients(
-    State(ServerState { facts, .. }): State<ServerState>,
-) -> axum::Json<HashSet<Ingredient>> {
+    State(ServerState { pg_pool, .. }): State<ServerState>,
+) -> Result<axum::Json<HashSet<Ingredient>>> {
+    let pagination = annapurna_models::Pagination::all();
+    // only the shared catalogue is listed, as the caller may not be signed in
+    let (recipes, _pagination) =
+        annapurna_models::recipe::Recipe::query(&pg_pool, None, pagination).await?;
+
     // iterate over all recipes and collect all ingredients
-    let ingredients = facts
-        .recipes
-        .iter()
-        .flat_map(|recipe| recipe.ingredients.iter())
-        .cloned()
+    let ingredients = recipes
+        .into_iter()
+        .flat_map(|recipe| recipe.ingredients.into_iter())
+        .map(|ingredient| Ingredient::new(ingredient.ingredient_type))
         .collect();
 
-    axum::Json(ingredients)
+    Ok(axum::Json(ingredients))
 }
 
 pub async fn health() -> &'static str {
@@ -0,0 +1,1712 @@
+//! The OpenAPI 3 description of the routes served by [`crate::api_routes`].
+//!
+//! Every route is listed once in [`OPERATIONS`], and the document is assembled from it
+//! along with the schemas of the main request and response types.
+//! The tests compare the table against the router, so a route cannot be added or removed without the other.
+use annapurna_data::{
+    dietary::{Diet, Tag},
+    nutrition::NUTRIENT_COLUMNS,
+};
+use axum::Json;
+use serde_json::{json, Map, Value};
+
+/// The content of a request or response body.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Body {
+    /// A JSON value described by one of the [`schemas`].
+    Schema(&'static str),
+    /// A JSON value described by one of the [`schemas`], which a request may leave out.
+    Optional(&'static str),
+    /// A JSON array of values described by one of the [`schemas`].
+    List(&'static str),
+    /// A [`crate::pagination::Page`] of values described by one of the [`schemas`].
+    Page(&'static str),
+    /// A JSON object without a documented schema.
+    Object,
+    Html,
+    Text,
+    /// A form submitted as `application/x-www-form-urlencoded`.
+    Form,
+    /// A redirect to another location.
+    Redirect,
+    /// No content, answered with `204 No Content`.
+    Empty,
+}
+
+/// A single method on a route.
+#[derive(Debug)]
+pub(crate) struct Operation {
+    /// The lowercase HTTP method.
+    pub method: &'static str,
+    /// The path as given to the router, with parameters written as `:name`.
+    pub path: &'static str,
+    pub tag: &'static str,
+    pub summary: &'static str,
+    /// Whether a bearer token must be sent.
+    pub authenticated: bool,
+    /// The names of the accepted query parameters.
+    pub query: &'static [&'static str],
+    pub request: Option<Body>,
+    pub response: Body,
+}
+
+const DATE_RANGE: &[&str] = &["from", "until"];
+
+/// Every operation served by the API, in the order they are routed.
+pub(crate) const OPERATIONS: &[Operation] = &[
+    Operation {
+        method: "get",
+        path: "/",
+        tag: "meta",
+        summary: "Greet the caller",
+        authenticated: false,
+        query: &[],
+        request: None,
+        response: Body::Html,
+    },
+    Operation {
+        method: "get",
+        path: "/inventory",
+        tag: "inventory",
+        summary: "List a page of the household's inventory",
+        authenticated: true,
+        query: &[
+            "ingredient_type",
+            "expiring_before",
+            "expiring_after",
+            "created_before",
+            "created_after",
+            "sort",
+            "order",
+            "after",
+            "limit",
+        ],
+        request: None,
+        response: Body::Page("Inventory"),
+    },
+    Operation {
+        method: "post",
+        path: "/inventory",
+        tag: "inventory",
+        summary: "Add an item to the household's inventory",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Schema("CreateInventory")),
+        response: Body::Schema("Inventory"),
+    },
+    Operation {
+        method: "post",
+        path: "/inventory/bulk",
+        tag: "inventory",
+        summary: "Create, update and delete inventory items in a single transaction",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::List("BulkOperation")),
+        response: Body::Schema("BulkResponse"),
+    },
+    Operation {
+        method: "get",
+        path: "/inventory/:id",
+        tag: "inventory",
+        summary: "Get an inventory item",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Schema("Inventory"),
+    },
+    Operation {
+        method: "patch",
+        path: "/inventory/:id",
+        tag: "inventory",
+        summary: "Change some fields of an inventory item",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Schema("UpdateInventory")),
+        response: Body::Schema("Inventory"),
+    },
+    Operation {
+        method: "delete",
+        path: "/inventory/:id",
+        tag: "inventory",
+        summary: "Remove an item from the inventory",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Empty,
+    },
+    Operation {
+        method: "get",
+        path: "/login",
+        tag: "meta",
+        summary: "Redirect to the login page of the auth server",
+        authenticated: false,
+        query: &[],
+        request: None,
+        response: Body::Redirect,
+    },
+    Operation {
+        method: "post",
+        path: "/submit",
+        tag: "meta",
+        summary: "Submit the example form",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Form),
+        response: Body::Redirect,
+    },
+    Operation {
+        method: "get",
+        path: "/recipes",
+        tag: "recipes",
+        summary: "List the shared recipes and those of the household",
+        authenticated: true,
+        query: &["after", "limit"],
+        request: None,
+        response: Body::Page("Recipe"),
+    },
+    Operation {
+        method: "post",
+        path: "/recipes",
+        tag: "recipes",
+        summary: "Add a recipe to the household",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Schema("Recipe"),
+    },
+    Operation {
+        method: "get",
+        path: "/recipes/dietary",
+        tag: "recipes",
+        summary: "Split the recipes into those that suit the caller's dietary profile and those it rules out",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Schema("DietCheck"),
+    },
+    Operation {
+        method: "post",
+        path: "/recipes/schedule",
+        tag: "recipes",
+        summary: "Plan the steps of recipes backwards from when they are served, as tasks",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::List("Task"),
+    },
+    Operation {
+        method: "get",
+        path: "/recipes/ranking",
+        tag: "recipes",
+        summary: "Rank the recipes by what is missing from the inventory",
+        authenticated: true,
+        query: &["by", "max_items"],
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "get",
+        path: "/recipes/suggestions",
+        tag: "recipes",
+        summary: "Suggest recipes that use up the inventory",
+        authenticated: true,
+        query: &["days", "max_missing"],
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "get",
+        path: "/recipes/:name",
+        tag: "recipes",
+        summary: "Get a recipe",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Schema("Recipe"),
+    },
+    Operation {
+        method: "put",
+        path: "/recipes/:name",
+        tag: "recipes",
+        summary: "Replace a recipe of the household",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Schema("Recipe"),
+    },
+    Operation {
+        method: "delete",
+        path: "/recipes/:name",
+        tag: "recipes",
+        summary: "Remove a recipe from the household",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Empty,
+    },
+    Operation {
+        method: "post",
+        path: "/recipes/:name/cook",
+        tag: "recipes",
+        summary: "Cook a recipe, taking its ingredients from the inventory",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Optional("CookRecipe")),
+        response: Body::Object,
+    },
+    Operation {
+        method: "get",
+        path: "/recipes/:name/nutrition",
+        tag: "recipes",
+        summary: "Work out the nutrition of a serving of a recipe",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Schema("RecipeNutrition"),
+    },
+    Operation {
+        method: "get",
+        path: "/meal-plans",
+        tag: "meal-plans",
+        summary: "List the household's meal plans",
+        authenticated: true,
+        query: DATE_RANGE,
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "post",
+        path: "/meal-plans",
+        tag: "meal-plans",
+        summary: "Plan a meal",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Object,
+    },
+    Operation {
+        method: "get",
+        path: "/meal-plans/check",
+        tag: "meal-plans",
+        summary: "Check whether the inventory covers the meals planned from today on",
+        authenticated: true,
+        query: DATE_RANGE,
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "get",
+        path: "/meal-plans/:id",
+        tag: "meal-plans",
+        summary: "Get a meal plan",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "put",
+        path: "/meal-plans/:id",
+        tag: "meal-plans",
+        summary: "Replace a meal plan",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Object,
+    },
+    Operation {
+        method: "delete",
+        path: "/meal-plans/:id",
+        tag: "meal-plans",
+        summary: "Remove a meal plan",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Empty,
+    },
+    Operation {
+        method: "get",
+        path: "/shopping-lists",
+        tag: "shopping-lists",
+        summary: "List the household's shopping lists",
+        authenticated: true,
+        query: &["after", "limit"],
+        request: None,
+        response: Body::Page("ShoppingList"),
+    },
+    Operation {
+        method: "post",
+        path: "/shopping-lists",
+        tag: "shopping-lists",
+        summary: "Create a shopping list",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Object,
+    },
+    Operation {
+        method: "get",
+        path: "/shopping-lists/:id",
+        tag: "shopping-lists",
+        summary: "Get a shopping list",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "delete",
+        path: "/shopping-lists/:id",
+        tag: "shopping-lists",
+        summary: "Remove a shopping list",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Empty,
+    },
+    Operation {
+        method: "patch",
+        path: "/shopping-lists/:id/items/:item_id",
+        tag: "shopping-lists",
+        summary: "Check off an item of a shopping list",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Schema("CheckShoppingListItem")),
+        response: Body::Schema("ShoppingListItem"),
+    },
+    Operation {
+        method: "get",
+        path: "/households",
+        tag: "households",
+        summary: "List the households the caller belongs to",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "post",
+        path: "/households",
+        tag: "households",
+        summary: "Create a household owned by the caller",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Object,
+    },
+    Operation {
+        method: "delete",
+        path: "/households/:id",
+        tag: "households",
+        summary: "Remove a household",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Empty,
+    },
+    Operation {
+        method: "get",
+        path: "/households/:id/members",
+        tag: "households",
+        summary: "List the members of a household",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "put",
+        path: "/households/:id/members/:user_id",
+        tag: "households",
+        summary: "Change the role of a member",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Object,
+    },
+    Operation {
+        method: "delete",
+        path: "/households/:id/members/:user_id",
+        tag: "households",
+        summary: "Remove a member from a household",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Empty,
+    },
+    Operation {
+        method: "post",
+        path: "/households/:id/invitations",
+        tag: "households",
+        summary: "Invite someone to a household",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Object,
+    },
+    Operation {
+        method: "get",
+        path: "/invitations",
+        tag: "households",
+        summary: "List the invitations addressed to the caller",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "delete",
+        path: "/invitations/:id",
+        tag: "households",
+        summary: "Decline an invitation",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Empty,
+    },
+    Operation {
+        method: "post",
+        path: "/invitations/:id/accept",
+        tag: "households",
+        summary: "Accept an invitation and join its household",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Object,
+    },
+    Operation {
+        method: "get",
+        path: "/tasks",
+        tag: "tasks",
+        summary: "List a page of the household's tasks",
+        authenticated: true,
+        query: &["after", "limit"],
+        request: None,
+        response: Body::Page("Task"),
+    },
+    Operation {
+        method: "post",
+        path: "/tasks",
+        tag: "tasks",
+        summary: "Add a task to the household's todo list",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Schema("Task"),
+    },
+    Operation {
+        method: "post",
+        path: "/tasks/import",
+        tag: "tasks",
+        summary: "Add the tasks from the facts that the household does not have yet",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::List("Task"),
+    },
+    Operation {
+        method: "get",
+        path: "/tasks/occurrences",
+        tag: "tasks",
+        summary: "List when the household's recurring tasks are due over a range of days",
+        authenticated: true,
+        query: DATE_RANGE,
+        request: None,
+        response: Body::List("ScheduledTask"),
+    },
+    Operation {
+        method: "get",
+        path: "/tasks/:id",
+        tag: "tasks",
+        summary: "Get a task",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Schema("Task"),
+    },
+    Operation {
+        method: "patch",
+        path: "/tasks/:id",
+        tag: "tasks",
+        summary: "Change some fields of a task, or complete it",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Schema("UpdateTask")),
+        response: Body::Schema("Task"),
+    },
+    Operation {
+        method: "delete",
+        path: "/tasks/:id",
+        tag: "tasks",
+        summary: "Remove a task",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Empty,
+    },
+    Operation {
+        method: "put",
+        path: "/tasks/:id/occurrences",
+        tag: "tasks",
+        summary: "Mark one occurrence of a recurring task as done, or not",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Object),
+        response: Body::Schema("ScheduledTask"),
+    },
+    Operation {
+        method: "get",
+        path: "/tasks/:id/streak",
+        tag: "tasks",
+        summary: "Count how many occurrences of a recurring task were done in a row",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Schema("Streak"),
+    },
+    Operation {
+        method: "get",
+        path: "/dietary-profile",
+        tag: "recipes",
+        summary: "Get the caller's dietary profile",
+        authenticated: true,
+        query: &[],
+        request: None,
+        response: Body::Schema("DietaryProfile"),
+    },
+    Operation {
+        method: "put",
+        path: "/dietary-profile",
+        tag: "recipes",
+        summary: "Replace the caller's dietary profile, which rules out the recipes suggested to them",
+        authenticated: true,
+        query: &[],
+        request: Some(Body::Schema("DietaryProfile")),
+        response: Body::Schema("DietaryProfile"),
+    },
+    Operation {
+        method: "get",
+        path: "/ingredients",
+        tag: "recipes",
+        summary: "List the ingredients of the shared recipes",
+        authenticated: false,
+        query: &[],
+        request: None,
+        response: Body::List("Ingredient"),
+    },
+    Operation {
+        method: "get",
+        path: "/nutrition",
+        tag: "recipes",
+        summary: "List the nutrition of ingredients, per 100 g",
+        authenticated: false,
+        query: &[],
+        request: None,
+        response: Body::List("IngredientNutrition"),
+    },
+    Operation {
+        method: "get",
+        path: "/health",
+        tag: "meta",
+        summary: "Check that the server is up",
+        authenticated: false,
+        query: &[],
+        request: None,
+        response: Body::Text,
+    },
+    Operation {
+        method: "get",
+        path: "/openapi.json",
+        tag: "meta",
+        summary: "Get this document",
+        authenticated: false,
+        query: &[],
+        request: None,
+        response: Body::Object,
+    },
+];
+
+/// Serves the OpenAPI document of the API.
+pub(crate) async fn openapi() -> Json<Value> {
+    Json(document())
+}
+
+/// Builds the OpenAPI document from [`OPERATIONS`].
+pub(crate) fn document() -> Value {
+    let mut paths = Map::new();
+    for operation in OPERATIONS {
+        let path = paths
+            .entry(openapi_path(operation.path))
+            .or_insert_with(|| json!({}));
+        path[operation.method] = operation.to_json();
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "annapurna",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "servers": [{ "url": "/api" }],
+        "paths": paths,
+        "components": {
+            "schemas": schemas(),
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+            },
+        },
+    })
+}
+
+impl Operation {
+    fn to_json(&self) -> Value {
+        let mut parameters: Vec<Value> = path_parameters(self.path)
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect();
+        parameters.extend(self.query.iter().map(|name| {
+            json!({
+                "name": name,
+                "in": "query",
+                "required": false,
+                "schema": { "type": "string" },
+            })
+        }));
+
+        let mut responses = Map::new();
+        let (status, response) = match self.response {
+            Body::Empty => ("204", json!({ "description": "No content" })),
+            Body::Redirect => ("307", json!({ "description": "Redirect" })),
+            body => (
+                "200",
+                json!({ "description": "Success", "content": body.content() }),
+            ),
+        };
+        responses.insert(status.to_string(), response);
+        responses.insert(
+            "default".to_string(),
+            json!({
+                "description": "Error",
+                "content": Body::Schema("ErrorBody").content(),
+            }),
+        );
+
+        let mut operation = json!({
+            "tags": [self.tag],
+            "summary": self.summary,
+            "operationId": format!("{} {}", self.method, self.path),
+            "parameters": parameters,
+            "responses": responses,
+        });
+        if let Some(request) = self.request {
+            let required = !matches!(request, Body::Optional(_));
+            operation["requestBody"] =
+                json!({ "required": required, "content": request.content() });
+        }
+        if self.authenticated {
+            operation["security"] = json!([{ "bearer": [] }]);
+        }
+        operation
+    }
+}
+
+impl Body {
+    /// The `content` of a request or response with this body, keyed by media type.
+    fn content(self) -> Value {
+        match self {
+            Body::Schema(name) | Body::Optional(name) => {
+                json!({ "application/json": { "schema": reference(name) } })
+            }
+            Body::List(name) => json!({
+                "application/json": { "schema": { "type": "array", "items": reference(name) } },
+            }),
+            Body::Page(name) => json!({
+                "application/json": {
+                    "schema": {
+                        "type": "object",
+                        "required": ["items"],
+                        "properties": {
+                            "items": { "type": "array", "items": reference(name) },
+                            "next": { "type": "string", "nullable": true },
+                        },
+                    },
+                },
+            }),
+            Body::Object => json!({ "application/json": { "schema": { "type": "object" } } }),
+            Body::Html => json!({ "text/html": { "schema": { "type": "string" } } }),
+            Body::Text => json!({ "text/plain": { "schema": { "type": "string" } } }),
+            Body::Form => json!({
+                "application/x-www-form-urlencoded": { "schema": { "type": "object" } },
+            }),
+            Body::Redirect | Body::Empty => json!({}),
+        }
+    }
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+/// Converts a router path such as `/recipes/:name` to the OpenAPI form `/recipes/{name}`.
+fn openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{name}}}"),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix(':'))
+}
+
+/// The schemas of the types exchanged with the API.
+fn schemas() -> Value {
+    let ulid = json!({ "type": "string", "description": "A ULID" });
+    let timestamp = json!({ "type": "string", "format": "date-time" });
+    let nullable_timestamp = json!({ "type": "string", "format": "date-time", "nullable": true });
+    let nutrients = NUTRIENT_COLUMNS
+        .iter()
+        .map(|name| (name.to_string(), json!({ "type": "number" })))
+        .collect::<Map<_, _>>();
+    let diets = Diet::ALL.iter().map(Diet::as_str).collect::<Vec<_>>();
+    let tags = Tag::ALL.iter().map(Tag::as_str).collect::<Vec<_>>();
+
+    json!({
+        "Inventory": {
+            "type": "object",
+            "required": [
+                "inventory_id",
+                "owner_id",
+                "household_id",
+                "ingredient_type",
+                "quantity",
+                "created_at",
+            ],
+            "properties": {
+                "inventory_id": ulid,
+                "owner_id": ulid,
+                "household_id": ulid,
+                "ingredient_type": { "type": "string" },
+                "quantity": { "type": "string", "example": "500 g" },
+                "created_at": timestamp,
+                "expiration_date": nullable_timestamp,
+            },
+        },
+        "CreateInventory": {
+            "type": "object",
+            "required": ["ingredient_type", "quantity"],
+            "properties": {
+                "inventory_id": ulid,
+                "ingredient_type": { "type": "string" },
+                "quantity": { "type": "string", "example": "500 g" },
+                "expiration_date": nullable_timestamp,
+            },
+        },
+        "UpdateInventory": {
+            "type": "object",
+            "description": "Only the fields that are sent are changed. A `null` expiration date removes it.",
+            "properties": {
+                "ingredient_type": { "type": "string" },
+                "quantity": { "type": "string", "example": "500 g" },
+                "expiration_date": nullable_timestamp,
+            },
+        },
+        "BulkOperation": {
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["op", "ingredient_type", "quantity"],
+                    "properties": {
+                        "op": { "type": "string", "enum": ["create"] },
+                        "inventory_id": ulid,
+                        "ingredient_type": { "type": "string" },
+                        "quantity": { "type": "string", "example": "500 g" },
+                        "expiration_date": nullable_timestamp,
+                    },
+                },
+                {
+                    "type": "object",
+                    "required": ["op", "inventory_id"],
+                    "properties": {
+                        "op": { "type": "string", "enum": ["update"] },
+                        "inventory_id": ulid,
+                        "ingredient_type": { "type": "string" },
+                        "quantity": { "type": "string", "example": "500 g" },
+                        "expiration_date": nullable_timestamp,
+                    },
+                },
+                {
+                    "type": "object",
+                    "required": ["op", "inventory_id"],
+                    "properties": {
+                        "op": { "type": "string", "enum": ["delete"] },
+                        "inventory_id": ulid,
+                    },
+                },
+            ],
+        },
+        "BulkResponse": {
+            "type": "object",
+            "required": ["committed", "results"],
+            "properties": {
+                "committed": { "type": "boolean" },
+                "results": { "type": "array", "items": reference("BulkResult") },
+            },
+        },
+        "BulkResult": {
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["status", "item"],
+                    "properties": {
+                        "status": { "type": "string", "enum": ["created", "updated"] },
+                        "item": reference("Inventory"),
+                    },
+                },
+                {
+                    "type": "object",
+                    "required": ["status", "inventory_id"],
+                    "properties": {
+                        "status": { "type": "string", "enum": ["deleted"] },
+                        "inventory_id": ulid,
+                    },
+                },
+                {
+                    "type": "object",
+                    "required": ["status", "inventory_id", "error"],
+                    "properties": {
+                        "status": { "type": "string", "enum": ["failed"] },
+                        "inventory_id": { "type": "string", "description": "A ULID", "nullable": true },
+                        "error": reference("ErrorBody"),
+                    },
+                },
+            ],
+        },
+        "Recipe": {
+            "type": "object",
+            "required": ["recipe_id", "name", "servings", "created_at", "ingredients"],
+            "properties": {
+                "recipe_id": ulid,
+                "household_id": {
+                    "type": "string",
+                    "nullable": true,
+                    "description": "Missing for recipes of the shared catalogue",
+                },
+                "name": { "type": "string" },
+                "servings": { "type": "integer", "minimum": 1 },
+                "created_at": timestamp,
+                "ingredients": { "type": "array", "items": reference("RecipeIngredient") },
+                "steps": { "type": "array", "items": reference("RecipeStep") },
+            },
+        },
+        "RecipeStep": {
+            "type": "object",
+            "required": ["description", "duration"],
+            "properties": {
+                "description": { "type": "string" },
+                "duration": reference("Duration"),
+                "after": {
+                    "type": "array",
+                    "items": { "type": "integer", "minimum": 0 },
+                    "nullable": true,
+                    "description": "The positions of the steps to finish first. Missing to follow the step before",
+                },
+            },
+        },
+        "RecipeIngredient": {
+            "type": "object",
+            "required": ["ingredient_type"],
+            "properties": {
+                "ingredient_type": { "type": "string" },
+                "quantity": { "type": "string", "nullable": true },
+            },
+        },
+        "Ingredient": {
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "amount": reference("Quantity"),
+            },
+        },
+        "Quantity": {
+            "type": "object",
+            "nullable": true,
+            "required": ["value", "unit"],
+            "properties": {
+                "value": { "type": "number" },
+                "unit": { "type": "string" },
+            },
+        },
+        "ShoppingList": {
+            "type": "object",
+            "required": [
+                "shopping_list_id",
+                "owner_id",
+                "household_id",
+                "name",
+                "recipes",
+                "created_at",
+                "items",
+            ],
+            "properties": {
+                "shopping_list_id": ulid,
+                "owner_id": ulid,
+                "household_id": ulid,
+                "name": { "type": "string" },
+                "recipes": { "type": "array", "items": { "type": "string" } },
+                "created_at": timestamp,
+                "items": { "type": "array", "items": reference("ShoppingListItem") },
+            },
+        },
+        "ShoppingListItem": {
+            "type": "object",
+            "required": ["shopping_list_item_id", "shopping_list_id", "ingredient_type", "checked"],
+            "properties": {
+                "shopping_list_item_id": ulid,
+                "shopping_list_id": ulid,
+                "ingredient_type": { "type": "string" },
+                "quantity": { "type": "string", "nullable": true },
+                "checked": { "type": "boolean" },
+            },
+        },
+        "CheckShoppingListItem": {
+            "type": "object",
+            "required": ["checked"],
+            "properties": {
+                "checked": { "type": "boolean" },
+            },
+        },
+        "UpdateTask": {
+            "type": "object",
+            "description": "Only the fields that are sent are changed. A `null` duration, start time or recurrence removes it.",
+            "properties": {
+                "description": { "type": "string" },
+                "completed": { "type": "boolean" },
+                "duration": reference("Duration"),
+                "start_time": nullable_timestamp,
+                "recurrence": reference("Recurrence"),
+            },
+        },
+        "Task": {
+            "type": "object",
+            "required": [
+                "task_id",
+                "owner_id",
+                "household_id",
+                "description",
+                "completed",
+                "created_at",
+            ],
+            "properties": {
+                "task_id": ulid,
+                "owner_id": ulid,
+                "household_id": ulid,
+                "description": { "type": "string" },
+                "completed": { "type": "boolean" },
+                "duration": reference("Duration"),
+                "start_time": nullable_timestamp,
+                "completion_time": nullable_timestamp,
+                "recurrence": reference("Recurrence"),
+                "created_at": timestamp,
+            },
+        },
+        "Recurrence": {
+            "type": "object",
+            "nullable": true,
+            "required": ["repeat"],
+            "properties": {
+                "repeat": {
+                    "description": "`daily`, `weekdays`, or `{\"every\": {\"days\": 2, \"starting\": \"2024-03-01\"}}`",
+                },
+                "times": { "type": "array", "items": { "type": "string", "format": "time" } },
+            },
+        },
+        "ScheduledTask": {
+            "type": "object",
+            "required": ["task_id", "description", "date", "scheduled_at", "completed"],
+            "properties": {
+                "task_id": ulid,
+                "description": { "type": "string" },
+                "date": { "type": "string", "format": "date" },
+                "time": { "type": "string", "format": "time", "nullable": true },
+                "scheduled_at": timestamp,
+                "completed": { "type": "boolean" },
+                "completion_time": nullable_timestamp,
+            },
+        },
+        "Streak": {
+            "type": "object",
+            "required": ["current", "longest", "completed", "missed"],
+            "properties": {
+                "current": { "type": "integer", "minimum": 0 },
+                "longest": { "type": "integer", "minimum": 0 },
+                "completed": { "type": "integer", "minimum": 0 },
+                "missed": { "type": "integer", "minimum": 0 },
+            },
+        },
+        "DietaryProfile": {
+            "type": "object",
+            "properties": {
+                "diets": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": diets },
+                },
+                "avoid": { "type": "array", "items": reference("Tag") },
+            },
+        },
+        "Tag": {
+            "type": "string",
+            "enum": tags,
+        },
+        "DietCheck": {
+            "type": "object",
+            "required": ["allowed", "forbidden"],
+            "properties": {
+                "allowed": { "type": "array", "items": { "type": "string" } },
+                "forbidden": {
+                    "type": "object",
+                    "description": "The ingredients that rule out each recipe, by recipe",
+                    "additionalProperties": { "type": "array", "items": reference("ForbiddenIngredient") },
+                },
+            },
+        },
+        "ForbiddenIngredient": {
+            "type": "object",
+            "required": ["ingredient", "tag"],
+            "properties": {
+                "ingredient": { "type": "string" },
+                "tag": reference("Tag"),
+            },
+        },
+        "Nutrients": {
+            "type": "object",
+            "description": "Energy in kcal, macronutrients in grams and micronutrients in milligrams",
+            "required": NUTRIENT_COLUMNS,
+            "properties": nutrients,
+        },
+        "IngredientNutrition": {
+            "type": "object",
+            "required": ["ingredient_type", "per_100g"],
+            "properties": {
+                "ingredient_type": { "type": "string" },
+                "per_100g": reference("Nutrients"),
+                "piece_weight": {
+                    "type": "number",
+                    "nullable": true,
+                    "description": "The weight of a piece in grams, for ingredients that are counted",
+                },
+                "updated_at": {
+                    "type": "string",
+                    "format": "date-time",
+                    "nullable": true,
+                    "description": "Missing for nutrition taken from the facts",
+                },
+            },
+        },
+        "RecipeNutrition": {
+            "type": "object",
+            "required": ["recipe", "servings", "per_serving", "unknown"],
+            "properties": {
+                "recipe": { "type": "string" },
+                "servings": { "type": "integer", "minimum": 1 },
+                "per_serving": reference("Nutrients"),
+                "unknown": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "The ingredients left out, as their amount or nutrition is unknown",
+                },
+            },
+        },
+        "Duration": {
+            "type": "object",
+            "nullable": true,
+            "required": ["secs", "nanos"],
+            "properties": {
+                "secs": { "type": "integer", "minimum": 0 },
+                "nanos": { "type": "integer", "minimum": 0 },
+            },
+        },
+        "ErrorBody": {
+            "type": "object",
+            "required": ["code", "message"],
+            "properties": {
+                "code": {
+                    "type": "string",
+                    "enum": ["not_found", "method_not_allowed", "validation", "unauthorized", "forbidden", "conflict", "internal"],
+                },
+                "message": { "type": "string" },
+                "fields": { "type": "array", "items": reference("FieldError") },
+                "shortfalls": { "type": "array", "items": reference("Shortfall") },
+            },
+        },
+        "CookRecipe": {
+            "type": "object",
+            "properties": {
+                "servings": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "nullable": true,
+                    "description": "Defaults to everything the recipe makes",
+                },
+                "force": {
+                    "type": "boolean",
+                    "description": "Cook even though the inventory is short of some ingredients",
+                },
+            },
+        },
+        "Shortfall": {
+            "type": "object",
+            "required": ["ingredient"],
+            "properties": {
+                "ingredient": { "type": "string" },
+                "amount": reference("Quantity"),
+            },
+        },
+        "FieldError": {
+            "type": "object",
+            "required": ["field", "message"],
+            "properties": {
+                "field": { "type": "string" },
+                "message": { "type": "string" },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api_routes, routes, ServerState};
+    use annapurna_data::Facts;
+    use axum::{body::Body as RequestBody, http::Request, http::StatusCode, Router};
+    use lockpad_auth::PublicKey;
+    use std::collections::{BTreeSet, HashSet};
+    use tower::ServiceExt;
+
+    const METHODS: &[&str] = &["get", "post", "put", "patch", "delete"];
+
+    const PUBLIC_KEY: &str = "-----BEGIN RSA PUBLIC KEY-----
+MIGJAoGBAMcl+OkpfvClD7XbrW0L5lk6SPrnLkAu/9jvmd72NCUgB64k9WyPx9V0
+J2e0N0hl39iSTEW/LCFvelpckMO0CHzDdPfkZ1U7Hklr55C/NKcIKzui8mHW14TK
+zgO0RA3YrCPcOc4VABYSmIG9AMSyhzQy8IabneilD+LKbBcz0oQrAgMBAAE=
+-----END RSA PUBLIC KEY-----";
+
+    /// A state whose database is never reached: requests are rejected before connecting,
+    /// or fail to connect to a port nothing listens on.
+    fn state() -> ServerState {
+        let pg_pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_secs(1))
+            .connect_lazy("postgres://annapurna@127.0.0.1:1/annapurna")
+            .unwrap();
+
+        ServerState {
+            pg_pool,
+            public_key: PublicKey::parse_from_pem(PUBLIC_KEY).unwrap(),
+            auth_url: "http://localhost:5000".to_string(),
+            auth_app_id: "annapurna".to_string(),
+            facts: Facts {
+                inventory: Vec::new(),
+                recipes: Vec::new(),
+                densities: Default::default(),
+                substitutions: Vec::new(),
+                prices: Default::default(),
+                servings: Default::default(),
+                expirations: Default::default(),
+                tasks: Vec::new(),
+                nutrition: Default::default(),
+                tags: Default::default(),
+            },
+            require_known_ingredients: false,
+        }
+    }
+
+    /// Whether the router has a handler for the method on the path.
+    async fn is_routed(router: &Router, method: &str, path: &str) -> bool {
+        let uri: String = path
+            .split('/')
+            .map(|segment| match segment.starts_with(':') {
+                true => "01ARZ3NDEKTSV4RRFFQ69G5FAV",
+                false => segment,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let request = Request::builder()
+            .method(method.to_uppercase().as_str())
+            .uri(uri)
+            .body(RequestBody::empty())
+            .unwrap();
+
+        let status = router.clone().oneshot(request).await.unwrap().status();
+        status != StatusCode::METHOD_NOT_ALLOWED && status != StatusCode::IM_A_TEAPOT
+    }
+
+    #[test]
+    fn documents_every_routed_path() {
+        let documented: BTreeSet<String> = OPERATIONS
+            .iter()
+            .map(|operation| operation.path.to_string())
+            .collect();
+
+        let routed: BTreeSet<String> = routes::<ServerState>()
+            .into_iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+
+        assert_eq!(routed, documented);
+    }
+
+    #[tokio::test]
+    async fn documents_every_routed_method() {
+        let router = api_routes()
+            .fallback(|| async { StatusCode::IM_A_TEAPOT })
+            .with_state(state());
+
+        let documented: HashSet<(&str, &str)> = OPERATIONS
+            .iter()
+            .map(|operation| (operation.method, operation.path))
+            .collect();
+        let paths: BTreeSet<&str> = OPERATIONS.iter().map(|operation| operation.path).collect();
+
+        for path in paths {
+            for method in METHODS {
+                assert_eq!(
+                    is_routed(&router, method, path).await,
+                    documented.contains(&(*method, path)),
+                    "{method} {path} is routed but not documented, or documented but not routed",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reserves_the_names_of_recipe_routes() {
+        let routes = OPERATIONS
+            .iter()
+            .filter_map(|operation| operation.path.strip_prefix("/recipes/"))
+            .filter_map(|rest| rest.split('/').next())
+            .filter(|segment| !segment.starts_with(':'))
+            .collect::<BTreeSet<_>>();
+        let reserved = crate::handlers::recipes::RESERVED_NAMES
+            .iter()
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(routes, reserved);
+    }
+
+    #[test]
+    fn converts_path_parameters() {
+        assert_eq!(
+            openapi_path("/shopping-lists/:id/items/:item_id"),
+            "/shopping-lists/{id}/items/{item_id}"
+        );
+
+        let document = document();
+        let operation = &document["paths"]["/inventory/{id}"]["patch"];
+        assert_eq!(operation["parameters"][0]["name"], "id");
+        assert_eq!(operation["security"][0]["bearer"], json!([]));
+        assert!(document["paths"]["/health"]["get"]["security"].is_null());
+    }
+
+    /// Checks a value against one of the schemas, returning where it first differs.
+    ///
+    /// Only the parts of JSON Schema the schemas use are understood. Objects are held to the
+    /// properties their schema lists, so a field added to a type without documenting it is caught.
+    fn conforms(value: &Value, schema: &Value, at: &str) -> std::result::Result<(), String> {
+        if let Some(name) = schema["$ref"].as_str() {
+            let name = name.trim_start_matches("#/components/schemas/");
+            return conforms(value, &schemas()[name], at);
+        }
+        if value.is_null() {
+            return match schema["nullable"].as_bool() == Some(true) || schema["type"].is_null() {
+                true => Ok(()),
+                false => Err(format!("{at} is null")),
+            };
+        }
+        if let Some(all) = schema["allOf"].as_array() {
+            return all
+                .iter()
+                .try_for_each(|schema| conforms(value, schema, at));
+        }
+        if let Some(one) = schema["oneOf"].as_array() {
+            let matching = one
+                .iter()
+                .filter(|schema| conforms(value, schema, at).is_ok())
+                .count();
+            return match matching {
+                1 => Ok(()),
+                _ => Err(format!(
+                    "{at} matches {matching} of its schemas rather than one"
+                )),
+            };
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                return Err(format!("{at} is {value}, which is not one of {allowed:?}"));
+            }
+        }
+
+        let valid = match schema["type"].as_str() {
+            None => true,
+            Some("string") => value.is_string(),
+            Some("boolean") => value.is_boolean(),
+            Some("number") => value.is_number(),
+            Some("integer") => {
+                let minimum = schema["minimum"].as_i64().unwrap_or(i64::MIN);
+                value
+                    .as_i64()
+                    .map_or(value.is_u64(), |value| value >= minimum)
+            }
+            Some("array") => {
+                let Some(items) = value.as_array() else {
+                    return Err(format!("{at} is not an array"));
+                };
+                for (index, item) in items.iter().enumerate() {
+                    conforms(item, &schema["items"], &format!("{at}[{index}]"))?;
+                }
+                true
+            }
+            Some("object") => {
+                let Some(fields) = value.as_object() else {
+                    return Err(format!("{at} is not an object"));
+                };
+                for required in schema["required"].as_array().into_iter().flatten() {
+                    let required = required.as_str().unwrap();
+                    if !fields.contains_key(required) {
+                        return Err(format!("{at}.{required} is missing"));
+                    }
+                }
+                for (name, field) in fields {
+                    let field_schema = match &schema["properties"][name] {
+                        Value::Null => &schema["additionalProperties"],
+                        field_schema => field_schema,
+                    };
+                    if field_schema.is_null() {
+                        return Err(format!("{at}.{name} is not documented"));
+                    }
+                    conforms(field, field_schema, &format!("{at}.{name}"))?;
+                }
+                true
+            }
+            Some(other) => return Err(format!("{at} has an unknown type {other}")),
+        };
+
+        match valid {
+            true => Ok(()),
+            false => Err(format!(
+                "{at} is {value}, which is not a {}",
+                schema["type"]
+            )),
+        }
+    }
+
+    fn assert_conforms(name: &str, value: impl serde::Serialize) {
+        let value = serde_json::to_value(value).unwrap();
+        if let Err(error) = conforms(&value, &schemas()[name], name) {
+            panic!("{error}, in {value}");
+        }
+    }
+
+    /// Checks a request body against its schema, and that the server reads it.
+    fn assert_accepts<T: serde::de::DeserializeOwned>(name: &str, value: Value) {
+        if let Err(error) = conforms(&value, &schemas()[name], name) {
+            panic!("{error}, in {value}");
+        }
+        serde_json::from_value::<T>(value).unwrap();
+    }
+
+    #[test]
+    fn describes_the_types_it_documents() {
+        use crate::{
+            error::{Error, ErrorBody},
+            handlers::{
+                inventory::{
+                    BulkOperation, BulkResponse, BulkResult, CreateInventory, UpdateInventory,
+                },
+                nutrition::KnownNutrition,
+                recipes::{CookRecipe, DietCheck},
+                shopping_lists::CheckShoppingListItem,
+                tasks::{ScheduledTask, UpdateTask},
+            },
+            validation::FieldError,
+        };
+        use annapurna_data::{
+            dietary::DietaryProfile,
+            nutrition::{Nutrients, Nutrition},
+            types::{Ingredient, Quantity, Recurrence, Repeat},
+        };
+        use annapurna_logic::{
+            nutrition::RecipeNutrition,
+            recipe::{ForbiddenIngredient, Shortfall},
+            routine::{Occurrence, Streak},
+        };
+        use annapurna_models::{
+            inventory::Inventory,
+            recipe::{Recipe, RecipeIngredient, RecipeStep},
+            shopping_list::{ShoppingList, ShoppingListItem},
+            task::Task,
+        };
+        use chrono::{NaiveDate, NaiveTime, Utc};
+        use sqlx_ulid::Ulid;
+        use std::{collections::BTreeMap, time::Duration};
+
+        let now = Utc::now();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let time = NaiveTime::from_hms_opt(7, 30, 0).unwrap();
+
+        let inventory = || Inventory {
+            inventory_id: Ulid::generate(),
+            owner_id: Ulid::generate(),
+            household_id: Ulid::generate(),
+            ingredient_type: "flour".to_string(),
+            quantity: "500 g".to_string(),
+            created_at: now,
+            expiration_date: Some(now),
+        };
+        assert_conforms("Inventory", inventory());
+        assert_accepts::<CreateInventory>(
+            "CreateInventory",
+            json!({ "ingredient_type": "flour", "quantity": "500 g", "expiration_date": null }),
+        );
+        assert_accepts::<UpdateInventory>("UpdateInventory", json!({ "quantity": "1 kg" }));
+        assert_accepts::<UpdateInventory>("UpdateInventory", json!({ "expiration_date": null }));
+
+        let inventory_id = Ulid::generate().to_string();
+        let operations = [
+            json!({ "op": "create", "ingredient_type": "flour", "quantity": "500 g" }),
+            json!({ "op": "update", "inventory_id": inventory_id, "quantity": "1 kg" }),
+            json!({ "op": "delete", "inventory_id": inventory_id }),
+        ];
+        for operation in operations {
+            assert_accepts::<BulkOperation>("BulkOperation", operation);
+        }
+        assert_conforms(
+            "BulkResponse",
+            BulkResponse {
+                committed: false,
+                results: vec![
+                    BulkResult::Created { item: inventory() },
+                    BulkResult::Updated { item: inventory() },
+                    BulkResult::Deleted {
+                        inventory_id: Ulid::generate(),
+                    },
+                    BulkResult::Failed {
+                        inventory_id: None,
+                        error: Error::NotFound.body(),
+                    },
+                ],
+            },
+        );
+
+        for household_id in [None, Some(Ulid::generate())] {
+            assert_conforms(
+                "Recipe",
+                Recipe {
+                    recipe_id: Ulid::generate(),
+                    household_id,
+                    name: "bread".to_string(),
+                    servings: 2,
+                    created_at: now,
+                    ingredients: vec![
+                        RecipeIngredient {
+                            ingredient_type: "flour".to_string(),
+                            quantity: Some("500 g".to_string()),
+                        },
+                        RecipeIngredient {
+                            ingredient_type: "salt".to_string(),
+                            quantity: None,
+                        },
+                    ],
+                    steps: vec![
+                        RecipeStep {
+                            description: "knead".to_string(),
+                            duration: Duration::from_secs(600),
+                            after: None,
+                        },
+                        RecipeStep {
+                            description: "bake".to_string(),
+                            duration: Duration::from_secs(1800),
+                            after: Some(vec![0]),
+                        },
+                    ],
+                },
+            );
+        }
+        assert_accepts::<CookRecipe>("CookRecipe", json!({}));
+        assert_accepts::<CookRecipe>("CookRecipe", json!({ "servings": 4, "force": true }));
+
+        assert_conforms(
+            "Ingredient",
+            Ingredient::with_amount("flour".to_string(), Quantity::new(500.0, "g".to_string())),
+        );
+        assert_conforms("Ingredient", Ingredient::new("salt".to_string()));
+
+        let shopping_list_id = Ulid::generate();
+        assert_conforms(
+            "ShoppingList",
+            ShoppingList {
+                shopping_list_id,
+                owner_id: Ulid::generate(),
+                household_id: Ulid::generate(),
+                name: "weekend".to_string(),
+                recipes: vec!["bread".to_string()],
+                created_at: now,
+                items: vec![ShoppingListItem {
+                    shopping_list_item_id: Ulid::generate(),
+                    shopping_list_id,
+                    ingredient_type: "flour".to_string(),
+                    quantity: None,
+                    checked: false,
+                }],
+            },
+        );
+        assert_accepts::<CheckShoppingListItem>(
+            "CheckShoppingListItem",
+            json!({ "checked": true }),
+        );
+
+        let recurrences = [
+            None,
+            Some(Recurrence {
+                repeat: Repeat::Weekdays,
+                times: vec![time],
+            }),
+            Some(Recurrence {
+                repeat: Repeat::Every {
+                    days: 2,
+                    starting: date,
+                },
+                times: Vec::new(),
+            }),
+        ];
+        for recurrence in recurrences {
+            assert_conforms(
+                "Task",
+                Task {
+                    task_id: Ulid::generate(),
+                    owner_id: Ulid::generate(),
+                    household_id: Ulid::generate(),
+                    description: "water the plants".to_string(),
+                    completed: false,
+                    duration: recurrence.as_ref().map(|_| Duration::from_secs(300)),
+                    start_time: recurrence.as_ref().map(|_| now),
+                    completion_time: None,
+                    recurrence,
+                    created_at: now,
+                },
+            );
+        }
+        assert_accepts::<UpdateTask>("UpdateTask", json!({ "completed": true }));
+        assert_accepts::<UpdateTask>(
+            "UpdateTask",
+            json!({
+                "description": "water the garden",
+                "duration": { "secs": 600, "nanos": 0 },
+                "start_time": null,
+                "recurrence": { "repeat": "daily", "times": ["07:30:00"] },
+            }),
+        );
+        assert_accepts::<UpdateTask>("UpdateTask", json!({ "recurrence": null }));
+
+        for time in [None, Some(time)] {
+            assert_conforms(
+                "ScheduledTask",
+                ScheduledTask {
+                    task_id: Ulid::generate(),
+                    description: "water the plants".to_string(),
+                    occurrence: Occurrence { date, time },
+                    scheduled_at: now,
+                    completed: true,
+                    completion_time: Some(now),
+                },
+            );
+        }
+        assert_conforms("Streak", Streak::default());
+
+        assert_conforms(
+            "DietaryProfile",
+            DietaryProfile {
+                diets: vec![Diet::Vegetarian],
+                avoid: vec![Tag::Nut],
+            },
+        );
+        assert_accepts::<DietaryProfile>("DietaryProfile", json!({ "avoid": ["nut"] }));
+        assert_conforms(
+            "DietCheck",
+            DietCheck {
+                allowed: vec!["bread".to_string()],
+                forbidden: BTreeMap::from([(
+                    "stew".to_string(),
+                    vec![ForbiddenIngredient {
+                        ingredient: "beef".to_string(),
+                        tag: Tag::Meat,
+                    }],
+                )]),
+            },
+        );
+
+        let per_100g = Nutrients {
+            calories: 364.0,
+            ..Default::default()
+        };
+        for (piece_weight, updated_at) in [(None, None), (Some(50.0), Some(now))] {
+            assert_conforms(
+                "IngredientNutrition",
+                KnownNutrition {
+                    ingredient_type: "flour".to_string(),
+                    nutrition: Nutrition {
+                        per_100g,
+                        piece_weight,
+                    },
+                    updated_at,
+                },
+            );
+        }
+        assert_conforms(
+            "RecipeNutrition",
+            RecipeNutrition {
+                recipe: "bread".to_string(),
+                servings: 2,
+                per_serving: per_100g,
+                unknown: vec!["salt".to_string()],
+            },
+        );
+
+        assert_conforms("ErrorBody", Error::NotFound.body());
+        assert_conforms(
+            "ErrorBody",
+            ErrorBody {
+                fields: vec![FieldError {
+                    field: "quantity".to_string(),
+                    message: "must not be blank".to_string(),
+                }],
+                ..Error::Validation(Vec::new()).body()
+            },
+        );
+        assert_conforms(
+            "ErrorBody",
+            Error::Shortfalls(vec![
+                Shortfall {
+                    ingredient: "flour".to_string(),
+                    amount: Some(Quantity::new(200.0, "g".to_string())),
+                },
+                Shortfall {
+                    ingredient: "salt".to_string(),
+                    amount: None,
+                },
+            ])
+            .body(),
+        );
+    }
+}
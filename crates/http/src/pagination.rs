@@ -0,0 +1,41 @@
+use annapurna_models::Pagination;
+use sqlx_ulid::Ulid;
+
+/// The number of items returned when a page does not ask for a limit.
+const DEFAULT_LIMIT: usize = 50;
+/// The most items a single page may return.
+const MAX_LIMIT: usize = 500;
+
+/// Query parameters selecting a page of a list, as `?after=<ulid>&limit=<n>`.
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct PageParams {
+    /// The cursor returned with the previous page.
+    pub after: Option<Ulid>,
+    pub limit: Option<usize>,
+}
+
+impl From<PageParams> for Pagination {
+    fn from(params: PageParams) -> Self {
+        Pagination {
+            last_key: params.after,
+            count: params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        }
+    }
+}
+
+/// A page of a list, along with the cursor to pass as `after` to fetch the next one.
+/// The cursor is missing on the last page.
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct Page<T> {
+    pub items: Vec<T>,
+    pub next: Option<Ulid>,
+}
+
+impl<T> From<(Vec<T>, Pagination)> for Page<T> {
+    fn from((items, pagination): (Vec<T>, Pagination)) -> Self {
+        Page {
+            items,
+            next: pagination.last_key,
+        }
+    }
+}
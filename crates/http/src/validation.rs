@@ -0,0 +1,265 @@
+use crate::{
+    auth::Access,
+    error::{Error, Result},
+    ServerState,
+};
+use annapurna_data::{taxonomy, types::Quantity, Facts};
+use annapurna_models::recipe::RecipeIngredient;
+use axum::{
+    extract::{FromRef, FromRequest, FromRequestParts, Request},
+    Json,
+};
+use lockpad_auth::PublicKey;
+use serde::de::DeserializeOwned;
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx_ulid::Ulid;
+use std::{collections::HashSet, time::Duration};
+
+/// The longest duration accepted, which keeps durations well within what can be stored and scheduled.
+pub(crate) const MAX_DURATION: Duration = Duration::from_secs(366 * 24 * 60 * 60);
+
+/// A problem with a single field of a request.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FieldError {
+    /// The path to the field, such as `ingredients[1].quantity`.
+    pub field: String,
+    pub message: String,
+}
+
+/// The ingredients requests may refer to when unknown ingredients are rejected:
+/// everything in the facts and every ingredient of the recipes the household can see.
+pub(crate) struct Catalog {
+    names: HashSet<String>,
+}
+
+impl Catalog {
+    pub(crate) async fn load(
+        pg_pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        facts: &Facts,
+        household_id: &Ulid,
+    ) -> Result<Self> {
+        let mut names = RecipeIngredient::types(pg_pool, household_id)
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        names.extend(facts.ingredient_names().into_iter().map(str::to_string));
+
+        Ok(Catalog { names })
+    }
+
+    /// Whether the ingredient is known, or is a kind of a known ingredient.
+    pub(crate) fn contains(&self, ingredient: &str) -> bool {
+        self.names.contains(ingredient)
+            || taxonomy::ancestors(ingredient).any(|category| self.names.contains(category))
+    }
+}
+
+/// Collects the problems with a request, so that they can all be reported at once.
+pub(crate) struct Validator<'a> {
+    errors: Vec<FieldError>,
+    prefix: String,
+    catalog: Option<&'a Catalog>,
+}
+
+impl<'a> Validator<'a> {
+    /// Creates a validator, which also checks ingredients are known when given a catalog.
+    pub(crate) fn new(catalog: Option<&'a Catalog>) -> Self {
+        Validator {
+            errors: Vec::new(),
+            prefix: String::new(),
+            catalog,
+        }
+    }
+
+    pub(crate) fn check(&mut self, field: &str, valid: bool, message: &str) -> &mut Self {
+        if !valid {
+            self.errors.push(FieldError {
+                field: format!("{}{field}", self.prefix),
+                message: message.to_string(),
+            });
+        }
+        self
+    }
+
+    pub(crate) fn not_blank(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(field, !value.trim().is_empty(), "must not be blank")
+    }
+
+    /// Checks the value is a quantity that can be compared with others, such as `2 cup`.
+    pub(crate) fn quantity(&mut self, field: &str, value: &str) -> &mut Self {
+        match value.parse::<Quantity>() {
+            Ok(_) => self,
+            Err(error) => self.check(field, false, &error.to_string()),
+        }
+    }
+
+    pub(crate) fn not_in_past(&mut self, field: &str, value: Option<DateTime<Utc>>) -> &mut Self {
+        let valid = value.map_or(true, |value| value >= Utc::now());
+        self.check(field, valid, "must not be in the past")
+    }
+
+    pub(crate) fn duration(&mut self, field: &str, value: Option<Duration>) -> &mut Self {
+        let valid = value.map_or(true, |value| value <= MAX_DURATION);
+        self.check(field, valid, "must be at most a year")
+    }
+
+    /// Checks the ingredient is blank-free and, when unknown ingredients are rejected, known.
+    pub(crate) fn ingredient(&mut self, field: &str, value: &str) -> &mut Self {
+        self.not_blank(field, value);
+        if let Some(catalog) = self.catalog {
+            if !value.trim().is_empty() && !catalog.contains(value) {
+                self.check(field, false, "must be a known ingredient");
+            }
+        }
+        self
+    }
+
+    /// Validates a nested value, reporting its fields beneath the given one.
+    pub(crate) fn nested(&mut self, field: &str, value: &impl Validate) -> &mut Self {
+        let nested = format!("{}{field}", self.prefix);
+        let prefix = std::mem::replace(&mut self.prefix, nested);
+        value.validate(self);
+        self.prefix = prefix;
+        self
+    }
+
+    pub(crate) fn finish(self) -> Result<()> {
+        match self.errors.is_empty() {
+            true => Ok(()),
+            false => Err(Error::Validation(self.errors)),
+        }
+    }
+}
+
+/// A request payload with checks to run before it is handled.
+pub(crate) trait Validate {
+    /// Whether the payload refers to ingredients, so the catalog is needed to check them.
+    const CHECKS_INGREDIENTS: bool = false;
+
+    fn validate(&self, validator: &mut Validator);
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    const CHECKS_INGREDIENTS: bool = T::CHECKS_INGREDIENTS;
+
+    fn validate(&self, validator: &mut Validator) {
+        for (index, item) in self.iter().enumerate() {
+            validator.nested(&format!("[{index}]."), item);
+        }
+    }
+}
+
+/// A JSON body that has passed validation, reporting every problem with it as a field error.
+/// Ingredients are checked against the household's [`Catalog`] when the server requires
+/// known ingredients.
+pub(crate) struct Valid<T>(pub T);
+
+#[axum::async_trait]
+impl<S, T> FromRequest<S> for Valid<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+    ServerState: FromRef<S>,
+    PublicKey: FromRef<S>,
+{
+    type Rejection = Error;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self> {
+        let ServerState {
+            pg_pool,
+            facts,
+            require_known_ingredients,
+            ..
+        } = ServerState::from_ref(state);
+
+        let (mut parts, body) = request.into_parts();
+        let catalog = match require_known_ingredients && T::CHECKS_INGREDIENTS {
+            true => {
+                let access = Access::from_request_parts(&mut parts, state).await?;
+                Some(Catalog::load(&pg_pool, &facts, &access.household_id).await?)
+            }
+            false => None,
+        };
+
+        let Json(payload) =
+            Json::<T>::from_request(Request::from_parts(parts, body), state).await?;
+
+        let mut validator = Validator::new(catalog.as_ref());
+        payload.validate(&mut validator);
+        validator.finish()?;
+
+        Ok(Valid(payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Item {
+        name: String,
+        quantity: String,
+    }
+
+    impl Validate for Item {
+        const CHECKS_INGREDIENTS: bool = true;
+
+        fn validate(&self, validator: &mut Validator) {
+            validator
+                .ingredient("name", &self.name)
+                .quantity("quantity", &self.quantity);
+        }
+    }
+
+    #[test]
+    fn reports_every_field_error() {
+        let items = vec![
+            Item {
+                name: "oil/olive".to_string(),
+                quantity: "1 cup".to_string(),
+            },
+            Item {
+                name: " ".to_string(),
+                quantity: "lots".to_string(),
+            },
+        ];
+
+        let mut validator = Validator::new(None);
+        validator.nested("items", &items);
+        let fields = match validator.finish() {
+            Err(Error::Validation(errors)) => errors
+                .into_iter()
+                .map(|error| error.field)
+                .collect::<Vec<_>>(),
+            _ => panic!("expected validation errors"),
+        };
+
+        assert_eq!(fields, vec!["items[1].name", "items[1].quantity"]);
+    }
+
+    #[test]
+    fn requires_known_ingredients_from_the_catalog() {
+        let catalog = Catalog {
+            names: HashSet::from(["oil".to_string(), "egg".to_string()]),
+        };
+        assert!(catalog.contains("oil/olive"));
+        assert!(!catalog.contains("eggplant"));
+
+        let mut validator = Validator::new(Some(&catalog));
+        validator.ingredient("a", "egg").ingredient("b", "eggplant");
+        assert!(matches!(validator.finish(), Err(Error::Validation(errors)) if errors.len() == 1));
+    }
+
+    #[test]
+    fn bounds_durations() {
+        let mut validator = Validator::new(None);
+        validator
+            .duration("a", Some(MAX_DURATION))
+            .duration("b", None)
+            .duration("c", Some(Duration::from_secs(u64::MAX)));
+        match validator.finish() {
+            Err(Error::Validation(errors)) => assert_eq!(errors[0].field, "c"),
+            _ => panic!("expected validation errors"),
+        }
+    }
+}
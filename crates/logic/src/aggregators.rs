@@ -1,18 +1,63 @@
-/// computes the missing ingredients for each recipe and returns the ingredients needed to make it
-pub fn vec_missing<'a, TInputIter>(
-    ingredients: Vec<String>,
-) -> impl Fn(TInputIter) -> std::vec::IntoIter<Vec<String>>
+use annapurna_data::{types::Quantity, units::Density};
+
+/// computes how much of an ingredient is still needed given the stock of it that is available.
+///
+/// Yields nothing when the stock covers the requirement, otherwise yields the outstanding amount
+/// in the unit the requirement was given in. Stock without an amount is assumed to be sufficient,
+/// and stock that cannot be converted to the requirement's unit is not counted towards it.
+pub fn quantity_shortfall<'a, TInputIter>(
+    needed: Option<Quantity>,
+    density: Option<Density>,
+) -> impl Fn(TInputIter) -> std::option::IntoIter<Option<Quantity>>
 where
-    TInputIter: Iterator<Item = (&'a String,)>,
+    TInputIter: Iterator<Item = (&'a Option<Quantity>,)>,
 {
     move |available| {
-        let ingredients = ingredients.clone();
-        let available: Vec<String> = available.map(|(i,)| i.to_string()).collect();
-        let missing = ingredients
+        let available: Vec<&Option<Quantity>> = available.map(|(q,)| q).collect();
+        if available.is_empty() {
+            return Some(needed.clone()).into_iter();
+        }
+
+        let needed = match &needed {
+            Some(needed) => needed,
+            None => return None.into_iter(),
+        };
+        if available.iter().any(|q| q.is_none()) {
+            return None.into_iter();
+        }
+
+        let have: f64 = available
             .into_iter()
-            .filter(|ingredient| !available.contains(ingredient))
-            .collect::<Vec<String>>();
+            .flatten()
+            .filter_map(|q| q.convert(&needed.unit, density).ok())
+            .map(|q| q.value)
+            .sum();
 
-        vec![missing].into_iter()
+        if have >= needed.value {
+            None.into_iter()
+        } else {
+            let outstanding = Quantity::new(needed.value - have, needed.unit.clone());
+            Some(Some(outstanding)).into_iter()
+        }
     }
 }
+
+/// computes the smallest outstanding amount among the candidates for a requirement.
+/// Candidates without an outstanding amount are only chosen when no amounts are known.
+pub fn least_outstanding<'a>(
+    outstanding: impl Iterator<Item = (&'a Option<Quantity>,)>,
+) -> impl Iterator<Item = Option<Quantity>> {
+    let mut least: Option<Option<Quantity>> = None;
+    for (candidate,) in outstanding {
+        least = match (least, candidate) {
+            (None, candidate) => Some(candidate.clone()),
+            (Some(Some(current)), Some(candidate)) if candidate.value < current.value => {
+                Some(Some(candidate.clone()))
+            }
+            (Some(None), Some(candidate)) => Some(Some(candidate.clone())),
+            (current, _) => current,
+        };
+    }
+
+    least.into_iter()
+}
@@ -0,0 +1,125 @@
+use crate::{
+    error::Result,
+    recipe::{RecipeManager, Shortfall},
+};
+use annapurna_data::{taxonomy, types::Ingredient, units::Density};
+use std::collections::HashMap;
+
+/// The precision remaining amounts are kept to, as a power of ten.
+const PRECISION: f64 = 1e6;
+
+/// Works out what is left of the stock once the given servings of a recipe have been cooked.
+/// The stock should be ordered by which items should be used first, such as those expiring soonest.
+pub fn cook(
+    manager: &RecipeManager,
+    recipe: &str,
+    servings: u32,
+    stock: &[Ingredient],
+) -> Result<Vec<Option<Ingredient>>> {
+    let recipe = manager.scaled(manager.recipe(recipe)?, servings);
+
+    Ok(consume(stock, &recipe.ingredients, &manager.densities))
+}
+
+/// Works out what the stock is short of to cook the given servings of a recipe.
+/// Only what [`cook`] would use up counts, so ingredients that could be made from the stock
+/// are short until they are in it.
+pub fn shortfalls(
+    manager: &RecipeManager,
+    recipe: &str,
+    servings: u32,
+    stock: &[Ingredient],
+) -> Result<Vec<Shortfall>> {
+    let recipe = manager.scaled(manager.recipe(recipe)?, servings);
+    let (_, mut shortfalls) = use_stock(stock, &recipe.ingredients, &manager.densities);
+    shortfalls.sort_by(|a, b| a.ingredient.cmp(&b.ingredient));
+
+    Ok(shortfalls)
+}
+
+/// Works out what is left of the stock once the requirements have been used.
+///
+/// Stock is used in the order it is given, preferring items that exactly match a requirement over
+/// those that are only within its category. Items without an amount are never used up, as how much
+/// of them there is isn't known, and requirements without an amount use nothing.
+/// The result has an entry for every stock item, which is `None` once the item has been used up.
+pub fn consume(
+    stock: &[Ingredient],
+    requirements: &[Ingredient],
+    densities: &HashMap<String, Density>,
+) -> Vec<Option<Ingredient>> {
+    use_stock(stock, requirements, densities).0
+}
+
+/// Uses the stock as [`consume`] does, also reporting what of the requirements it could not cover.
+/// A requirement without an amount is only short when nothing in the stock matches it.
+fn use_stock(
+    stock: &[Ingredient],
+    requirements: &[Ingredient],
+    densities: &HashMap<String, Density>,
+) -> (Vec<Option<Ingredient>>, Vec<Shortfall>) {
+    let mut remaining = stock.iter().cloned().map(Some).collect::<Vec<_>>();
+    let mut shortfalls = Vec::new();
+
+    for requirement in requirements {
+        let exact = (0..stock.len()).filter(|&index| stock[index].name == requirement.name);
+        let within = (0..stock.len()).filter(|&index| {
+            stock[index].name != requirement.name
+                && taxonomy::is_within(&stock[index].name, &requirement.name)
+        });
+        let matching = exact
+            .chain(within)
+            .filter(|&index| remaining[index].is_some())
+            .collect::<Vec<_>>();
+
+        let Some(mut needed) = requirement.amount.clone() else {
+            if matching.is_empty() {
+                shortfalls.push(Shortfall {
+                    ingredient: requirement.name.clone(),
+                    amount: None,
+                });
+            }
+            continue;
+        };
+
+        let mut covered = false;
+        for index in matching {
+            let Some(item) = &mut remaining[index] else {
+                continue;
+            };
+            let Some(available) = &mut item.amount else {
+                // an unknown amount is assumed to be enough
+                covered = true;
+                break;
+            };
+
+            let density = densities.get(&item.name).copied();
+            let Ok(wanted) = needed.convert(&available.unit, density) else {
+                continue;
+            };
+
+            let used = wanted.value.min(available.value);
+            // rounded so that what is left doesn't pick up noise such as `0.30000000000000004 kg`
+            available.value = ((available.value - used) * PRECISION).round() / PRECISION;
+            if available.value <= 0.0 {
+                remaining[index] = None;
+            }
+
+            if used >= wanted.value {
+                covered = true;
+                break;
+            }
+            needed = needed.scale(1.0 - used / wanted.value);
+        }
+
+        if !covered {
+            needed.value = (needed.value * PRECISION).round() / PRECISION;
+            shortfalls.push(Shortfall {
+                ingredient: requirement.name.clone(),
+                amount: Some(needed),
+            });
+        }
+    }
+
+    (remaining, shortfalls)
+}
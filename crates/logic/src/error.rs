@@ -2,6 +2,14 @@
 pub enum Error {
     #[error("todo")]
     Todo,
+    #[error("unknown recipe: {0}")]
+    UnknownRecipe(String),
+    #[error("step {step} of {recipe} waits on a step that does not exist")]
+    UnknownStep { recipe: String, step: usize },
+    #[error("step {step} of {recipe} takes too long to schedule")]
+    StepTooLong { recipe: String, step: usize },
+    #[error("the steps of {0} wait on each other")]
+    CyclicSteps(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,94 @@
+use crate::{consumption::consume, program::AscentProgram, recipe::RecipeManager};
+use annapurna_data::{taxonomy, types::Ingredient};
+use serde::Serialize;
+
+/// A recipe that would use up some of the stock that is about to expire.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub recipe: String,
+    /// The expiring ingredients the recipe uses.
+    pub uses: Vec<String>,
+    /// The ingredients that would need to be bought first.
+    pub missing: Vec<String>,
+    /// How much of the expiring stock the recipe uses up, as a sum of the share of each item used.
+    pub score: f64,
+}
+
+/// Ranks the recipes that can be made, or are missing at most `max_missing` ingredients,
+/// by how much of the expiring stock they would use up.
+///
+/// The expiring stock should also be part of the manager's available ingredients. Where either the
+/// stock or the recipe doesn't give an amount, the item is counted as wholly used, and recipes that
+/// use none of the expiring stock are left out.
+pub fn use_it_up(
+    manager: &RecipeManager,
+    expiring: &[Ingredient],
+    max_missing: usize,
+) -> Vec<Suggestion> {
+    let result = manager.process();
+
+    let candidates =
+        manager
+            .recipes
+            .iter()
+            .filter_map(|recipe| match result.missing.get(&recipe.name) {
+                Some(missing) if missing.len() > max_missing => None,
+                Some(missing) => Some((recipe, missing.clone())),
+                None if result.can_make.contains(&recipe.name) => Some((recipe, Vec::new())),
+                None => None,
+            });
+
+    let mut suggestions = candidates
+        .filter_map(|(recipe, mut missing)| {
+            let remaining = consume(expiring, &recipe.ingredients, &manager.densities);
+
+            let mut uses = Vec::new();
+            let mut score = 0.0;
+            for (item, after) in expiring.iter().zip(remaining) {
+                let Some(requirement) = recipe
+                    .ingredients
+                    .iter()
+                    .find(|requirement| taxonomy::is_within(&item.name, &requirement.name))
+                else {
+                    continue;
+                };
+
+                let used = match (&item.amount, &requirement.amount) {
+                    (Some(before), Some(_)) if before.value > 0.0 => {
+                        let left = after
+                            .and_then(|after| after.amount)
+                            .map_or(0.0, |a| a.value);
+                        (before.value - left) / before.value
+                    }
+                    _ => 1.0,
+                };
+                if used > 0.0 {
+                    uses.push(item.name.clone());
+                    score += used;
+                }
+            }
+
+            if uses.is_empty() {
+                return None;
+            }
+            uses.sort();
+            uses.dedup();
+            missing.sort();
+
+            Some(Suggestion {
+                recipe: recipe.name.clone(),
+                uses,
+                missing,
+                score,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    suggestions.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.missing.len().cmp(&b.missing.len()))
+            .then(a.recipe.cmp(&b.recipe))
+    });
+    suggestions
+}
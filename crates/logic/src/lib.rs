@@ -1,9 +1,17 @@
 use annapurna_data::types::{Ingredient, Recipe};
 
 mod aggregators;
+pub mod consumption;
 pub mod error;
+pub mod expiration;
+pub mod nutrition;
+pub mod planning;
 pub mod program;
+pub mod ranking;
 pub mod recipe;
+pub mod routine;
+pub mod scheduling;
+pub mod shopping;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,73 @@
+use annapurna_data::{
+    nutrition::{Nutrients, Nutrition},
+    taxonomy,
+    types::{Ingredient, Recipe},
+    units::{Density, Dimension},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The nutrition of a serving of a recipe, worked out from the amounts of its ingredients.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RecipeNutrition {
+    pub recipe: String,
+    pub servings: u32,
+    pub per_serving: Nutrients,
+    /// The ingredients left out, because their amount or nutrition is unknown
+    /// or their amount cannot be weighed.
+    pub unknown: Vec<String>,
+}
+
+/// Works out the nutrition of each of the `servings` a recipe makes.
+///
+/// Ingredients use the nutrition of the nearest category that has one, such as `onion` for
+/// `onion/green`. Volumes are weighed using the density of the ingredient, and counted amounts
+/// using the weight of a piece.
+pub fn recipe_nutrition(
+    recipe: &Recipe,
+    servings: u32,
+    nutrition: &HashMap<String, Nutrition>,
+    densities: &HashMap<String, Density>,
+) -> RecipeNutrition {
+    let mut total = Nutrients::default();
+    let mut unknown = Vec::new();
+    for ingredient in &recipe.ingredients {
+        match nutrition_of(ingredient, nutrition, densities) {
+            Some(nutrients) => total += nutrients,
+            None => unknown.push(ingredient.name.clone()),
+        }
+    }
+
+    RecipeNutrition {
+        recipe: recipe.name.clone(),
+        servings,
+        per_serving: total.scale(1.0 / f64::from(servings.max(1))),
+        unknown,
+    }
+}
+
+fn nutrition_of(
+    ingredient: &Ingredient,
+    nutrition: &HashMap<String, Nutrition>,
+    densities: &HashMap<String, Density>,
+) -> Option<Nutrients> {
+    let known = nearest(&ingredient.name, nutrition)?;
+    let amount = ingredient.amount.as_ref()?;
+
+    let grams = match amount.dimension().ok()? {
+        Dimension::Mass | Dimension::Volume => {
+            let density = nearest(&ingredient.name, densities).copied();
+            amount.convert("g", density).ok()?.value
+        }
+        Dimension::Count => amount.normalize().ok()?.value * known.piece_weight?,
+    };
+
+    Some(known.per_100g.scale(grams / 100.0))
+}
+
+/// The entry for the ingredient, or else for the closest category it is within.
+fn nearest<'a, T>(ingredient: &str, entries: &'a HashMap<String, T>) -> Option<&'a T> {
+    std::iter::once(ingredient)
+        .chain(taxonomy::ancestors(ingredient))
+        .find_map(|name| entries.get(name))
+}
@@ -0,0 +1,67 @@
+use crate::{
+    consumption::consume,
+    error::Result,
+    recipe::{RecipeManager, Shortfall},
+    shopping::{combine_requirements, shortfalls},
+};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A recipe that is planned to be made on a day.
+#[derive(Clone, Debug)]
+pub struct PlannedMeal {
+    pub date: NaiveDate,
+    pub recipe: String,
+    pub servings: u32,
+}
+
+/// Whether the meals planned for a day can be made from the inventory projected for that day.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DayCheck {
+    pub date: NaiveDate,
+    pub recipes: Vec<String>,
+    /// Everything the day's meals need that will not be available.
+    pub shortfalls: Vec<Shortfall>,
+}
+
+impl DayCheck {
+    pub fn is_feasible(&self) -> bool {
+        self.shortfalls.is_empty()
+    }
+}
+
+/// Checks each planned day against the inventory that will be left by the days before it.
+///
+/// Every day uses up the stock its meals need, even when some of them cannot be made, so that
+/// later days do not count on ingredients that will already be gone. Only ingredients taken from
+/// stock are projected, so the ingredients of intermediate recipes are not used up.
+pub fn check_plan(manager: &RecipeManager, meals: &[PlannedMeal]) -> Result<Vec<DayCheck>> {
+    let mut days: BTreeMap<NaiveDate, Vec<&PlannedMeal>> = BTreeMap::new();
+    for meal in meals {
+        days.entry(meal.date).or_default().push(meal);
+    }
+
+    let mut stock = manager.available_ingredients.clone();
+    let mut checks = Vec::new();
+    for (date, meals) in days {
+        let recipes = meals
+            .iter()
+            .map(|meal| Ok(manager.scaled(manager.recipe(&meal.recipe)?, meal.servings)))
+            .collect::<Result<Vec<_>>>()?;
+        let requirements = combine_requirements(&recipes.iter().collect::<Vec<_>>());
+
+        checks.push(DayCheck {
+            date,
+            recipes: meals.iter().map(|meal| meal.recipe.clone()).collect(),
+            shortfalls: shortfalls(manager, stock.clone(), requirements.clone()),
+        });
+
+        stock = consume(&stock, &requirements, &manager.densities)
+            .into_iter()
+            .flatten()
+            .collect();
+    }
+
+    Ok(checks)
+}
@@ -0,0 +1,132 @@
+use crate::recipe::RecipeResult;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+type Ingredient = String;
+type Recipe = String;
+
+/// The cost of buying an ingredient that has no known price.
+/// With no prices at all, recipes are ranked by the number of ingredients they are missing.
+pub const DEFAULT_COST: f64 = 1.0;
+
+/// A recipe that cannot be made yet, and what it would take to make it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RankedRecipe {
+    pub recipe: Recipe,
+    pub missing: Vec<Ingredient>,
+    pub cost: f64,
+}
+
+/// Ingredients to buy, and the recipes that can be made once they have been bought.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ShoppingStep {
+    pub buy: Vec<Ingredient>,
+    pub unlocks: Vec<Recipe>,
+}
+
+fn cost<'a>(
+    ingredients: impl IntoIterator<Item = &'a Ingredient>,
+    prices: &HashMap<Ingredient, f64>,
+) -> f64 {
+    ingredients
+        .into_iter()
+        .map(|ingredient| prices.get(ingredient).copied().unwrap_or(DEFAULT_COST))
+        .sum()
+}
+
+/// Orders the recipes that cannot be made by how much it would cost to make them, cheapest first.
+pub fn rank(result: &RecipeResult, prices: &HashMap<Ingredient, f64>) -> Vec<RankedRecipe> {
+    let mut ranked = result
+        .missing
+        .iter()
+        .map(|(recipe, missing)| {
+            let mut missing = missing.clone();
+            missing.sort();
+            RankedRecipe {
+                recipe: recipe.clone(),
+                cost: cost(&missing, prices),
+                missing,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(|a, b| {
+        a.cost
+            .total_cmp(&b.cost)
+            .then(a.missing.len().cmp(&b.missing.len()))
+            .then(a.recipe.cmp(&b.recipe))
+    });
+    ranked
+}
+
+/// Chooses ingredients to buy that unlock the most recipes for the least cost.
+///
+/// This is a greedy set cover: each step buys whatever unlocks the most recipes per unit of cost,
+/// given what has already been bought. `max_items` limits the total number of ingredients bought.
+pub fn shopping_suggestions(
+    result: &RecipeResult,
+    prices: &HashMap<Ingredient, f64>,
+    max_items: Option<usize>,
+) -> Vec<ShoppingStep> {
+    let mut remaining = result
+        .missing
+        .iter()
+        .map(|(recipe, missing)| {
+            (
+                recipe.clone(),
+                missing.iter().cloned().collect::<BTreeSet<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    remaining.sort();
+
+    let mut bought = BTreeSet::new();
+    let mut steps = Vec::new();
+
+    loop {
+        let best = remaining
+            .iter()
+            .filter_map(|(_, missing)| {
+                let buy = missing
+                    .difference(&bought)
+                    .cloned()
+                    .collect::<BTreeSet<_>>();
+                if let Some(max_items) = max_items {
+                    if bought.len() + buy.len() > max_items {
+                        return None;
+                    }
+                }
+
+                let unlocks = remaining
+                    .iter()
+                    .filter(|(_, other)| {
+                        other.iter().all(|i| bought.contains(i) || buy.contains(i))
+                    })
+                    .map(|(recipe, _)| recipe.clone())
+                    .collect::<Vec<_>>();
+                let score = unlocks.len() as f64 / cost(&buy, prices);
+                Some((score, buy, unlocks))
+            })
+            // the first of the best candidates is kept, so ties are broken by recipe name
+            .fold(
+                None,
+                |best: Option<(f64, BTreeSet<Ingredient>, Vec<Recipe>)>, candidate| match best {
+                    Some(best) if best.0 >= candidate.0 => Some(best),
+                    _ => Some(candidate),
+                },
+            );
+
+        let Some((_, buy, unlocks)) = best else {
+            break;
+        };
+
+        bought.extend(buy.iter().cloned());
+        remaining.retain(|(recipe, _)| !unlocks.contains(recipe));
+        steps.push(ShoppingStep {
+            buy: buy.into_iter().collect(),
+            unlocks,
+        });
+    }
+
+    steps
+}
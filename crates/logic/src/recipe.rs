@@ -1,46 +1,206 @@
-#![allow(clippy::clone_on_copy)]
-#![allow(clippy::collapsible_if)]
-#![allow(clippy::let_unit_value)]
-#![allow(clippy::just_underscores_and_digits)]
-#![allow(clippy::unused_enumerate_index)]
-#![allow(clippy::unused_unit)]
-
-use annapurna_data::types;
-use ascent::ascent;
-use std::collections::HashMap;
-
-use crate::aggregators::vec_missing;
+use annapurna_data::{
+    dietary::{DietaryProfile, Tag},
+    types::{self, Quantity},
+    units::Density,
+    Facts,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Error, Result};
 use crate::program::AscentProgram;
 
 type Ingredient = String;
 type Recipe = String;
 
-ascent! {
-    pub (crate) struct RecipeProgram;
-    relation recipe_ingredients(Recipe, Vec<Ingredient>);
-    relation has(Ingredient);
-
-    relation is_recipe(Recipe);
-    is_recipe(x) <-- recipe_ingredients(x, _);
-
-    relation missing(Recipe, Vec<Ingredient>);
-    missing(recipe, contents) <-- recipe_ingredients(recipe, contents), for ingredient in contents.iter(), !has(ingredient);
-    // the above implementation of `missing` does not filter out the ingredients that are present
-    // the below implementation of `missing` does filter out the ingredients that are present
-    relation missing_ingredients(Recipe, Vec<Ingredient>);
-    missing_ingredients(recipe, missing) <--
-        recipe_ingredients(recipe, contents),
-        for ingredient in contents.iter(),
-        !has(ingredient),
-        agg missing = (vec_missing(contents.to_vec()))(x) in has(x);
-
-    relation can_make(Recipe);
-    can_make(recipe) <-- is_recipe(recipe), !missing(recipe, _);
+// the program is kept in its own module so that the lints its generated code trips
+// are only allowed there
+mod rules {
+    #![allow(clippy::clone_on_copy)]
+    #![allow(clippy::collapsible_if)]
+    #![allow(clippy::let_unit_value)]
+    #![allow(clippy::just_underscores_and_digits)]
+    #![allow(clippy::unused_enumerate_index)]
+    #![allow(clippy::unused_unit)]
+    #![allow(unused_variables)]
+
+    use super::{Ingredient, Recipe};
+    use crate::aggregators::{least_outstanding, quantity_shortfall};
+    use annapurna_data::{dietary::Tag, taxonomy, types::Quantity, units::Density};
+    use ascent::ascent;
+
+    ascent! {
+        pub (crate) struct RecipeProgram;
+        relation is_recipe(Recipe);
+        relation requires(Recipe, Ingredient, Option<Quantity>);
+        // the total amount of each ingredient on hand, per unit. `None` when the amount is unknown
+        relation stock(Ingredient, Option<Quantity>);
+        // the density of every known ingredient, if one has been given
+        relation density(Ingredient, Option<Density>);
+
+        // the position of each distinct ingredient in a recipe, and how many there are
+        relation requirement(Recipe, usize, Ingredient);
+        relation requirement_count(Recipe, usize);
+
+        // set to allow a requirement to be met by a more general ingredient, such as `onion` for `onion/green`
+        relation allow_approximate();
+
+        // the stock items that can be used for a recipe's ingredient, and whether the match is approximate.
+        // a requirement for a category, such as `oil`, accepts any kind of it, such as `oil/avocado`.
+        // a recipe never matches its own output, so `egg/yolk` is not made from `egg/yolk`
+        // ingredients that have their own recipe are made rather than approximated
+        relation matches(Recipe, Ingredient, Ingredient, bool);
+        matches(recipe, needed, item, false) <--
+            requires(recipe, needed, _),
+            stock(item, _),
+            if item != recipe && taxonomy::is_within(item, needed);
+        matches(recipe, needed, item, true) <--
+            allow_approximate(),
+            requires(recipe, needed, _),
+            !is_recipe(needed),
+            stock(item, _),
+            if item != recipe && needed != item && taxonomy::is_within(needed, item);
+
+        // the amount of an ingredient a recipe would still need if it were taken from a single stock item
+        relation candidate_shortfall(Recipe, Ingredient, Ingredient, Option<Quantity>);
+        candidate_shortfall(recipe, ingredient, item, outstanding) <--
+            requires(recipe, ingredient, needed),
+            matches(recipe, ingredient, item, _),
+            density(item, density),
+            agg outstanding = (quantity_shortfall(needed.clone(), *density))(available) in stock(item, available);
+
+        // the stock items that fully cover a recipe's requirement
+        relation has(Recipe, Ingredient, Ingredient, bool);
+        has(recipe, ingredient, item, approximate) <--
+            requires(recipe, ingredient, _),
+            matches(recipe, ingredient, item, approximate),
+            !candidate_shortfall(recipe, ingredient, item, _);
+
+        relation in_stock(Recipe, Ingredient);
+        in_stock(recipe, ingredient) <-- has(recipe, ingredient, _, _);
+
+        // the amount of an ingredient that a recipe still needs from the stock. `None` when the recipe
+        // does not specify an amount and the ingredient is not on hand at all
+        relation stock_shortfall(Recipe, Ingredient, Option<Quantity>);
+        stock_shortfall(recipe, ingredient, needed.clone()) <--
+            requires(recipe, ingredient, needed),
+            !matches(recipe, ingredient, _, _);
+        stock_shortfall(recipe, ingredient, outstanding) <--
+            requires(recipe, ingredient, _),
+            !in_stock(recipe, ingredient),
+            agg outstanding = least_outstanding(candidate) in candidate_shortfall(recipe, ingredient, _, candidate);
+
+        // an ingredient is satisfied when it is in stock, or when it is the output of another recipe that can be made
+        relation satisfied(Recipe, Ingredient);
+        satisfied(recipe, ingredient) <-- in_stock(recipe, ingredient);
+        satisfied(recipe, ingredient) <-- requires(recipe, ingredient, _), can_make(ingredient);
+
+        // recipes are makeable once each of their ingredients is satisfied, in order.
+        // this avoids negation so that `can_make` may depend on itself through intermediate recipes
+        relation satisfied_upto(Recipe, usize);
+        satisfied_upto(recipe, 0) <-- is_recipe(recipe);
+        satisfied_upto(recipe, n + 1) <--
+            satisfied_upto(recipe, n),
+            requirement(recipe, n, ingredient),
+            satisfied(recipe, ingredient);
+
+        relation can_make(Recipe);
+        can_make(recipe) <-- requirement_count(recipe, n), satisfied_upto(recipe, n);
+
+        // intermediate recipes that need to be prepared before a recipe can be made
+        relation intermediate(Recipe, Recipe);
+        intermediate(recipe, ingredient) <--
+            stock_shortfall(recipe, ingredient, _),
+            can_make(ingredient);
+
+        relation shortfall(Recipe, Ingredient, Option<Quantity>);
+        shortfall(recipe, ingredient, outstanding) <--
+            stock_shortfall(recipe, ingredient, outstanding),
+            !satisfied(recipe, ingredient);
+
+        relation missing(Recipe, Ingredient);
+        missing(recipe, ingredient) <-- shortfall(recipe, ingredient, _);
+
+        // substitutions for an ingredient, either by a single ingredient or by a set of them
+        relation substitute(Ingredient, Ingredient);
+        relation substitute_many(usize, Ingredient, Vec<Ingredient>);
+
+        // replacements are available when they are in stock or can be made
+        relation replacement(Ingredient);
+        replacement(ingredient) <-- substitute(_, ingredient);
+        replacement(ingredient.clone()) <--
+            substitute_many(_, _, replacements),
+            for ingredient in replacements.iter();
+
+        relation replacement_available(Ingredient);
+        replacement_available(ingredient) <--
+            replacement(ingredient),
+            stock(item, _),
+            if taxonomy::is_within(item, ingredient);
+        replacement_available(ingredient) <-- replacement(ingredient), can_make(ingredient);
+
+        relation substitute_many_unavailable(usize);
+        substitute_many_unavailable(id) <--
+            substitute_many(id, _, replacements),
+            for ingredient in replacements.iter(),
+            !replacement_available(ingredient);
+
+        // missing ingredients of a recipe that can be substituted, and what they are replaced with
+        relation substituted(Recipe, Ingredient, Vec<Ingredient>);
+        substituted(recipe, ingredient, vec![replacement.clone()]) <--
+            missing(recipe, ingredient),
+            substitute(ingredient, replacement),
+            replacement_available(replacement);
+        substituted(recipe, ingredient, replacements.clone()) <--
+            missing(recipe, ingredient),
+            substitute_many(id, ingredient, replacements),
+            !substitute_many_unavailable(id);
+
+        relation unsubstituted(Recipe, Ingredient);
+        unsubstituted(recipe, ingredient) <--
+            missing(recipe, ingredient),
+            !substituted(recipe, ingredient, _);
+
+        // recipes that can only be made by substituting some of their ingredients
+        relation can_make_with_substitutions(Recipe);
+        can_make_with_substitutions(recipe) <--
+            missing(recipe, _),
+            !unsubstituted(recipe, _);
+
+        // what each ingredient contains, and what the dietary profile rules out
+        relation tagged(Ingredient, Tag);
+        relation forbidden_tag(Tag);
+
+        // the ingredients a recipe uses, including those of the intermediate recipes it is made from
+        relation uses(Recipe, Ingredient);
+        uses(recipe, ingredient) <-- requires(recipe, ingredient, _);
+        uses(recipe, ingredient) <-- uses(recipe, intermediate), requires(intermediate, ingredient, _);
+
+        // the ingredients that rule a recipe out. a tag on a category applies to every kind of it,
+        // so `flour/wheat` contains gluten when `flour` does
+        relation forbidden(Recipe, Ingredient, Tag);
+        forbidden(recipe, ingredient, tag) <--
+            uses(recipe, ingredient),
+            tagged(category, tag),
+            forbidden_tag(tag),
+            if taxonomy::is_within(ingredient, category);
+
+        relation allowed(Recipe);
+        allowed(recipe) <-- is_recipe(recipe), !forbidden(recipe, _, _);
+    }
 }
 
+pub(crate) use rules::RecipeProgram;
+
 pub struct RecipeManager {
-    available_ingredients: Vec<types::Ingredient>,
-    recipes: Vec<types::Recipe>,
+    pub(crate) available_ingredients: Vec<types::Ingredient>,
+    pub(crate) recipes: Vec<types::Recipe>,
+    pub(crate) densities: HashMap<Ingredient, Density>,
+    pub(crate) substitutions: Vec<types::Substitution>,
+    pub(crate) approximate: bool,
+    pub(crate) servings: HashMap<Recipe, u32>,
+    pub(crate) tags: HashMap<Ingredient, Vec<Tag>>,
+    pub(crate) profile: DietaryProfile,
 }
 
 impl RecipeManager {
@@ -48,37 +208,220 @@ impl RecipeManager {
         Self {
             available_ingredients,
             recipes,
+            densities: HashMap::new(),
+            substitutions: Vec::new(),
+            approximate: false,
+            servings: HashMap::new(),
+            tags: HashMap::new(),
+            profile: DietaryProfile::default(),
+        }
+    }
+
+    /// Creates a manager for the recipes in the facts, using their densities, substitutions and tags.
+    /// The available ingredients are given separately, as they may not come from the facts.
+    pub fn from_facts(facts: &Facts, available_ingredients: Vec<types::Ingredient>) -> Self {
+        Self::new(available_ingredients, facts.recipes.clone())
+            .densities(facts.densities.clone())
+            .substitutions(facts.substitutions.clone())
+            .servings(facts.servings.clone())
+            .tags(facts.tags.clone())
+    }
+
+    /// Replaces the recipes, such as with those that have been stored elsewhere.
+    pub fn recipes(mut self, recipes: Vec<types::Recipe>) -> Self {
+        self.recipes = recipes;
+        self
+    }
+
+    /// Sets the densities used to convert ingredient quantities between mass and volume.
+    pub fn densities(mut self, densities: HashMap<Ingredient, Density>) -> Self {
+        self.densities = densities;
+        self
+    }
+
+    /// Sets the substitutions that may be used for missing ingredients.
+    pub fn substitutions(mut self, substitutions: Vec<types::Substitution>) -> Self {
+        self.substitutions = substitutions;
+        self
+    }
+
+    /// Sets the number of servings each recipe makes, used to scale recipes to a number of servings.
+    pub fn servings(mut self, servings: HashMap<Recipe, u32>) -> Self {
+        self.servings = servings;
+        self
+    }
+
+    /// Sets what each ingredient contains, used to rule out recipes that do not suit the profile.
+    pub fn tags(mut self, tags: HashMap<Ingredient, Vec<Tag>>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Rules out the recipes with ingredients the profile will not eat.
+    /// They are only listed in [`RecipeResult::forbidden`], and not as recipes to make or shop for.
+    pub fn profile(mut self, profile: DietaryProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Finds a known recipe by name.
+    pub(crate) fn recipe(&self, name: &str) -> Result<&types::Recipe> {
+        self.recipes
+            .iter()
+            .find(|recipe| recipe.name == name)
+            .ok_or_else(|| Error::UnknownRecipe(name.to_string()))
+    }
+
+    /// The number of servings the recipe makes.
+    pub fn servings_of(&self, recipe: &str) -> u32 {
+        self.servings.get(recipe).copied().unwrap_or(1).max(1)
+    }
+
+    /// Scales a recipe's ingredients to make the given number of servings.
+    pub(crate) fn scaled(&self, recipe: &types::Recipe, servings: u32) -> types::Recipe {
+        recipe.scaled(servings as f64 / self.servings_of(&recipe.name) as f64)
+    }
+
+    /// Allows ingredients to be met by a more general ingredient in stock, such as `onion` for
+    /// `onion/green`. These matches are flagged as approximate in the result.
+    pub fn approximate(mut self, approximate: bool) -> Self {
+        self.approximate = approximate;
+        self
+    }
+
+    /// Totals the available ingredients so that each ingredient has at most one stock entry per unit.
+    /// Quantities in known units are normalized first, so `1 kg` and `500 g` of flour are combined.
+    fn stock(&self) -> Vec<(Ingredient, Option<Quantity>)> {
+        let mut totals: HashMap<(Ingredient, Option<String>), Option<Quantity>> = HashMap::new();
+        for ingredient in &self.available_ingredients {
+            let amount = ingredient
+                .amount
+                .as_ref()
+                .map(|q| q.normalize().unwrap_or_else(|_| q.clone()));
+            let unit = amount.as_ref().map(|q| q.unit.clone());
+            let total = totals
+                .entry((ingredient.name.clone(), unit))
+                .or_insert_with(|| amount.as_ref().map(|q| Quantity::new(0.0, q.unit.clone())));
+            if let (Some(total), Some(amount)) = (total, &amount) {
+                total.value += amount.value;
+            }
         }
+
+        totals
+            .into_iter()
+            .map(|((name, _), amount)| (name, amount))
+            .collect()
     }
 
     /// Prepare the ascent program for running
     pub(crate) fn get_program(&self) -> RecipeProgram {
-        let has = self
-            .available_ingredients
+        let is_recipe = self
+            .recipes
             .iter()
-            .map(|i| (i.to_string(),))
+            .map(|recipe| (recipe.name.clone(),))
             .collect();
 
-        let recipe_ingredients = self
+        let requires = self
             .recipes
             .iter()
-            .map(|recipe| {
+            .flat_map(|recipe| {
+                recipe.ingredients.iter().map(|ingredient| {
+                    (
+                        recipe.name.clone(),
+                        ingredient.name.clone(),
+                        ingredient.amount.clone(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut requirement = Vec::new();
+        let mut requirement_count = Vec::new();
+        for recipe in &self.recipes {
+            let mut names = recipe
+                .ingredients
+                .iter()
+                .map(|ingredient| ingredient.name.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            names.dedup();
+
+            requirement_count.push((recipe.name.clone(), names.len()));
+            requirement.extend(
+                names
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, name)| (recipe.name.clone(), index, name)),
+            );
+        }
+
+        let stock = self.stock();
+
+        let mut density = requires
+            .iter()
+            .map(|(_, ingredient, _)| ingredient)
+            .chain(stock.iter().map(|(ingredient, _)| ingredient))
+            .map(|ingredient| (ingredient.clone(), self.densities.get(ingredient).copied()))
+            .collect::<Vec<_>>();
+        density.sort_by(|a, b| a.0.cmp(&b.0));
+        density.dedup_by(|a, b| a.0 == b.0);
+
+        // a substitution without replacements would count as always available, so it is ignored
+        let (substitute, substitute_many): (Vec<_>, Vec<_>) = self
+            .substitutions
+            .iter()
+            .filter(|substitution| !substitution.replacements.is_empty())
+            .partition(|substitution| substitution.replacements.len() == 1);
+        let substitute = substitute
+            .into_iter()
+            .map(|substitution| {
+                (
+                    substitution.ingredient.clone(),
+                    substitution.replacements[0].clone(),
+                )
+            })
+            .collect();
+        let substitute_many = substitute_many
+            .into_iter()
+            .enumerate()
+            .map(|(id, substitution)| {
                 (
-                    recipe.name.clone(),
-                    recipe
-                        .ingredients
-                        .iter()
-                        .map(|i| i.to_string())
-                        .collect::<Vec<String>>(),
+                    id,
+                    substitution.ingredient.clone(),
+                    substitution.replacements.clone(),
                 )
             })
             .collect();
 
-        RecipeProgram {
-            has,
-            recipe_ingredients,
-            ..Default::default()
-        }
+        let tagged = self
+            .tags
+            .iter()
+            .flat_map(|(ingredient, tags)| tags.iter().map(|tag| (ingredient.clone(), *tag)))
+            .collect();
+        let forbidden_tag = self
+            .profile
+            .forbidden_tags()
+            .into_iter()
+            .map(|tag| (tag,))
+            .collect();
+
+        // the generated program has private fields, so the relations are set one at a time
+        let mut program = RecipeProgram::default();
+        program.is_recipe = is_recipe;
+        program.requires = requires;
+        program.requirement = requirement;
+        program.requirement_count = requirement_count;
+        program.stock = stock;
+        program.density = density;
+        program.substitute = substitute;
+        program.substitute_many = substitute_many;
+        program.allow_approximate = match self.approximate {
+            true => vec![()],
+            false => vec![],
+        };
+        program.tagged = tagged;
+        program.forbidden_tag = forbidden_tag;
+        program
     }
 
     pub(crate) fn run(&self) -> RecipeProgram {
@@ -93,22 +436,210 @@ impl AscentProgram for RecipeManager {
 
     fn process(&self) -> Self::Output {
         let program = self.run();
-        RecipeResult {
+
+        let intermediates = program.intermediate.iter().fold(
+            HashMap::new(),
+            |mut acc: HashMap<&Recipe, Vec<&Recipe>>, (recipe, intermediate)| {
+                acc.entry(recipe).or_default().push(intermediate);
+                acc
+            },
+        );
+        let production_chains = program
+            .can_make
+            .iter()
+            .filter_map(|(recipe,)| {
+                let mut chain = Vec::new();
+                production_chain(recipe, &intermediates, &mut chain);
+                chain.pop();
+                (!chain.is_empty()).then(|| (recipe.clone(), chain))
+            })
+            .collect();
+
+        // prefer exact matches, and otherwise the first item by name, so results are stable
+        let mut has = program.has.clone();
+        has.sort_by(|a, b| (&a.0, &a.1, a.3, &a.2).cmp(&(&b.0, &b.1, b.3, &b.2)));
+        has.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+        let matches = has.into_iter().fold(
+            HashMap::new(),
+            |mut acc: HashMap<Recipe, Vec<IngredientMatch>>,
+             (recipe, ingredient, item, approximate)| {
+                acc.entry(recipe).or_default().push(IngredientMatch {
+                    ingredient,
+                    item,
+                    approximate,
+                });
+                acc
+            },
+        );
+
+        // use the first substitution found for each ingredient
+        let mut substituted = program.substituted.clone();
+        substituted.sort();
+        substituted.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+        let substitutions = substituted
+            .into_iter()
+            .filter(|(recipe, _, _)| {
+                program
+                    .can_make_with_substitutions
+                    .contains(&(recipe.clone(),))
+            })
+            .fold(
+                HashMap::new(),
+                |mut acc: HashMap<Recipe, Vec<SubstitutionUse>>,
+                 (recipe, ingredient, replacements)| {
+                    acc.entry(recipe).or_default().push(SubstitutionUse {
+                        ingredient,
+                        replacements,
+                    });
+                    acc
+                },
+            );
+
+        let mut forbidden = program.forbidden.clone();
+        forbidden.sort();
+        forbidden.dedup();
+        let forbidden = forbidden.into_iter().fold(
+            HashMap::new(),
+            |mut acc: HashMap<Recipe, Vec<ForbiddenIngredient>>, (recipe, ingredient, tag)| {
+                acc.entry(recipe)
+                    .or_default()
+                    .push(ForbiddenIngredient { ingredient, tag });
+                acc
+            },
+        );
+        let mut allowed = program
+            .allowed
+            .iter()
+            .map(|(recipe,)| recipe.clone())
+            .collect::<Vec<_>>();
+        allowed.sort();
+
+        let mut result = RecipeResult {
+            allowed,
+            forbidden,
             can_make: program.can_make.into_iter().map(|(r,)| r).collect(),
-            missing: program.missing_ingredients.into_iter().fold(
+            matches,
+            substitutions,
+            production_chains,
+            missing: program.missing.into_iter().fold(
+                HashMap::new(),
+                |mut acc: HashMap<Recipe, Vec<Ingredient>>, (recipe, ingredient)| {
+                    acc.entry(recipe).or_default().push(ingredient);
+                    acc
+                },
+            ),
+            shortfalls: program.shortfall.into_iter().fold(
                 HashMap::new(),
-                |mut acc, (recipe, missing)| {
-                    acc.insert(recipe, missing);
+                |mut acc: HashMap<Recipe, Vec<Shortfall>>, (recipe, ingredient, amount)| {
+                    acc.entry(recipe)
+                        .or_default()
+                        .push(Shortfall { ingredient, amount });
                     acc
                 },
             ),
+        };
+
+        // recipes that are ruled out are left out of everything else, so they are never suggested
+        if !result.forbidden.is_empty() {
+            let forbidden = result.forbidden.keys().cloned().collect::<HashSet<_>>();
+            result.can_make.retain(|recipe| !forbidden.contains(recipe));
+            result
+                .matches
+                .retain(|recipe, _| !forbidden.contains(recipe));
+            result
+                .substitutions
+                .retain(|recipe, _| !forbidden.contains(recipe));
+            result
+                .production_chains
+                .retain(|recipe, _| !forbidden.contains(recipe));
+            result
+                .missing
+                .retain(|recipe, _| !forbidden.contains(recipe));
+            result
+                .shortfalls
+                .retain(|recipe, _| !forbidden.contains(recipe));
         }
+        result
     }
 }
 
+/// Orders the intermediates needed to make a recipe so that each comes after the intermediates it
+/// depends on. The recipe itself is placed last.
+fn production_chain(
+    recipe: &Recipe,
+    intermediates: &HashMap<&Recipe, Vec<&Recipe>>,
+    chain: &mut Vec<Recipe>,
+) {
+    if chain.contains(recipe) {
+        return;
+    }
+
+    let mut dependencies = intermediates.get(recipe).cloned().unwrap_or_default();
+    dependencies.sort();
+    for dependency in dependencies {
+        production_chain(dependency, intermediates, chain);
+    }
+    chain.push(recipe.clone());
+}
+
 #[derive(Clone, Debug)]
 pub struct RecipeResult {
+    /// The recipes that suit the dietary profile, by name. Without a profile, every recipe is allowed.
+    pub allowed: Vec<Recipe>,
+    /// The recipes the dietary profile rules out, with the ingredients that rule them out.
+    /// These recipes are left out of every other field.
+    pub forbidden: HashMap<Recipe, Vec<ForbiddenIngredient>>,
     pub can_make: Vec<Recipe>,
-    // pub missing: Vec<(Recipe, Vec<Ingredient>)>,
+    /// The stock item used for each of a recipe's ingredients that is in stock.
+    pub matches: HashMap<Recipe, Vec<IngredientMatch>>,
+    /// Recipes that can only be made by substituting ingredients, with the substitutions to use.
+    pub substitutions: HashMap<Recipe, Vec<SubstitutionUse>>,
+    /// The intermediate recipes to prepare, in order, before a recipe can be made.
+    /// Only recipes that depend on intermediates are included.
+    pub production_chains: HashMap<Recipe, Vec<Recipe>>,
     pub missing: HashMap<Recipe, Vec<Ingredient>>,
+    /// How much of each missing ingredient is still needed, per recipe.
+    pub shortfalls: HashMap<Recipe, Vec<Shortfall>>,
+}
+
+impl RecipeResult {
+    /// Whether a recipe relies on any approximate ingredient matches.
+    pub fn is_approximate(&self, recipe: &str) -> bool {
+        self.matches
+            .get(recipe)
+            .map(|matches| matches.iter().any(|m| m.approximate))
+            .unwrap_or(false)
+    }
+}
+
+/// The stock item that satisfies one of a recipe's ingredients.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IngredientMatch {
+    pub ingredient: Ingredient,
+    pub item: Ingredient,
+    /// Whether the item is a more general ingredient than the one that was asked for.
+    pub approximate: bool,
+}
+
+/// A missing ingredient of a recipe and the ingredients to use instead of it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubstitutionUse {
+    pub ingredient: Ingredient,
+    pub replacements: Vec<Ingredient>,
+}
+
+/// An ingredient that rules a recipe out, and what it contains that the profile will not eat.
+/// Ingredients of the intermediate recipes a recipe is made from are included.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ForbiddenIngredient {
+    pub ingredient: Ingredient,
+    pub tag: Tag,
+}
+
+/// An ingredient a recipe needs more of than is available.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Shortfall {
+    pub ingredient: Ingredient,
+    /// The outstanding amount, if the recipe specifies one.
+    pub amount: Option<Quantity>,
 }
@@ -0,0 +1,75 @@
+use annapurna_data::types::Recurrence;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+
+/// One time a recurring task is due.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Occurrence {
+    pub date: NaiveDate,
+    /// Missing when the task is due at no particular time of the day.
+    pub time: Option<NaiveTime>,
+}
+
+impl Occurrence {
+    /// The moment identifying the occurrence, taking occurrences without a time to be due at midnight.
+    pub fn scheduled_at(&self) -> DateTime<Utc> {
+        self.date
+            .and_time(self.time.unwrap_or(NaiveTime::MIN))
+            .and_utc()
+    }
+}
+
+/// Lists the occurrences from `from` through `until`, in order.
+pub fn occurrences(recurrence: &Recurrence, from: NaiveDate, until: NaiveDate) -> Vec<Occurrence> {
+    let mut times = recurrence
+        .times
+        .iter()
+        .copied()
+        .map(Some)
+        .collect::<Vec<_>>();
+    times.sort();
+    times.dedup();
+    if times.is_empty() {
+        times.push(None);
+    }
+
+    from.iter_days()
+        .take_while(|date| *date <= until)
+        .filter(|date| recurrence.repeat.occurs_on(*date))
+        .flat_map(|date| times.iter().map(move |&time| Occurrence { date, time }))
+        .collect()
+}
+
+/// How consistently a recurring task has been done.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Streak {
+    /// The occurrences done in a row, up to the latest.
+    pub current: usize,
+    pub longest: usize,
+    pub completed: usize,
+    pub missed: usize,
+}
+
+/// Tallies the occurrences, given in order along with whether each was done.
+///
+/// Occurrences after `today` are ignored, and those due `today` that are not done yet
+/// still have time, so they neither count as missed nor end the current streak.
+pub fn streak(occurrences: &[(Occurrence, bool)], today: NaiveDate) -> Streak {
+    let mut streak = Streak::default();
+    for (occurrence, done) in occurrences {
+        if occurrence.date > today || (occurrence.date == today && !done) {
+            continue;
+        }
+
+        if *done {
+            streak.completed += 1;
+            streak.current += 1;
+            streak.longest = streak.longest.max(streak.current);
+        } else {
+            streak.missed += 1;
+            streak.current = 0;
+        }
+    }
+
+    streak
+}
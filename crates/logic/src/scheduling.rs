@@ -0,0 +1,128 @@
+use crate::error::{Error, Result};
+use annapurna_data::types::{Recipe, Task};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+/// A step of a recipe, along with when to start it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ScheduledStep {
+    pub recipe: String,
+    /// The position of the step in the recipe.
+    pub step: usize,
+    pub description: String,
+    pub duration: Duration,
+    pub start_time: DateTime<Utc>,
+}
+
+impl ScheduledStep {
+    pub fn end_time(&self) -> DateTime<Utc> {
+        self.start_time + self.duration
+    }
+
+    /// The step as a task, described along with the recipe it is for.
+    pub fn task(&self) -> Task {
+        Task {
+            description: format!("{}: {}", self.recipe, self.description),
+            completed: false,
+            duration: Some(self.duration),
+            start_time: Some(self.start_time.time()),
+            completion_time: None,
+            recurrence: None,
+        }
+    }
+}
+
+/// Plans the steps of the recipes backwards from `serve_at`, so that every recipe is ready then.
+///
+/// Each step starts as late as it can while still finishing before the steps that wait on it,
+/// and the last steps of each recipe finish at `serve_at`. Steps that do not wait on each other,
+/// including the steps of different recipes, overlap. The steps are listed by when to start them.
+pub fn schedule(recipes: &[Recipe], serve_at: DateTime<Utc>) -> Result<Vec<ScheduledStep>> {
+    let mut scheduled = Vec::new();
+    for recipe in recipes {
+        let count = recipe.steps.len();
+        let dependencies = recipe
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(position, step)| {
+                let dependencies = step.dependencies(position);
+                match dependencies
+                    .iter()
+                    .all(|&dependency| dependency < count && dependency != position)
+                {
+                    true => Ok(dependencies),
+                    false => Err(Error::UnknownStep {
+                        recipe: recipe.name.clone(),
+                        step: position,
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // a step has to end by the time the first of the steps waiting on it starts
+        let mut end_times: Vec<Option<DateTime<Utc>>> = vec![None; count];
+        let mut start_times: Vec<Option<DateTime<Utc>>> = vec![None; count];
+        for position in finishing_order(&recipe.name, &dependencies)? {
+            let end_time = end_times[position].unwrap_or(serve_at);
+            let start_time = chrono::Duration::from_std(recipe.steps[position].duration)
+                .ok()
+                .and_then(|duration| end_time.checked_sub_signed(duration))
+                .ok_or_else(|| Error::StepTooLong {
+                    recipe: recipe.name.clone(),
+                    step: position,
+                })?;
+            start_times[position] = Some(start_time);
+            for &dependency in &dependencies[position] {
+                let end = end_times[dependency].get_or_insert(start_time);
+                *end = (*end).min(start_time);
+            }
+        }
+
+        for (position, (step, start_time)) in recipe.steps.iter().zip(start_times).enumerate() {
+            scheduled.push(ScheduledStep {
+                recipe: recipe.name.clone(),
+                step: position,
+                description: step.description.clone(),
+                duration: step.duration,
+                start_time: start_time.unwrap_or(serve_at),
+            });
+        }
+    }
+
+    scheduled.sort_by(|a, b| {
+        a.start_time
+            .cmp(&b.start_time)
+            .then(a.recipe.cmp(&b.recipe))
+            .then(a.step.cmp(&b.step))
+    });
+    Ok(scheduled)
+}
+
+/// Orders the steps so that every step comes before the steps it waits on.
+fn finishing_order(recipe: &str, dependencies: &[Vec<usize>]) -> Result<Vec<usize>> {
+    let mut waiting = vec![0; dependencies.len()];
+    for step in dependencies.iter().flatten() {
+        waiting[*step] += 1;
+    }
+
+    let mut ready = (0..dependencies.len())
+        .filter(|&step| waiting[step] == 0)
+        .collect::<Vec<_>>();
+    let mut order = Vec::with_capacity(dependencies.len());
+    while let Some(step) = ready.pop() {
+        order.push(step);
+        for &dependency in &dependencies[step] {
+            waiting[dependency] -= 1;
+            if waiting[dependency] == 0 {
+                ready.push(dependency);
+            }
+        }
+    }
+
+    match order.len() == dependencies.len() {
+        true => Ok(order),
+        false => Err(Error::CyclicSteps(recipe.to_string())),
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    error::Result,
+    program::AscentProgram,
+    recipe::{RecipeManager, Shortfall},
+};
+use annapurna_data::types::{Ingredient, Quantity, Recipe};
+use std::collections::HashMap;
+
+/// The name of the recipe that combines the requirements of every recipe on a shopping list.
+/// It is not a valid recipe name in the facts files, so it cannot collide with a real recipe.
+const SHOPPING_LIST_RECIPE: &str = "#shopping-list";
+
+/// Works out what needs to be bought to make all of the selected recipes.
+///
+/// The recipes' requirements are combined before they are checked against the available
+/// ingredients, so two recipes that each need most of the flour on hand will add flour to the list.
+/// Intermediate recipes that can be made from what is available are not added to the list.
+pub fn shopping_list(manager: &RecipeManager, selected: &[String]) -> Result<Vec<Shortfall>> {
+    let selected = selected
+        .iter()
+        .map(|name| manager.recipe(name))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(shortfalls(
+        manager,
+        manager.available_ingredients.clone(),
+        combine_requirements(&selected),
+    ))
+}
+
+/// Checks the requirements against the available ingredients, as though they were a single recipe.
+pub(crate) fn shortfalls(
+    manager: &RecipeManager,
+    available_ingredients: Vec<Ingredient>,
+    requirements: Vec<Ingredient>,
+) -> Vec<Shortfall> {
+    let mut recipes = manager.recipes.clone();
+    recipes.push(Recipe::new(SHOPPING_LIST_RECIPE.to_string(), requirements));
+
+    let combined = RecipeManager {
+        available_ingredients,
+        recipes,
+        densities: manager.densities.clone(),
+        substitutions: manager.substitutions.clone(),
+        approximate: manager.approximate,
+        servings: manager.servings.clone(),
+        // the requirements are checked as they are, whatever the profile rules out
+        tags: HashMap::new(),
+        profile: Default::default(),
+    };
+
+    let mut items = combined
+        .process()
+        .shortfalls
+        .remove(SHOPPING_LIST_RECIPE)
+        .unwrap_or_default();
+    items.sort_by(|a, b| a.ingredient.cmp(&b.ingredient));
+
+    items
+}
+
+/// Totals the ingredients of the recipes, adding together amounts of the same ingredient.
+/// Amounts are normalized so they can be added, and those that cannot be converted into one
+/// another are kept separate. An ingredient without any amounts is kept without one.
+pub(crate) fn combine_requirements(recipes: &[&Recipe]) -> Vec<Ingredient> {
+    let mut totals: HashMap<String, HashMap<String, f64>> = HashMap::new();
+    for ingredient in recipes.iter().flat_map(|recipe| recipe.ingredients.iter()) {
+        let amounts = totals.entry(ingredient.name.clone()).or_default();
+        if let Some(amount) = &ingredient.amount {
+            let amount = amount.normalize().unwrap_or_else(|_| amount.clone());
+            *amounts.entry(amount.unit).or_default() += amount.value;
+        }
+    }
+
+    let mut ingredients = totals
+        .into_iter()
+        .flat_map(|(name, amounts)| match amounts.is_empty() {
+            true => vec![Ingredient::new(name)],
+            false => amounts
+                .into_iter()
+                .map(|(unit, value)| {
+                    Ingredient::with_amount(name.clone(), Quantity::new(value, unit))
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    ingredients.sort_by(|a, b| a.name.cmp(&b.name));
+
+    ingredients
+}
@@ -1,8 +1,24 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::consumption::{consume, cook, shortfalls};
+use crate::error::Error;
+use crate::expiration::{use_it_up, Suggestion};
+use crate::nutrition::recipe_nutrition;
+use crate::planning::{check_plan, PlannedMeal};
 use crate::program::AscentProgram;
-use crate::recipe::RecipeManager;
-use annapurna_data::types::{Ingredient, Recipe};
+use crate::ranking::{rank, shopping_suggestions, ShoppingStep};
+use crate::recipe::{
+    ForbiddenIngredient, IngredientMatch, RecipeManager, Shortfall, SubstitutionUse,
+};
+use crate::routine::{occurrences, streak, Occurrence, Streak};
+use crate::scheduling::schedule;
+use crate::shopping::shopping_list;
+use annapurna_data::{
+    dietary::{Diet, DietaryProfile, Tag},
+    nutrition::{Nutrients, Nutrition},
+    types::{Ingredient, Quantity, Recipe, Recurrence, Repeat, Step, Substitution},
+    units::Density,
+};
 
 fn build_ingredients(data: Vec<&str>) -> Vec<Ingredient> {
     data.into_iter()
@@ -10,6 +26,14 @@ fn build_ingredients(data: Vec<&str>) -> Vec<Ingredient> {
         .collect()
 }
 
+fn build_measured(data: Vec<(&str, f64, &str)>) -> Vec<Ingredient> {
+    data.into_iter()
+        .map(|(name, value, unit)| {
+            Ingredient::with_amount(name.to_string(), Quantity::new(value, unit.to_string()))
+        })
+        .collect()
+}
+
 fn build_recipe(name: &str, ingredients: Vec<&str>) -> Recipe {
     Recipe::new(name.to_string(), build_ingredients(ingredients))
 }
@@ -80,3 +104,852 @@ fn missing_ingredients() {
 
     assert_eq!(missing, expected);
 }
+
+#[test]
+fn reports_quantity_shortfalls() {
+    let recipes = vec![
+        Recipe::new(
+            "bread".to_string(),
+            build_measured(vec![("flour", 500.0, "g"), ("water", 350.0, "ml")]),
+        ),
+        Recipe::new(
+            "flatbread".to_string(),
+            build_measured(vec![("flour", 200.0, "g"), ("water", 100.0, "ml")]),
+        ),
+    ];
+
+    let has_ingredients = build_measured(vec![
+        ("flour", 150.0, "g"),
+        ("flour", 100.0, "g"),
+        ("water", 1.0, "l"),
+    ]);
+
+    let manager = RecipeManager::new(has_ingredients, recipes);
+    let res = manager.process();
+
+    // stock in different units of the same dimension is converted before comparing
+    assert_eq!(res.can_make, vec!["flatbread"]);
+    assert_eq!(
+        res.shortfalls["bread"],
+        vec![Shortfall {
+            ingredient: "flour".to_string(),
+            amount: Some(Quantity::new(250.0, "g".to_string())),
+        }]
+    );
+}
+
+#[test]
+fn converts_with_densities() {
+    let recipes = vec![Recipe::new(
+        "cookies".to_string(),
+        build_measured(vec![("flour", 1.0, "cup"), ("butter", 100.0, "g")]),
+    )];
+
+    let has_ingredients = build_measured(vec![("flour", 200.0, "g"), ("butter", 0.25, "kg")]);
+
+    // without a density the volume of flour cannot be compared to its mass
+    let manager = RecipeManager::new(has_ingredients.clone(), recipes.clone());
+    let res = manager.process();
+    assert_eq!(res.can_make, Vec::<String>::new());
+    assert_eq!(res.missing["cookies"], vec!["flour"]);
+
+    let densities = HashMap::from([("flour".to_string(), Density(0.53))]);
+    let manager = RecipeManager::new(has_ingredients, recipes).densities(densities);
+    let res = manager.process();
+    assert_eq!(res.can_make, vec!["cookies"]);
+}
+
+#[test]
+fn unmeasured_stock_is_sufficient() {
+    let recipes = vec![
+        Recipe::new(
+            "bread".to_string(),
+            build_measured(vec![("flour", 500.0, "g"), ("salt", 10.0, "g")]),
+        ),
+        build_recipe("toast", vec!["bread", "butter"]),
+    ];
+
+    let mut has_ingredients = build_ingredients(vec!["flour", "salt", "bread"]);
+    has_ingredients.extend(build_measured(vec![("salt", 1.0, "g")]));
+
+    let manager = RecipeManager::new(has_ingredients, recipes);
+    let res = manager.process();
+
+    assert_eq!(res.can_make, vec!["bread"]);
+    assert_eq!(
+        res.shortfalls["toast"],
+        vec![Shortfall {
+            ingredient: "butter".to_string(),
+            amount: None,
+        }]
+    );
+}
+
+#[test]
+fn makes_intermediate_recipes() {
+    let recipes = vec![
+        build_recipe("egg/yolk", vec!["egg"]),
+        build_recipe("mayo", vec!["oil/avocado", "egg/yolk"]),
+        build_recipe("aioli", vec!["mayo", "garlic"]),
+        build_recipe("sandwich", vec!["bread", "mayo", "aioli"]),
+    ];
+
+    let has_ingredients = build_ingredients(vec!["egg", "oil/avocado", "garlic", "bread"]);
+
+    let manager = RecipeManager::new(has_ingredients, recipes);
+    let res = manager.process();
+
+    let can_make = res.can_make.into_iter().collect::<HashSet<_>>();
+    let expected = ["egg/yolk", "mayo", "aioli", "sandwich"]
+        .into_iter()
+        .map(String::from)
+        .collect::<HashSet<_>>();
+    assert_eq!(can_make, expected);
+    assert!(res.missing.is_empty());
+
+    assert_eq!(res.production_chains["mayo"], vec!["egg/yolk"]);
+    assert_eq!(res.production_chains["aioli"], vec!["egg/yolk", "mayo"]);
+    assert_eq!(
+        res.production_chains["sandwich"],
+        vec!["egg/yolk", "mayo", "aioli"]
+    );
+    assert!(!res.production_chains.contains_key("egg/yolk"));
+}
+
+#[test]
+fn prefers_stocked_intermediates() {
+    let recipes = vec![
+        build_recipe("egg/yolk", vec!["egg"]),
+        build_recipe("mayo", vec!["oil/avocado", "egg/yolk"]),
+        build_recipe("custard", vec!["milk", "egg/yolk"]),
+    ];
+
+    let has_ingredients = build_ingredients(vec!["oil/avocado", "egg/yolk"]);
+
+    let manager = RecipeManager::new(has_ingredients, recipes);
+    let res = manager.process();
+
+    assert_eq!(res.can_make, vec!["mayo"]);
+    assert!(res.production_chains.is_empty());
+    assert_eq!(res.missing["egg/yolk"], vec!["egg"]);
+    assert_eq!(res.missing["custard"], vec!["milk"]);
+}
+
+#[test]
+fn rules_out_recipes_that_do_not_suit_the_profile() {
+    let recipes = vec![
+        build_recipe("egg/yolk", vec!["egg"]),
+        build_recipe("mayo", vec!["oil/avocado", "egg/yolk"]),
+        build_recipe("sandwich", vec!["bread", "mayo"]),
+        build_recipe("toast", vec!["bread/sourdough", "oil/avocado"]),
+        build_recipe("salad", vec!["lettuce", "oil/avocado"]),
+        build_recipe("satay", vec!["peanut", "rice"]),
+    ];
+    let has_ingredients = build_ingredients(vec![
+        "egg",
+        "oil/avocado",
+        "bread",
+        "bread/sourdough",
+        "lettuce",
+    ]);
+    let tags = HashMap::from([
+        ("egg".to_string(), vec![Tag::Egg]),
+        ("bread".to_string(), vec![Tag::Gluten]),
+        ("peanut".to_string(), vec![Tag::Peanut]),
+    ]);
+    let manager = RecipeManager::new(has_ingredients, recipes).tags(tags);
+
+    // without a profile, every recipe is allowed
+    let res = manager.process();
+    assert_eq!(res.allowed.len(), 6);
+    assert!(res.forbidden.is_empty());
+
+    let res = manager
+        .profile(DietaryProfile {
+            diets: vec![Diet::Vegan],
+            avoid: vec![Tag::Gluten],
+        })
+        .process();
+    assert_eq!(res.allowed, vec!["salad", "satay"]);
+    assert_eq!(
+        res.forbidden["toast"],
+        vec![ForbiddenIngredient {
+            ingredient: "bread/sourdough".to_string(),
+            tag: Tag::Gluten,
+        }]
+    );
+    // the egg of an intermediate recipe rules out everything made from it
+    assert_eq!(
+        res.forbidden["mayo"],
+        vec![
+            ForbiddenIngredient {
+                ingredient: "egg".to_string(),
+                tag: Tag::Egg,
+            },
+            ForbiddenIngredient {
+                ingredient: "egg/yolk".to_string(),
+                tag: Tag::Egg,
+            },
+        ]
+    );
+    assert_eq!(res.forbidden["sandwich"].len(), 3);
+
+    // recipes that are ruled out are not suggested
+    assert_eq!(res.can_make, vec!["salad"]);
+    assert!(res.production_chains.is_empty());
+    assert_eq!(res.missing.keys().collect::<Vec<_>>(), vec!["satay"]);
+}
+
+#[test]
+fn matches_ingredient_categories() {
+    let recipes = vec![
+        build_recipe("stir-fry", vec!["oil", "onion/green"]),
+        build_recipe("dressing", vec!["oil/olive", "vinegar"]),
+    ];
+
+    let has_ingredients = build_ingredients(vec!["oil/avocado", "onion", "vinegar"]);
+
+    // a general ingredient is only used for a specific one when approximations are allowed
+    let manager = RecipeManager::new(has_ingredients.clone(), recipes.clone());
+    let res = manager.process();
+    assert_eq!(res.can_make, Vec::<String>::new());
+    assert_eq!(res.missing["stir-fry"], vec!["onion/green"]);
+    assert_eq!(res.missing["dressing"], vec!["oil/olive"]);
+    assert_eq!(
+        res.matches["stir-fry"],
+        vec![IngredientMatch {
+            ingredient: "oil".to_string(),
+            item: "oil/avocado".to_string(),
+            approximate: false,
+        }]
+    );
+
+    let manager = RecipeManager::new(has_ingredients, recipes).approximate(true);
+    let res = manager.process();
+    assert_eq!(res.can_make, vec!["stir-fry"]);
+    assert!(res.is_approximate("stir-fry"));
+    assert_eq!(
+        res.matches["stir-fry"],
+        vec![
+            IngredientMatch {
+                ingredient: "oil".to_string(),
+                item: "oil/avocado".to_string(),
+                approximate: false,
+            },
+            IngredientMatch {
+                ingredient: "onion/green".to_string(),
+                item: "onion".to_string(),
+                approximate: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn prefers_exact_matches() {
+    let recipes = vec![build_recipe("fried-rice", vec!["rice", "onion/green"])];
+    let has_ingredients = build_ingredients(vec!["rice", "onion", "onion/green"]);
+
+    let manager = RecipeManager::new(has_ingredients, recipes).approximate(true);
+    let res = manager.process();
+
+    assert!(!res.is_approximate("fried-rice"));
+    let items = res.matches["fried-rice"]
+        .iter()
+        .map(|m| m.item.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(items, vec!["onion/green", "rice"]);
+}
+
+#[test]
+fn substitutes_missing_ingredients() {
+    let recipes = vec![
+        build_recipe("pancakes", vec!["flour", "buttermilk", "egg"]),
+        build_recipe("kimchi", vec!["cabbage", "gochugaru"]),
+        build_recipe("ranch", vec!["buttermilk", "mayo"]),
+        build_recipe("toast", vec!["bread"]),
+    ];
+
+    let substitutions = vec![
+        Substitution {
+            ingredient: "buttermilk".to_string(),
+            replacements: vec!["milk".to_string(), "vinegar".to_string()],
+        },
+        Substitution {
+            ingredient: "gochugaru".to_string(),
+            replacements: vec!["chili flakes".to_string()],
+        },
+    ];
+
+    let has_ingredients = build_ingredients(vec![
+        "flour",
+        "egg",
+        "milk",
+        "vinegar/apple-cider",
+        "cabbage",
+        "chili flakes",
+        "bread",
+    ]);
+
+    let manager = RecipeManager::new(has_ingredients, recipes).substitutions(substitutions);
+    let res = manager.process();
+
+    assert_eq!(res.can_make, vec!["toast"]);
+
+    let mut substituted = res.substitutions.keys().cloned().collect::<Vec<_>>();
+    substituted.sort();
+    assert_eq!(substituted, vec!["kimchi", "pancakes"]);
+
+    assert_eq!(
+        res.substitutions["pancakes"],
+        vec![SubstitutionUse {
+            ingredient: "buttermilk".to_string(),
+            replacements: vec!["milk".to_string(), "vinegar".to_string()],
+        }]
+    );
+    assert_eq!(
+        res.substitutions["kimchi"],
+        vec![SubstitutionUse {
+            ingredient: "gochugaru".to_string(),
+            replacements: vec!["chili flakes".to_string()],
+        }]
+    );
+    // ranch still needs mayo, so substituting its buttermilk is not enough
+    assert_eq!(res.missing["ranch"].len(), 2);
+    assert!(!res.substitutions.contains_key("ranch"));
+
+    // replacing an ingredient with nothing does not make it available
+    let empty = Substitution {
+        ingredient: "mayo".to_string(),
+        replacements: Vec::new(),
+    };
+    let ranch = vec![build_recipe("ranch", vec!["buttermilk", "mayo"])];
+    let res = RecipeManager::new(build_ingredients(vec!["buttermilk"]), ranch)
+        .substitutions(vec![empty])
+        .process();
+    assert!(res.can_make.is_empty());
+    assert!(res.substitutions.is_empty());
+}
+
+#[test]
+fn ranks_almost_makeable_recipes() {
+    let recipes = vec![
+        build_recipe("cake", vec!["flour", "sugar", "eggs", "milk"]),
+        build_recipe("pancakes", vec!["flour", "eggs", "milk"]),
+        build_recipe("crepes", vec!["flour", "eggs", "milk", "butter"]),
+        build_recipe("bread", vec!["flour", "water"]),
+    ];
+    let has_ingredients = build_ingredients(vec!["flour", "milk", "water"]);
+
+    let res = RecipeManager::new(has_ingredients, recipes).process();
+
+    let ranked = rank(&res, &HashMap::new());
+    let order = ranked.iter().map(|r| r.recipe.as_str()).collect::<Vec<_>>();
+    assert_eq!(order, vec!["pancakes", "cake", "crepes"]);
+    assert_eq!(ranked[0].missing, vec!["eggs"]);
+    assert_eq!(ranked[0].cost, 1.0);
+
+    // expensive ingredients push recipes down the ranking
+    let prices = HashMap::from([("sugar".to_string(), 0.5), ("butter".to_string(), 10.0)]);
+    let ranked = rank(&res, &prices);
+    let order = ranked.iter().map(|r| r.recipe.as_str()).collect::<Vec<_>>();
+    assert_eq!(order, vec!["pancakes", "cake", "crepes"]);
+    assert_eq!(ranked[1].cost, 1.5);
+    assert_eq!(ranked[2].cost, 11.0);
+}
+
+#[test]
+fn suggests_ingredients_that_unlock_the_most_recipes() {
+    let recipes = vec![
+        build_recipe("omelette", vec!["eggs", "butter"]),
+        build_recipe("scramble", vec!["eggs", "butter", "milk"]),
+        build_recipe("fried-eggs", vec!["eggs", "oil"]),
+        build_recipe("saffron-rice", vec!["saffron", "rice"]),
+    ];
+    let has_ingredients = build_ingredients(vec!["milk", "oil", "rice"]);
+
+    let res = RecipeManager::new(has_ingredients, recipes).process();
+
+    let steps = shopping_suggestions(&res, &HashMap::new(), None);
+    assert_eq!(
+        steps,
+        vec![
+            ShoppingStep {
+                buy: vec!["butter".to_string(), "eggs".to_string()],
+                unlocks: vec![
+                    "fried-eggs".to_string(),
+                    "omelette".to_string(),
+                    "scramble".to_string()
+                ],
+            },
+            ShoppingStep {
+                buy: vec!["saffron".to_string()],
+                unlocks: vec!["saffron-rice".to_string()],
+            },
+        ]
+    );
+
+    // with a single item allowed, eggs alone unlock fried eggs
+    let steps = shopping_suggestions(&res, &HashMap::new(), Some(1));
+    assert_eq!(
+        steps,
+        vec![ShoppingStep {
+            buy: vec!["eggs".to_string()],
+            unlocks: vec!["fried-eggs".to_string()],
+        }]
+    );
+}
+
+#[test]
+fn combines_recipes_into_a_shopping_list() {
+    let recipes = vec![
+        Recipe::new(
+            "bread".to_string(),
+            build_measured(vec![("flour", 400.0, "g"), ("salt", 10.0, "g")]),
+        ),
+        Recipe::new(
+            "pizza".to_string(),
+            build_measured(vec![("flour", 0.3, "kg"), ("tomato/puree", 200.0, "ml")]),
+        ),
+        build_recipe("egg/yolk", vec!["egg"]),
+        build_recipe("mayo", vec!["oil", "egg/yolk"]),
+    ];
+
+    let mut has_ingredients = build_measured(vec![("flour", 500.0, "g"), ("salt", 1.0, "kg")]);
+    has_ingredients.extend(build_ingredients(vec!["egg"]));
+
+    let manager = RecipeManager::new(has_ingredients, recipes);
+
+    // each recipe can be made on its own, but not together
+    let items = shopping_list(&manager, &["bread".to_string(), "mayo".to_string()]).unwrap();
+    assert_eq!(
+        items,
+        vec![Shortfall {
+            ingredient: "oil".to_string(),
+            amount: None,
+        }]
+    );
+
+    let items = shopping_list(&manager, &["bread".to_string(), "pizza".to_string()]).unwrap();
+    assert_eq!(
+        items,
+        vec![
+            Shortfall {
+                ingredient: "flour".to_string(),
+                amount: Some(Quantity::new(200.0, "g".to_string())),
+            },
+            Shortfall {
+                ingredient: "tomato/puree".to_string(),
+                amount: Some(Quantity::new(200.0, "ml".to_string())),
+            },
+        ]
+    );
+
+    assert!(matches!(
+        shopping_list(&manager, &["cake".to_string()]),
+        Err(Error::UnknownRecipe(name)) if name == "cake"
+    ));
+}
+
+#[test]
+fn consumes_stock_in_order() {
+    let mut stock = build_measured(vec![
+        ("flour", 300.0, "g"),
+        ("flour/bread", 1.0, "kg"),
+        ("flour", 200.0, "g"),
+        ("milk", 1.0, "l"),
+    ]);
+    stock.extend(build_ingredients(vec!["salt"]));
+
+    let mut requirements = build_measured(vec![
+        ("flour", 600.0, "g"),
+        ("milk", 250.0, "ml"),
+        ("salt", 10.0, "g"),
+    ]);
+    requirements.extend(build_ingredients(vec!["yeast"]));
+
+    let remaining = consume(&stock, &requirements, &HashMap::new());
+
+    // exact matches are used up before the more specific flour
+    assert_eq!(
+        remaining,
+        vec![
+            None,
+            Some(build_measured(vec![("flour/bread", 0.9, "kg")]).remove(0)),
+            None,
+            Some(build_measured(vec![("milk", 0.75, "l")]).remove(0)),
+            Some(Ingredient::new("salt".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn flags_planned_days_without_enough_stock() {
+    let recipes = vec![Recipe::new(
+        "bread".to_string(),
+        build_measured(vec![("flour", 500.0, "g"), ("salt", 10.0, "g")]),
+    )];
+    let has_ingredients = build_measured(vec![("flour", 700.0, "g"), ("salt", 1.0, "kg")]);
+
+    let manager = RecipeManager::new(has_ingredients, recipes)
+        .servings(HashMap::from([("bread".to_string(), 2)]));
+
+    let day = |day| chrono::NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+    let meals = vec![
+        PlannedMeal {
+            date: day(2),
+            recipe: "bread".to_string(),
+            servings: 1,
+        },
+        PlannedMeal {
+            date: day(1),
+            recipe: "bread".to_string(),
+            servings: 2,
+        },
+    ];
+
+    let checks = check_plan(&manager, &meals).unwrap();
+    assert_eq!(checks.len(), 2);
+
+    assert_eq!(checks[0].date, day(1));
+    assert!(checks[0].is_feasible());
+
+    // the first day leaves 200 g of flour, but half a loaf needs 250 g
+    assert_eq!(checks[1].date, day(2));
+    assert_eq!(
+        checks[1].shortfalls,
+        vec![Shortfall {
+            ingredient: "flour".to_string(),
+            amount: Some(Quantity::new(50.0, "g".to_string())),
+        }]
+    );
+}
+
+#[test]
+fn cooks_scaled_recipes() {
+    let recipes = vec![Recipe::new(
+        "bread".to_string(),
+        build_measured(vec![("flour", 500.0, "g"), ("water", 350.0, "ml")]),
+    )];
+    let stock = build_measured(vec![("flour", 300.0, "g"), ("flour", 1.0, "kg")]);
+
+    let manager =
+        RecipeManager::new(Vec::new(), recipes).servings(HashMap::from([("bread".to_string(), 2)]));
+    assert_eq!(manager.servings_of("bread"), 2);
+    assert_eq!(manager.servings_of("cake"), 1);
+
+    let remaining = cook(&manager, "bread", 4, &stock).unwrap();
+    assert_eq!(
+        remaining,
+        vec![
+            None,
+            Some(build_measured(vec![("flour", 0.3, "kg")]).remove(0))
+        ]
+    );
+    assert_eq!(
+        shortfalls(&manager, "bread", 4, &stock).unwrap(),
+        vec![Shortfall {
+            ingredient: "water".to_string(),
+            amount: Some(Quantity::new(700.0, "ml".to_string())),
+        }]
+    );
+
+    assert!(matches!(
+        cook(&manager, "cake", 1, &stock),
+        Err(Error::UnknownRecipe(_))
+    ));
+}
+
+#[test]
+fn only_counts_stock_that_cooking_uses_up() {
+    let recipes = vec![
+        build_recipe("egg/yolk", vec!["egg"]),
+        Recipe::new(
+            "mayo".to_string(),
+            build_measured(vec![("egg/yolk", 1.0, "piece"), ("oil", 200.0, "ml")]),
+        ),
+    ];
+    let mut stock = build_measured(vec![("egg", 6.0, "piece"), ("oil", 150.0, "ml")]);
+    stock.extend(build_ingredients(vec!["salt"]));
+
+    let manager = RecipeManager::new(Vec::new(), recipes);
+
+    // the yolk could be made from an egg, but cooking would not use the egg up
+    assert_eq!(
+        shortfalls(&manager, "mayo", 1, &stock).unwrap(),
+        vec![
+            Shortfall {
+                ingredient: "egg/yolk".to_string(),
+                amount: Some(Quantity::new(1.0, "piece".to_string())),
+            },
+            Shortfall {
+                ingredient: "oil".to_string(),
+                amount: Some(Quantity::new(50.0, "ml".to_string())),
+            },
+        ]
+    );
+    assert_eq!(
+        cook(&manager, "mayo", 1, &stock).unwrap()[0],
+        Some(stock[0].clone())
+    );
+}
+
+#[test]
+fn suggests_recipes_that_use_up_expiring_stock() {
+    let recipes = vec![
+        Recipe::new(
+            "omelette".to_string(),
+            build_measured(vec![("egg", 3.0, "count"), ("milk", 100.0, "ml")]),
+        ),
+        Recipe::new(
+            "custard".to_string(),
+            build_measured(vec![("egg", 6.0, "count"), ("milk", 500.0, "ml")]),
+        ),
+        build_recipe("pancakes", vec!["egg", "milk", "flour", "sugar"]),
+        build_recipe("toast", vec!["bread", "butter"]),
+    ];
+
+    let expiring = build_measured(vec![("egg", 6.0, "count"), ("milk", 0.5, "l")]);
+    let mut has_ingredients = expiring.clone();
+    has_ingredients.extend(build_ingredients(vec!["bread", "butter"]));
+
+    let manager = RecipeManager::new(has_ingredients, recipes);
+    let suggestions = use_it_up(&manager, &expiring, 1);
+
+    // pancakes are missing two ingredients, and toast uses nothing that is expiring
+    assert_eq!(
+        suggestions,
+        vec![
+            Suggestion {
+                recipe: "custard".to_string(),
+                uses: vec!["egg".to_string(), "milk".to_string()],
+                missing: vec![],
+                score: 2.0,
+            },
+            Suggestion {
+                recipe: "omelette".to_string(),
+                uses: vec!["egg".to_string(), "milk".to_string()],
+                missing: vec![],
+                score: 0.7,
+            },
+        ]
+    );
+}
+
+#[test]
+fn materializes_occurrences_at_each_time() {
+    let date = |day| chrono::NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+    let time = |hour| chrono::NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+    let recurrence = Recurrence {
+        repeat: Repeat::Weekdays,
+        times: vec![time(19), time(7)],
+    };
+
+    // 2024-03-08 is a Friday
+    let found = occurrences(&recurrence, date(8), date(11));
+    assert_eq!(
+        found,
+        vec![
+            Occurrence {
+                date: date(8),
+                time: Some(time(7)),
+            },
+            Occurrence {
+                date: date(8),
+                time: Some(time(19)),
+            },
+            Occurrence {
+                date: date(11),
+                time: Some(time(7)),
+            },
+            Occurrence {
+                date: date(11),
+                time: Some(time(19)),
+            },
+        ]
+    );
+
+    let daily = Recurrence {
+        repeat: Repeat::Daily,
+        times: vec![],
+    };
+    assert_eq!(occurrences(&daily, date(1), date(3)).len(), 3);
+    assert_eq!(occurrences(&daily, date(3), date(1)), vec![]);
+}
+
+#[test]
+fn counts_streaks_of_completed_occurrences() {
+    let date = |day| chrono::NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+    let done = [true, true, true, false, true, true, false];
+    let history = done
+        .iter()
+        .enumerate()
+        .map(|(day, done)| {
+            let occurrence = Occurrence {
+                date: date(day as u32 + 1),
+                time: None,
+            };
+            (occurrence, *done)
+        })
+        .collect::<Vec<_>>();
+
+    // the last occurrence is due today, so it does not end the streak yet
+    assert_eq!(
+        streak(&history, date(7)),
+        Streak {
+            current: 2,
+            longest: 3,
+            completed: 5,
+            missed: 1,
+        }
+    );
+    assert_eq!(
+        streak(&history, date(8)),
+        Streak {
+            current: 0,
+            longest: 3,
+            completed: 5,
+            missed: 2,
+        }
+    );
+    assert_eq!(streak(&history, date(3)).current, 3);
+}
+
+fn build_step(description: &str, minutes: u64, after: Option<Vec<usize>>) -> Step {
+    Step {
+        description: description.to_string(),
+        duration: std::time::Duration::from_secs(minutes * 60),
+        after,
+    }
+}
+
+#[test]
+fn schedules_steps_backwards_from_serving() {
+    let bread = build_recipe("bread", vec!["flour", "water", "salt", "yeast"]).with_steps(vec![
+        build_step("mix", 15, None),
+        build_step("proof", 60, None),
+        build_step("shape", 10, None),
+        build_step("proof again", 30, None),
+        build_step("preheat", 30, Some(vec![])),
+        build_step("bake", 35, Some(vec![3, 4])),
+    ]);
+    let rice = build_recipe("fried rice", vec!["rice", "egg"]).with_steps(vec![
+        build_step("cook rice", 20, None),
+        build_step("chop onion", 5, Some(vec![])),
+        build_step("fry", 10, Some(vec![0, 1])),
+    ]);
+    let serve_at = chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+        .unwrap()
+        .and_hms_opt(18, 0, 0)
+        .unwrap()
+        .and_utc();
+
+    let steps = schedule(&[bread, rice], serve_at).unwrap();
+    let starts = steps
+        .iter()
+        .map(|step| {
+            let start = step.start_time.format("%H:%M").to_string();
+            (step.description.as_str(), start)
+        })
+        .collect::<Vec<_>>();
+
+    // the oven preheats while the loaf proofs, and the rice is made while the bread bakes
+    assert_eq!(
+        starts,
+        vec![
+            ("mix", "15:30".to_string()),
+            ("proof", "15:45".to_string()),
+            ("shape", "16:45".to_string()),
+            ("proof again", "16:55".to_string()),
+            ("preheat", "16:55".to_string()),
+            ("bake", "17:25".to_string()),
+            ("cook rice", "17:30".to_string()),
+            ("chop onion", "17:45".to_string()),
+            ("fry", "17:50".to_string()),
+        ]
+    );
+    assert!(steps.iter().all(|step| step.end_time() <= serve_at));
+    assert_eq!(steps[0].task().description, "bread: mix");
+}
+
+#[test]
+fn rejects_steps_that_cannot_be_ordered() {
+    let serve_at = chrono::Utc::now();
+    let cyclic = build_recipe("cake", vec!["flour"]).with_steps(vec![
+        build_step("mix", 5, Some(vec![1])),
+        build_step("bake", 30, Some(vec![0])),
+    ]);
+    let unknown =
+        build_recipe("pie", vec!["flour"]).with_steps(vec![build_step("bake", 30, Some(vec![4]))]);
+
+    assert!(matches!(
+        schedule(&[cyclic], serve_at),
+        Err(Error::CyclicSteps(_))
+    ));
+    assert!(matches!(
+        schedule(&[unknown], serve_at),
+        Err(Error::UnknownStep { step: 0, .. })
+    ));
+
+    let endless = build_recipe("stock", vec!["water"]).with_steps(vec![build_step(
+        "simmer",
+        300_000 * 366 * 24 * 60,
+        None,
+    )]);
+    assert!(matches!(
+        schedule(&[endless], serve_at),
+        Err(Error::StepTooLong { step: 0, .. })
+    ));
+}
+
+#[test]
+fn works_out_nutrition_per_serving() {
+    let per_100g = |calories, protein| Nutrients {
+        calories,
+        protein,
+        ..Default::default()
+    };
+    let nutrition = HashMap::from([
+        (
+            "flour".to_string(),
+            Nutrition {
+                per_100g: per_100g(360.0, 10.0),
+                piece_weight: None,
+            },
+        ),
+        (
+            "egg".to_string(),
+            Nutrition {
+                per_100g: per_100g(140.0, 12.0),
+                piece_weight: Some(50.0),
+            },
+        ),
+        (
+            "milk".to_string(),
+            Nutrition {
+                per_100g: per_100g(60.0, 3.0),
+                piece_weight: None,
+            },
+        ),
+    ]);
+    let densities = HashMap::from([("milk".to_string(), Density(1.0))]);
+
+    let mut ingredients = build_measured(vec![
+        ("flour", 0.5, "kg"),
+        ("egg/duck", 2.0, "count"),
+        ("milk", 200.0, "ml"),
+        ("sugar", 100.0, "g"),
+    ]);
+    ingredients.push(Ingredient::new("salt".to_string()));
+    let recipe = Recipe::new("pancakes".to_string(), ingredients);
+
+    let result = recipe_nutrition(&recipe, 4, &nutrition, &densities);
+
+    // duck eggs are weighed as eggs, and sugar and salt have no known nutrition or amount
+    assert_eq!(result.per_serving.calories, (1800.0 + 140.0 + 120.0) / 4.0);
+    assert_eq!(result.per_serving.protein, (50.0 + 12.0 + 6.0) / 4.0);
+    assert_eq!(
+        result.unknown,
+        vec!["sugar".to_string(), "salt".to_string()]
+    );
+}
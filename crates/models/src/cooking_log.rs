@@ -0,0 +1,117 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx_ulid::Ulid;
+
+/// A record of a recipe having been cooked.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CookingLog {
+    pub cooking_log_id: Ulid,
+    /// The user who cooked the recipe.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
+    pub recipe: String,
+    pub servings: i32,
+    pub cooked_at: DateTime<Utc>,
+}
+
+impl CookingLog {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    pub async fn create<'c, E: sqlx::PgExecutor<'c>>(&self, executor: E) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO
+                cooking_log(cooking_log_id, owner_id, household_id, recipe, servings, cooked_at)
+            VALUES
+                ($1::uuid, $2::uuid, $3::uuid, $4, $5, $6)
+            "#,
+        )
+        .bind(self.cooking_log_id.to_sqlx_uuid())
+        .bind(self.owner_id.to_sqlx_uuid())
+        .bind(self.household_id.to_sqlx_uuid())
+        .bind(&self.recipe)
+        .bind(self.servings)
+        .bind(self.cooked_at)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Builder {
+    cooking_log_id: Option<Ulid>,
+    owner_id: Option<Ulid>,
+    household_id: Option<Ulid>,
+    recipe: Option<String>,
+    servings: Option<i32>,
+    cooked_at: Option<DateTime<Utc>>,
+}
+
+impl Builder {
+    pub fn cooking_log_id(mut self, cooking_log_id: Ulid) -> Self {
+        self.cooking_log_id = Some(cooking_log_id);
+        self
+    }
+
+    pub fn owner_id(mut self, owner_id: Ulid) -> Self {
+        self.owner_id = Some(owner_id);
+        self
+    }
+
+    pub fn household_id(mut self, household_id: Ulid) -> Self {
+        self.household_id = Some(household_id);
+        self
+    }
+
+    pub fn recipe(mut self, recipe: String) -> Self {
+        self.recipe = Some(recipe);
+        self
+    }
+
+    pub fn servings(mut self, servings: i32) -> Self {
+        self.servings = Some(servings);
+        self
+    }
+
+    pub fn cooked_at(mut self, cooked_at: DateTime<Utc>) -> Self {
+        self.cooked_at = Some(cooked_at);
+        self
+    }
+}
+
+impl crate::entity::Builder for Builder {
+    type Item = CookingLog;
+
+    fn build(self) -> Result<Self::Item> {
+        let cooking_log_id = self.cooking_log_id.unwrap_or_else(Ulid::generate);
+        let owner_id = self
+            .owner_id
+            .ok_or_else(|| Error::ModelFieldsMissing("owner_id"))?;
+        let household_id = self
+            .household_id
+            .ok_or_else(|| Error::ModelFieldsMissing("household_id"))?;
+        let recipe = self
+            .recipe
+            .ok_or_else(|| Error::ModelFieldsMissing("recipe"))?;
+        let servings = self
+            .servings
+            .ok_or_else(|| Error::ModelFieldsMissing("servings"))?;
+        let cooked_at = self
+            .cooked_at
+            .ok_or_else(|| Error::ModelFieldsMissing("cooked_at"))?;
+
+        Ok(CookingLog {
+            cooking_log_id,
+            owner_id,
+            household_id,
+            recipe,
+            servings,
+            cooked_at,
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use crate::error::Result;
+use annapurna_data::dietary::{Diet, DietaryProfile, Tag};
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx_ulid::Ulid;
+
+/// What a user will not eat, used to rule out the recipes suggested to them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserDietaryProfile {
+    pub user_id: Ulid,
+    #[serde(flatten)]
+    pub profile: DietaryProfile,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct DietaryProfileRow {
+    user_id: Ulid,
+    // diets and tags are stored by name
+    diets: Vec<String>,
+    avoid: Vec<String>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<DietaryProfileRow> for UserDietaryProfile {
+    type Error = crate::error::Error;
+
+    fn try_from(row: DietaryProfileRow) -> Result<Self> {
+        let diets = row
+            .diets
+            .iter()
+            .map(|diet| diet.parse::<Diet>())
+            .collect::<annapurna_data::error::Result<_>>()?;
+        let avoid = row
+            .avoid
+            .iter()
+            .map(|tag| tag.parse::<Tag>())
+            .collect::<annapurna_data::error::Result<_>>()?;
+
+        Ok(Self {
+            user_id: row.user_id,
+            profile: DietaryProfile { diets, avoid },
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+impl UserDietaryProfile {
+    pub fn new(user_id: Ulid, profile: DietaryProfile, updated_at: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            profile,
+            updated_at,
+        }
+    }
+
+    pub async fn by_user(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        user_id: &Ulid,
+    ) -> Result<Option<Self>> {
+        let row = sqlx::query_as::<_, DietaryProfileRow>(
+            r#"
+            SELECT
+                user_id::uuid as user_id,
+                diets,
+                avoid,
+                updated_at
+            FROM
+                dietary_profile
+            WHERE
+                user_id::uuid = $1::uuid
+            "#,
+        )
+        .bind(user_id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(UserDietaryProfile::try_from).transpose()
+    }
+
+    /// Stores the profile, replacing the one the user had before.
+    pub async fn save(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<()> {
+        let diets = self
+            .profile
+            .diets
+            .iter()
+            .map(|diet| diet.as_str())
+            .collect::<Vec<_>>();
+        let avoid = self
+            .profile
+            .avoid
+            .iter()
+            .map(|tag| tag.as_str())
+            .collect::<Vec<_>>();
+
+        sqlx::query(
+            r#"
+            INSERT INTO
+                dietary_profile(user_id, diets, avoid, updated_at)
+            VALUES
+                ($1::uuid, $2, $3, $4)
+            ON CONFLICT (user_id) DO UPDATE SET
+                diets = EXCLUDED.diets,
+                avoid = EXCLUDED.avoid,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(self.user_id.to_sqlx_uuid())
+        .bind(diets)
+        .bind(avoid)
+        .bind(self.updated_at)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
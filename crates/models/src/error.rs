@@ -6,10 +6,17 @@ pub enum Error {
     #[error(transparent)]
     Sqlx(#[from] sqlx::Error),
 
+    #[error(transparent)]
+    Data(#[from] annapurna_data::error::Error),
+
     #[error("invalid unique field")]
     InvalidUniqueField,
-    #[error("required fields missing")]
+    #[error("required field missing: {0}")]
     ModelFieldsMissing(&'static str),
+    #[error("invalid meal slot: {0}")]
+    InvalidMealSlot(String),
+    #[error("invalid role: {0}")]
+    InvalidRole(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
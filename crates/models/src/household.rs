@@ -0,0 +1,724 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    encode::IsNull,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    types::chrono::{DateTime, Utc},
+    Decode, Encode, Postgres,
+};
+use sqlx_ulid::Ulid;
+
+/// What a member of a household is allowed to do.
+/// Roles are ordered so that each one can do everything the roles before it can.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Can see the household's data.
+    Viewer,
+    /// Can also change the household's inventory, recipes and shopping lists.
+    Editor,
+    /// Can also manage who belongs to the household.
+    Owner,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Owner => "owner",
+        }
+    }
+
+    pub fn can_edit(&self) -> bool {
+        *self >= Role::Editor
+    }
+
+    pub fn can_manage(&self) -> bool {
+        *self >= Role::Owner
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "viewer" => Ok(Role::Viewer),
+            "editor" => Ok(Role::Editor),
+            "owner" => Ok(Role::Owner),
+            _ => Err(Error::InvalidRole(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl sqlx::Type<Postgres> for Role {
+    fn type_info() -> PgTypeInfo {
+        <&str as sqlx::Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <&str as sqlx::Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for Role {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+impl Decode<'_, Postgres> for Role {
+    fn decode(value: PgValueRef<'_>) -> std::result::Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(value.parse()?)
+    }
+}
+
+/// A group of users sharing an inventory, recipes and shopping lists.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Household {
+    pub household_id: Ulid,
+    pub name: String,
+    /// The user this household was created for, if it is someone's personal household.
+    pub personal_for: Option<Ulid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A household along with the role a user has in it.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Membership {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub household: Household,
+    pub role: Role,
+}
+
+impl Household {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    pub async fn by_id(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<Option<Self>> {
+        let household = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                household_id::uuid as household_id,
+                name,
+                personal_for::uuid as personal_for,
+                created_at
+            FROM
+                household
+            WHERE
+                household_id = $1::ulid
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(household)
+    }
+
+    /// Finds the user's personal household, creating it the first time it is needed.
+    pub async fn personal(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        user: &crate::User,
+    ) -> Result<Self> {
+        if let Some(household) = Self::personal_for(pool, &user.user_id).await? {
+            return Ok(household);
+        }
+
+        let mut transaction = pool.begin().await?;
+
+        // another request may have created the household in the meantime
+        sqlx::query(
+            r#"
+            INSERT INTO
+                household(household_id, name, personal_for, created_at)
+            VALUES
+                ($1::uuid, $2, $3::uuid, $4)
+            ON CONFLICT (personal_for) DO NOTHING
+            "#,
+        )
+        .bind(Ulid::generate().to_sqlx_uuid())
+        .bind(&user.identifier)
+        .bind(user.user_id.to_sqlx_uuid())
+        .bind(Utc::now())
+        .execute(&mut *transaction)
+        .await?;
+
+        let household = Self::personal_for(&mut *transaction, &user.user_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        Member::insert(
+            &mut *transaction,
+            &household.household_id,
+            &user.user_id,
+            Role::Owner,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(household)
+    }
+
+    async fn personal_for<'c, E: sqlx::PgExecutor<'c>>(
+        executor: E,
+        user_id: &Ulid,
+    ) -> Result<Option<Self>> {
+        let household = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                household_id::uuid as household_id,
+                name,
+                personal_for::uuid as personal_for,
+                created_at
+            FROM
+                household
+            WHERE
+                personal_for::uuid = $1
+            "#,
+        )
+        .bind(user_id.to_sqlx_uuid())
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(household)
+    }
+
+    /// Creates the household, with the given user as its owner.
+    pub async fn create(
+        &self,
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        owner_id: &Ulid,
+    ) -> Result<()> {
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO
+                household(household_id, name, personal_for, created_at)
+            VALUES
+                ($1::uuid, $2, $3::uuid, $4)
+            "#,
+        )
+        .bind(self.household_id.to_sqlx_uuid())
+        .bind(&self.name)
+        .bind(self.personal_for.as_ref().map(Ulid::to_sqlx_uuid))
+        .bind(self.created_at)
+        .execute(&mut *transaction)
+        .await?;
+
+        Member::insert(&mut *transaction, &self.household_id, owner_id, Role::Owner).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Lists the households the user belongs to, along with their role in each.
+    pub async fn for_user(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        user_id: &Ulid,
+    ) -> Result<Vec<Membership>> {
+        let memberships = sqlx::query_as::<_, Membership>(
+            r#"
+            SELECT
+                household.household_id::uuid as household_id,
+                household.name,
+                household.personal_for::uuid as personal_for,
+                household.created_at,
+                household_member.role
+            FROM
+                household
+                JOIN household_member ON household_member.household_id = household.household_id
+            WHERE
+                household_member.user_id::uuid = $1
+            ORDER BY
+                household.household_id
+            "#,
+        )
+        .bind(user_id.to_sqlx_uuid())
+        .fetch_all(pool)
+        .await?;
+
+        Ok(memberships)
+    }
+
+    /// Deletes the household along with everything it owns, returning whether it existed.
+    pub async fn delete(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                household
+            WHERE
+                household_id = $1::ulid
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// A user belonging to a household.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Member {
+    pub household_id: Ulid,
+    pub user_id: Ulid,
+    pub identifier: String,
+    pub role: Role,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Member {
+    pub async fn find(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+        user_id: &Ulid,
+    ) -> Result<Option<Self>> {
+        let member = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                household_member.household_id::uuid as household_id,
+                household_member.user_id::uuid as user_id,
+                users.identifier,
+                household_member.role,
+                household_member.created_at
+            FROM
+                household_member
+                JOIN users ON users.user_id = household_member.user_id
+            WHERE
+                household_member.household_id = $1::ulid
+                AND household_member.user_id::uuid = $2
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(user_id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(member)
+    }
+
+    pub async fn for_household(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+    ) -> Result<Vec<Self>> {
+        let members = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                household_member.household_id::uuid as household_id,
+                household_member.user_id::uuid as user_id,
+                users.identifier,
+                household_member.role,
+                household_member.created_at
+            FROM
+                household_member
+                JOIN users ON users.user_id = household_member.user_id
+            WHERE
+                household_member.household_id = $1::ulid
+            ORDER BY
+                users.identifier
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .fetch_all(pool)
+        .await?;
+
+        Ok(members)
+    }
+
+    /// The user's role in the household, locked until the transaction ends.
+    async fn role_for_update(
+        conn: &mut sqlx::PgConnection,
+        household_id: &Ulid,
+        user_id: &Ulid,
+    ) -> Result<Option<Role>> {
+        let role = sqlx::query_scalar::<_, Role>(
+            r#"
+            SELECT
+                role
+            FROM
+                household_member
+            WHERE
+                household_id = $1::ulid
+                AND user_id = $2::ulid
+            FOR UPDATE
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(user_id.to_sqlx_uuid())
+        .fetch_optional(conn)
+        .await?;
+
+        Ok(role)
+    }
+
+    /// Adds the user to the household, or changes their role if they already belong to it.
+    async fn insert<'c, E: sqlx::PgExecutor<'c>>(
+        executor: E,
+        household_id: &Ulid,
+        user_id: &Ulid,
+        role: Role,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO
+                household_member(household_id, user_id, role)
+            VALUES
+                ($1::uuid, $2::uuid, $3)
+            ON CONFLICT (household_id, user_id) DO UPDATE
+                SET role = EXCLUDED.role
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(user_id.to_sqlx_uuid())
+        .bind(role)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Changes the member's role, returning whether they belong to the household.
+    pub async fn set_role(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+        user_id: &Ulid,
+        role: Role,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                household_member
+            SET
+                role = $3
+            WHERE
+                household_id = $1::ulid
+                AND user_id::uuid = $2
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(user_id.to_sqlx_uuid())
+        .bind(role)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Removes the member from the household, returning whether they belonged to it.
+    pub async fn delete(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+        user_id: &Ulid,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                household_member
+            WHERE
+                household_id = $1::ulid
+                AND user_id::uuid = $2
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(user_id.to_sqlx_uuid())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// An offer for a user to join a household, addressed by their identifier.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Invitation {
+    pub household_invitation_id: Ulid,
+    pub household_id: Ulid,
+    pub identifier: String,
+    pub role: Role,
+    pub invited_by: Ulid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Invitation {
+    pub fn builder() -> InvitationBuilder {
+        InvitationBuilder::default()
+    }
+
+    pub async fn by_id(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<Option<Self>> {
+        let invitation = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                household_invitation_id::uuid as household_invitation_id,
+                household_id::uuid as household_id,
+                identifier,
+                role,
+                invited_by::uuid as invited_by,
+                created_at
+            FROM
+                household_invitation
+            WHERE
+                household_invitation_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(invitation)
+    }
+
+    /// Lists the invitations addressed to the identifier.
+    pub async fn for_identifier(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        identifier: &str,
+    ) -> Result<Vec<Self>> {
+        let invitations = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                household_invitation_id::uuid as household_invitation_id,
+                household_id::uuid as household_id,
+                identifier,
+                role,
+                invited_by::uuid as invited_by,
+                created_at
+            FROM
+                household_invitation
+            WHERE
+                identifier = $1
+            ORDER BY
+                household_invitation_id
+            "#,
+        )
+        .bind(identifier)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(invitations)
+    }
+
+    /// Creates the invitation, replacing any earlier one for the same identifier and household.
+    pub async fn create(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO
+                household_invitation(household_invitation_id, household_id, identifier, role, invited_by, created_at)
+            VALUES
+                ($1::uuid, $2::uuid, $3, $4, $5::uuid, $6)
+            ON CONFLICT (household_id, identifier) DO UPDATE
+                SET household_invitation_id = EXCLUDED.household_invitation_id,
+                    role = EXCLUDED.role,
+                    invited_by = EXCLUDED.invited_by,
+                    created_at = EXCLUDED.created_at
+            "#,
+        )
+        .bind(self.household_invitation_id.to_sqlx_uuid())
+        .bind(self.household_id.to_sqlx_uuid())
+        .bind(&self.identifier)
+        .bind(self.role)
+        .bind(self.invited_by.to_sqlx_uuid())
+        .bind(self.created_at)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The role accepting the invitation leaves someone with, given the role they already have.
+    /// Invitations only ever raise a role, so accepting one cannot demote an owner.
+    pub fn accepted_role(&self, current: Option<Role>) -> Role {
+        current.map_or(self.role, |current| current.max(self.role))
+    }
+
+    /// Adds the user to the household with the invited role, and removes the invitation.
+    pub async fn accept(
+        &self,
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        user_id: &Ulid,
+    ) -> Result<()> {
+        let mut transaction = pool.begin().await?;
+
+        let current =
+            Member::role_for_update(&mut transaction, &self.household_id, user_id).await?;
+        let role = self.accepted_role(current);
+        Member::insert(&mut *transaction, &self.household_id, user_id, role).await?;
+        Self::delete(&mut *transaction, &self.household_invitation_id).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Deletes the invitation, returning whether it existed.
+    pub async fn delete<'c, E: sqlx::PgExecutor<'c>>(executor: E, id: &Ulid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                household_invitation
+            WHERE
+                household_invitation_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Builder {
+    household_id: Option<Ulid>,
+    name: Option<String>,
+    created_at: Option<DateTime<Utc>>,
+}
+
+impl Builder {
+    pub fn household_id(mut self, household_id: Ulid) -> Self {
+        self.household_id = Some(household_id);
+        self
+    }
+
+    pub fn name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+}
+
+impl crate::entity::Builder for Builder {
+    type Item = Household;
+
+    fn build(self) -> Result<Self::Item> {
+        let household_id = self.household_id.unwrap_or_else(Ulid::generate);
+        let name = self.name.ok_or_else(|| Error::ModelFieldsMissing("name"))?;
+        let created_at = self
+            .created_at
+            .ok_or_else(|| Error::ModelFieldsMissing("created_at"))?;
+
+        Ok(Household {
+            household_id,
+            name,
+            personal_for: None,
+            created_at,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InvitationBuilder {
+    household_invitation_id: Option<Ulid>,
+    household_id: Option<Ulid>,
+    identifier: Option<String>,
+    role: Option<Role>,
+    invited_by: Option<Ulid>,
+    created_at: Option<DateTime<Utc>>,
+}
+
+impl InvitationBuilder {
+    pub fn household_invitation_id(mut self, household_invitation_id: Ulid) -> Self {
+        self.household_invitation_id = Some(household_invitation_id);
+        self
+    }
+
+    pub fn household_id(mut self, household_id: Ulid) -> Self {
+        self.household_id = Some(household_id);
+        self
+    }
+
+    pub fn identifier(mut self, identifier: String) -> Self {
+        self.identifier = Some(identifier);
+        self
+    }
+
+    pub fn role(mut self, role: Role) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    pub fn invited_by(mut self, invited_by: Ulid) -> Self {
+        self.invited_by = Some(invited_by);
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+}
+
+impl crate::entity::Builder for InvitationBuilder {
+    type Item = Invitation;
+
+    fn build(self) -> Result<Self::Item> {
+        let household_invitation_id = self.household_invitation_id.unwrap_or_else(Ulid::generate);
+        let household_id = self
+            .household_id
+            .ok_or_else(|| Error::ModelFieldsMissing("household_id"))?;
+        let identifier = self
+            .identifier
+            .ok_or_else(|| Error::ModelFieldsMissing("identifier"))?;
+        let role = self.role.unwrap_or(Role::Viewer);
+        let invited_by = self
+            .invited_by
+            .ok_or_else(|| Error::ModelFieldsMissing("invited_by"))?;
+        let created_at = self
+            .created_at
+            .ok_or_else(|| Error::ModelFieldsMissing("created_at"))?;
+
+        Ok(Invitation {
+            household_invitation_id,
+            household_id,
+            identifier,
+            role,
+            invited_by,
+            created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Builder as _;
+
+    fn invitation(role: Role) -> Invitation {
+        Invitation::builder()
+            .household_id(Ulid::generate())
+            .identifier("guest".to_string())
+            .role(role)
+            .invited_by(Ulid::generate())
+            .created_at(Utc::now())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn never_demotes_members_who_accept_an_invitation() {
+        let viewer = invitation(Role::Viewer);
+        assert_eq!(viewer.accepted_role(Some(Role::Owner)), Role::Owner);
+        assert_eq!(viewer.accepted_role(None), Role::Viewer);
+
+        let editor = invitation(Role::Editor);
+        assert_eq!(editor.accepted_role(Some(Role::Viewer)), Role::Editor);
+    }
+}
@@ -1,11 +1,17 @@
 use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
-use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{
+    types::chrono::{DateTime, Utc},
+    Postgres, QueryBuilder,
+};
 use sqlx_ulid::Ulid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Inventory {
     pub inventory_id: Ulid,
+    /// The user who added the item.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
     pub ingredient_type: String,
     pub quantity: String,
     pub created_at: DateTime<Utc>,
@@ -17,11 +23,16 @@ impl Inventory {
         Builder::default()
     }
 
-    pub async fn by_id(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<Option<Self>> {
-        let query = sqlx::query!(
+    pub async fn by_id<'c, E: sqlx::PgExecutor<'c>>(
+        executor: E,
+        id: &Ulid,
+    ) -> Result<Option<Self>> {
+        let inventory = sqlx::query_as::<_, Self>(
             r#"
             SELECT
-                inventory_id::uuid as "inventory_id: Ulid",
+                inventory_id::uuid as inventory_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
                 ingredient_type,
                 quantity,
                 created_at,
@@ -31,98 +42,323 @@ impl Inventory {
             WHERE 
                 inventory_id::uuid = $1
             "#,
-            id.to_sqlx_uuid()
-        );
+        )
+        .bind(id.to_sqlx_uuid())
+        .fetch_optional(executor)
+        .await?;
 
-        let inventory = query.fetch_one(pool).await?;
-        match inventory.inventory_id {
-            Some(inventory_id) => {
-                let inventory = Inventory {
-                    inventory_id,
-                    ingredient_type: inventory.ingredient_type,
-                    quantity: inventory.quantity,
-                    created_at: inventory.created_at,
-                    expiration_date: inventory.expiration_date,
-                };
-
-                Ok(Some(inventory))
-            }
-            _ => Ok(None),
-        }
+        Ok(inventory)
     }
 
-    pub async fn create(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<()> {
+    pub async fn create<'c, E: sqlx::PgExecutor<'c>>(&self, executor: E) -> Result<()> {
         sqlx::query(
             r#"
             INSERT INTO 
-                inventory(inventory_id, ingredient_type, quantity, created_at, expiration_date)
+                inventory(inventory_id, owner_id, household_id, ingredient_type, quantity, created_at, expiration_date)
             SELECT 
-                inventory_id::uuid, ingredient_type, quantity, created_at, expiration_date::timestamptz
+                inventory_id::uuid, owner_id::uuid, household_id::uuid, ingredient_type, quantity, created_at, expiration_date::timestamptz
             FROM(
                 VALUES(
-                    $1, $2, $3, 
-                    $4, $5
+                    $1, $2, $3, $4,
+                    $5, $6, $7
                 )
-            ) AS data(inventory_id, ingredient_type, quantity, created_at, expiration_date)
+            ) AS data(inventory_id, owner_id, household_id, ingredient_type, quantity, created_at, expiration_date)
             "#,
         )
         .bind(self.inventory_id.queryable())
+        .bind(self.owner_id.queryable())
+        .bind(self.household_id.queryable())
         .bind(&self.ingredient_type)
         .bind(&self.quantity)
         .bind(self.created_at)
         .bind(self.expiration_date)
-        .execute(pool)
+        .execute(executor)
         .await?;
 
         Ok(())
     }
 
+    /// Saves changes to the item, returning whether it existed.
+    pub async fn update<'c, E: sqlx::PgExecutor<'c>>(&self, executor: E) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                inventory
+            SET
+                ingredient_type = $2,
+                quantity = $3,
+                expiration_date = $4
+            WHERE
+                inventory_id::uuid = $1
+            "#,
+        )
+        .bind(self.inventory_id.to_sqlx_uuid())
+        .bind(&self.ingredient_type)
+        .bind(&self.quantity)
+        .bind(self.expiration_date)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Lists a page of the household's inventory matching the filter, in the given order.
     pub async fn query(
         pool: &sqlx::pool::Pool<sqlx::Postgres>,
-        _pagination: crate::Pagination,
+        household_id: &Ulid,
+        filter: &Filter,
+        sort: Sort,
+        pagination: crate::Pagination,
     ) -> Result<(Vec<Self>, crate::Pagination)> {
-        // TODO: Implement pagination for querying
-        let inventory = sqlx::query!(
+        let mut query = QueryBuilder::<Postgres>::new(
             r#"
             SELECT
-                inventory_id::uuid as "inventory_id: Ulid",
+                inventory_id::uuid as inventory_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
                 ingredient_type,
                 quantity,
                 created_at,
                 expiration_date
-            FROM 
+            FROM
                 inventory
+            WHERE
+                household_id = "#,
+        );
+        query.push_bind(household_id.to_sqlx_uuid()).push("::ulid");
+        filter.push_conditions(&mut query);
+        sort.push_after(&mut query, pagination.after());
+        sort.push_order(&mut query);
+        query.push(" LIMIT ").push_bind(pagination.fetch_limit());
+
+        let inventory = query.build_query_as::<Self>().fetch_all(pool).await?;
+
+        Ok(pagination.page(inventory, |inventory| inventory.inventory_id))
+    }
+
+    /// Lists the household's inventory with the items that expire soonest first.
+    /// The rows are locked until the transaction ends, so they can be used up without racing.
+    pub async fn by_expiration_for_update(
+        conn: &mut sqlx::PgConnection,
+        household_id: &Ulid,
+    ) -> Result<Vec<Self>> {
+        let inventory = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                inventory_id::uuid as inventory_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
+                ingredient_type,
+                quantity,
+                created_at,
+                expiration_date
+            FROM
+                inventory
+            WHERE
+                household_id = $1::ulid
+            ORDER BY
+                expiration_date ASC NULLS LAST,
+                created_at ASC
+            FOR UPDATE
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .fetch_all(conn)
+        .await?;
+
+        Ok(inventory)
+    }
+
+    pub async fn update_quantity<'c, E: sqlx::PgExecutor<'c>>(
+        executor: E,
+        id: &Ulid,
+        quantity: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                inventory
+            SET
+                quantity = $2
+            WHERE
+                inventory_id::uuid = $1
             "#,
         )
-        .fetch_all(pool)
+        .bind(id.to_sqlx_uuid())
+        .bind(quantity)
+        .execute(executor)
         .await?;
 
-        let inventory = inventory
-            .into_iter()
-            .filter_map(|inventory| match inventory.inventory_id {
-                Some(inventory_id) => Some(Inventory {
-                    inventory_id,
-                    ingredient_type: inventory.ingredient_type,
-                    quantity: inventory.quantity,
-                    created_at: inventory.created_at,
-                    expiration_date: inventory.expiration_date,
-                }),
-                _ => None,
-            })
-            .collect::<Vec<_>>();
-
-        let pagination = crate::Pagination {
-            last_key: inventory.last().map(|inventory| inventory.inventory_id),
-            count: inventory.len(),
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes the item, returning whether it existed.
+    pub async fn delete<'c, E: sqlx::PgExecutor<'c>>(executor: E, id: &Ulid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                inventory
+            WHERE
+                inventory_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Conditions narrowing down which inventory is listed. Every condition given must hold.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Filter {
+    /// Matches the ingredient type exactly, or every kind within a category when it ends in `/*`,
+    /// so `oil/*` matches `oil` as well as `oil/olive`.
+    pub ingredient_type: Option<String>,
+    /// Only items expiring before the time. Items without an expiration date are left out.
+    pub expiring_before: Option<DateTime<Utc>>,
+    /// Only items expiring at or after the time. Items without an expiration date are left out.
+    pub expiring_after: Option<DateTime<Utc>>,
+    /// Only items added before the time.
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only items added at or after the time.
+    pub created_after: Option<DateTime<Utc>>,
+}
+
+impl Filter {
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        if let Some(ingredient_type) = &self.ingredient_type {
+            match ingredient_type.strip_suffix("/*") {
+                Some(category) => {
+                    query
+                        .push(" AND (ingredient_type = ")
+                        .push_bind(category.to_string())
+                        .push(" OR ingredient_type LIKE ")
+                        .push_bind(format!("{}/%", escape_like(category)))
+                        .push(")");
+                }
+                None => {
+                    query
+                        .push(" AND ingredient_type = ")
+                        .push_bind(ingredient_type.clone());
+                }
+            }
+        }
+        if let Some(expiring_before) = self.expiring_before {
+            query
+                .push(" AND expiration_date < ")
+                .push_bind(expiring_before);
+        }
+        if let Some(expiring_after) = self.expiring_after {
+            query
+                .push(" AND expiration_date >= ")
+                .push_bind(expiring_after);
+        }
+        if let Some(created_before) = self.created_before {
+            query.push(" AND created_at < ").push_bind(created_before);
+        }
+        if let Some(created_after) = self.created_after {
+            query.push(" AND created_at >= ").push_bind(created_after);
+        }
+    }
+}
+
+/// Escapes the characters `LIKE` treats specially, so the text only matches itself.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    /// The order the items were added in.
+    #[default]
+    Added,
+    /// The items that expire soonest first, with items that never expire last.
+    Expiration,
+    IngredientType,
+}
+
+impl SortBy {
+    /// The expression to sort by before the key, if there is one.
+    fn expression(&self) -> Option<&'static str> {
+        match self {
+            SortBy::Added => None,
+            SortBy::Expiration => Some("COALESCE(expiration_date, 'infinity'::timestamptz)"),
+            SortBy::IngredientType => Some("ingredient_type"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// The order inventory is listed in. Ties are broken by the order the items were added in,
+/// so that pages can continue from the key of the last item seen.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Sort {
+    #[serde(default, rename = "sort")]
+    pub by: SortBy,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+impl Sort {
+    /// Only rows after the cursor in this order.
+    /// When sorting by an expression, the cursor's item must still exist for this to match anything.
+    fn push_after(&self, query: &mut QueryBuilder<'_, Postgres>, after: Option<sqlx::types::Uuid>) {
+        let Some(after) = after else {
+            return;
         };
 
-        Ok((inventory, pagination))
+        let comparison = match self.order {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        };
+        match self.by.expression() {
+            Some(expression) => {
+                query
+                    .push(format!(
+                        " AND ({expression}, inventory_id::uuid) {comparison} (SELECT {expression}, inventory_id::uuid FROM inventory WHERE inventory_id = "
+                    ))
+                    .push_bind(after)
+                    .push("::ulid)");
+            }
+            None => {
+                query
+                    .push(format!(" AND inventory_id::uuid {comparison} "))
+                    .push_bind(after);
+            }
+        }
+    }
+
+    fn push_order(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        let direction = match self.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+
+        query.push(" ORDER BY ");
+        if let Some(expression) = self.by.expression() {
+            query.push(format!("{expression} {direction}, "));
+        }
+        query.push(format!("inventory_id::uuid {direction}"));
     }
 }
 
 #[derive(Debug, Default)]
 pub struct Builder {
     inventory_id: Option<Ulid>,
+    owner_id: Option<Ulid>,
+    household_id: Option<Ulid>,
     ingredient_type: Option<String>,
     quantity: Option<String>,
     created_at: Option<DateTime<Utc>>,
@@ -135,6 +371,16 @@ impl Builder {
         self
     }
 
+    pub fn owner_id(mut self, owner_id: Ulid) -> Self {
+        self.owner_id = Some(owner_id);
+        self
+    }
+
+    pub fn household_id(mut self, household_id: Ulid) -> Self {
+        self.household_id = Some(household_id);
+        self
+    }
+
     pub fn ingredient_type(mut self, ingredient_type: String) -> Self {
         self.ingredient_type = Some(ingredient_type);
         self
@@ -164,6 +410,12 @@ impl crate::entity::Builder for Builder {
             .inventory_id
             .or_else(|| Some(Ulid::generate()))
             .unwrap();
+        let owner_id = self
+            .owner_id
+            .ok_or_else(|| Error::ModelFieldsMissing("owner_id"))?;
+        let household_id = self
+            .household_id
+            .ok_or_else(|| Error::ModelFieldsMissing("household_id"))?;
         let ingredient_type = self
             .ingredient_type
             .ok_or_else(|| Error::ModelFieldsMissing("ingredient_type"))?;
@@ -177,6 +429,8 @@ impl crate::entity::Builder for Builder {
 
         Ok(Inventory {
             inventory_id,
+            owner_id,
+            household_id,
             ingredient_type,
             quantity,
             created_at,
@@ -184,3 +438,50 @@ impl crate::entity::Builder for Builder {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conditions(filter: &Filter, sort: Sort, after: Option<Ulid>) -> String {
+        let mut query = QueryBuilder::<Postgres>::new("");
+        filter.push_conditions(&mut query);
+        sort.push_after(&mut query, after.as_ref().map(Ulid::to_sqlx_uuid));
+        sort.push_order(&mut query);
+        query.sql().to_string()
+    }
+
+    #[test]
+    fn matches_categories_by_prefix() {
+        let filter = Filter {
+            ingredient_type: Some("oil/*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            conditions(&filter, Sort::default(), None),
+            " AND (ingredient_type = $1 OR ingredient_type LIKE $2) ORDER BY inventory_id::uuid ASC"
+        );
+        assert_eq!(escape_like("50%_off"), "50\\%\\_off");
+    }
+
+    #[test]
+    fn continues_sorted_pages_after_the_cursor() {
+        let filter = Filter {
+            expiring_before: Some(Utc::now()),
+            created_after: Some(Utc::now()),
+            ..Default::default()
+        };
+        let sort = Sort {
+            by: SortBy::Expiration,
+            order: SortOrder::Desc,
+        };
+        let sql = conditions(&filter, sort, Some(Ulid::generate()));
+
+        assert!(sql.starts_with(" AND expiration_date < $1 AND created_at >= $2 AND ("));
+        assert!(sql.contains(") < (SELECT "));
+        assert!(sql.contains(" WHERE inventory_id = $3::ulid)"));
+        assert!(sql.ends_with(
+            " ORDER BY COALESCE(expiration_date, 'infinity'::timestamptz) DESC, inventory_id::uuid DESC"
+        ));
+    }
+}
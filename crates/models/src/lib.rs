@@ -1,19 +1,102 @@
 use serde::{Deserialize, Serialize};
 use sqlx_ulid::Ulid;
 
+pub mod cooking_log;
+pub mod dietary_profile;
 pub mod entity;
 pub mod error;
+pub mod household;
 pub mod inventory;
+pub mod meal_plan;
+pub mod nutrition;
+pub mod recipe;
+pub mod shopping_list;
+pub mod task;
+pub mod user;
 
+pub use user::User;
+
+/// A position in a list of rows ordered by their ULID keys.
+///
+/// When querying, `last_key` is the key of the last row already seen and `count` is the most
+/// rows to return, where zero means no limit. Queries return the pagination for the next page,
+/// with `last_key` set to the cursor to continue from if there are more rows.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Pagination {
     pub last_key: Option<Ulid>,
     pub count: usize,
 }
 
-#[derive(Debug)]
-pub struct User {
-    pub user_id: Option<Ulid>,
-    // pub user_id: Option<sqlx::types::Uuid>,
-    pub identifier: String,
+impl Pagination {
+    /// Every row, in a single page.
+    pub fn all() -> Self {
+        Self {
+            last_key: None,
+            count: 0,
+        }
+    }
+
+    /// The key rows must come after, bound as `($n::uuid IS NULL OR key::uuid > $n)`.
+    pub fn after(&self) -> Option<sqlx::types::Uuid> {
+        self.last_key.as_ref().map(Ulid::to_sqlx_uuid)
+    }
+
+    /// The number of rows to fetch, bound as `LIMIT $n`.
+    /// One more row than requested is fetched to tell whether there is a next page.
+    pub fn fetch_limit(&self) -> Option<i64> {
+        match self.count {
+            0 => None,
+            count => Some(count.saturating_add(1).min(i64::MAX as usize) as i64),
+        }
+    }
+
+    /// Trims the rows fetched with [`Pagination::fetch_limit`] to the requested page,
+    /// returning the page along with the pagination for the one after it.
+    pub fn page<T>(&self, mut rows: Vec<T>, key: impl Fn(&T) -> Ulid) -> (Vec<T>, Pagination) {
+        let more = self.count > 0 && rows.len() > self.count;
+        if more {
+            rows.truncate(self.count);
+        }
+
+        let pagination = Pagination {
+            last_key: rows.last().map(&key).filter(|_| more),
+            count: rows.len(),
+        };
+
+        (rows, pagination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_rows_after_the_cursor() {
+        let keys = (0..3).map(|_| Ulid::generate()).collect::<Vec<_>>();
+        let pagination = Pagination {
+            last_key: None,
+            count: 2,
+        };
+
+        let (page, next) = pagination.page(keys.clone(), |key| *key);
+        assert_eq!(page, keys[..2]);
+        assert_eq!(next.last_key, Some(keys[1]));
+        assert_eq!(next.count, 2);
+
+        let (page, next) = pagination.page(keys[..2].to_vec(), |key| *key);
+        assert_eq!(page, keys[..2]);
+        assert_eq!(next.last_key, None);
+    }
+
+    #[test]
+    fn fetches_every_row_without_a_limit() {
+        let pagination = Pagination::all();
+        assert_eq!(pagination.fetch_limit(), None);
+
+        let keys = (0..3).map(|_| Ulid::generate()).collect::<Vec<_>>();
+        let (page, next) = pagination.page(keys.clone(), |key| *key);
+        assert_eq!(page, keys);
+        assert_eq!(next.last_key, None);
+    }
 }
@@ -0,0 +1,327 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    encode::IsNull,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    types::chrono::{DateTime, NaiveDate, Utc},
+    Decode, Encode, Postgres,
+};
+use sqlx_ulid::Ulid;
+
+/// The meal of the day a recipe is planned for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MealSlot {
+    Breakfast,
+    Lunch,
+    Dinner,
+    Snack,
+}
+
+impl MealSlot {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MealSlot::Breakfast => "breakfast",
+            MealSlot::Lunch => "lunch",
+            MealSlot::Dinner => "dinner",
+            MealSlot::Snack => "snack",
+        }
+    }
+}
+
+impl std::str::FromStr for MealSlot {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "breakfast" => Ok(MealSlot::Breakfast),
+            "lunch" => Ok(MealSlot::Lunch),
+            "dinner" => Ok(MealSlot::Dinner),
+            "snack" => Ok(MealSlot::Snack),
+            _ => Err(Error::InvalidMealSlot(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for MealSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// meal slots are stored as text so that new ones can be added without altering the table
+impl sqlx::Type<Postgres> for MealSlot {
+    fn type_info() -> PgTypeInfo {
+        <&str as sqlx::Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <&str as sqlx::Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for MealSlot {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+impl Decode<'_, Postgres> for MealSlot {
+    fn decode(value: PgValueRef<'_>) -> std::result::Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(value.parse()?)
+    }
+}
+
+/// A recipe planned to be made for a meal on a given day.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MealPlan {
+    pub meal_plan_id: Ulid,
+    /// The user who planned the meal.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
+    pub date: NaiveDate,
+    pub meal: MealSlot,
+    pub recipe: String,
+    pub servings: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MealPlan {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    pub async fn by_id(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<Option<Self>> {
+        let meal_plan = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                meal_plan_id::uuid as meal_plan_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
+                date,
+                meal,
+                recipe,
+                servings,
+                created_at
+            FROM
+                meal_plan
+            WHERE
+                meal_plan_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(meal_plan)
+    }
+
+    pub async fn create(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO
+                meal_plan(
+                    meal_plan_id,
+                    owner_id,
+                    household_id,
+                    date,
+                    meal,
+                    recipe,
+                    servings,
+                    created_at
+                )
+            VALUES
+                ($1::uuid, $2::uuid, $3::uuid, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(self.meal_plan_id.to_sqlx_uuid())
+        .bind(self.owner_id.to_sqlx_uuid())
+        .bind(self.household_id.to_sqlx_uuid())
+        .bind(self.date)
+        .bind(self.meal)
+        .bind(&self.recipe)
+        .bind(self.servings)
+        .bind(self.created_at)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists the household's planned meals between the given dates, inclusive,
+    /// in the order they will be eaten.
+    pub async fn query(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+        from: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> Result<Vec<Self>> {
+        let meal_plans = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                meal_plan_id::uuid as meal_plan_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
+                date,
+                meal,
+                recipe,
+                servings,
+                created_at
+            FROM
+                meal_plan
+            WHERE
+                household_id = $1::ulid
+                AND ($2::date IS NULL OR date >= $2)
+                AND ($3::date IS NULL OR date <= $3)
+            ORDER BY
+                date,
+                CASE meal
+                    WHEN 'breakfast' THEN 0
+                    WHEN 'lunch' THEN 1
+                    WHEN 'dinner' THEN 2
+                    ELSE 3
+                END,
+                meal_plan_id
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(from)
+        .bind(until)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(meal_plans)
+    }
+
+    /// Saves changes to the planned meal, returning whether it existed.
+    pub async fn update(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                meal_plan
+            SET
+                date = $2,
+                meal = $3,
+                recipe = $4,
+                servings = $5
+            WHERE
+                meal_plan_id::uuid = $1
+            "#,
+        )
+        .bind(self.meal_plan_id.to_sqlx_uuid())
+        .bind(self.date)
+        .bind(self.meal)
+        .bind(&self.recipe)
+        .bind(self.servings)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes the planned meal, returning whether it existed.
+    pub async fn delete(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                meal_plan
+            WHERE
+                meal_plan_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Builder {
+    meal_plan_id: Option<Ulid>,
+    owner_id: Option<Ulid>,
+    household_id: Option<Ulid>,
+    date: Option<NaiveDate>,
+    meal: Option<MealSlot>,
+    recipe: Option<String>,
+    servings: Option<i32>,
+    created_at: Option<DateTime<Utc>>,
+}
+
+impl Builder {
+    pub fn meal_plan_id(mut self, meal_plan_id: Ulid) -> Self {
+        self.meal_plan_id = Some(meal_plan_id);
+        self
+    }
+
+    pub fn owner_id(mut self, owner_id: Ulid) -> Self {
+        self.owner_id = Some(owner_id);
+        self
+    }
+
+    pub fn household_id(mut self, household_id: Ulid) -> Self {
+        self.household_id = Some(household_id);
+        self
+    }
+
+    pub fn date(mut self, date: NaiveDate) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    pub fn meal(mut self, meal: MealSlot) -> Self {
+        self.meal = Some(meal);
+        self
+    }
+
+    pub fn recipe(mut self, recipe: String) -> Self {
+        self.recipe = Some(recipe);
+        self
+    }
+
+    pub fn servings(mut self, servings: i32) -> Self {
+        self.servings = Some(servings);
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+}
+
+impl crate::entity::Builder for Builder {
+    type Item = MealPlan;
+
+    fn build(self) -> Result<Self::Item> {
+        let meal_plan_id = self.meal_plan_id.unwrap_or_else(Ulid::generate);
+        let owner_id = self
+            .owner_id
+            .ok_or_else(|| Error::ModelFieldsMissing("owner_id"))?;
+        let household_id = self
+            .household_id
+            .ok_or_else(|| Error::ModelFieldsMissing("household_id"))?;
+        let date = self.date.ok_or_else(|| Error::ModelFieldsMissing("date"))?;
+        let meal = self.meal.ok_or_else(|| Error::ModelFieldsMissing("meal"))?;
+        let recipe = self
+            .recipe
+            .ok_or_else(|| Error::ModelFieldsMissing("recipe"))?;
+        let servings = self.servings.unwrap_or(1);
+        let created_at = self
+            .created_at
+            .ok_or_else(|| Error::ModelFieldsMissing("created_at"))?;
+
+        Ok(MealPlan {
+            meal_plan_id,
+            owner_id,
+            household_id,
+            date,
+            meal,
+            recipe,
+            servings,
+            created_at,
+        })
+    }
+}
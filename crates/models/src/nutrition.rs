@@ -0,0 +1,156 @@
+use crate::error::Result;
+use annapurna_data::nutrition::{Nutrients, Nutrition};
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+
+/// The nutrition of an ingredient, shared by every household.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngredientNutrition {
+    pub ingredient_type: String,
+    #[serde(flatten)]
+    pub nutrition: Nutrition,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct IngredientNutritionRow {
+    ingredient_type: String,
+    calories: f64,
+    protein: f64,
+    fat: f64,
+    saturated_fat: f64,
+    carbohydrates: f64,
+    sugar: f64,
+    fiber: f64,
+    sodium: f64,
+    calcium: f64,
+    iron: f64,
+    potassium: f64,
+    vitamin_c: f64,
+    piece_weight: Option<f64>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<IngredientNutritionRow> for IngredientNutrition {
+    fn from(row: IngredientNutritionRow) -> Self {
+        let per_100g = Nutrients {
+            calories: row.calories,
+            protein: row.protein,
+            fat: row.fat,
+            saturated_fat: row.saturated_fat,
+            carbohydrates: row.carbohydrates,
+            sugar: row.sugar,
+            fiber: row.fiber,
+            sodium: row.sodium,
+            calcium: row.calcium,
+            iron: row.iron,
+            potassium: row.potassium,
+            vitamin_c: row.vitamin_c,
+        };
+
+        Self {
+            ingredient_type: row.ingredient_type,
+            nutrition: Nutrition {
+                per_100g,
+                piece_weight: row.piece_weight,
+            },
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl IngredientNutrition {
+    pub fn new(ingredient_type: String, nutrition: Nutrition, updated_at: DateTime<Utc>) -> Self {
+        Self {
+            ingredient_type,
+            nutrition,
+            updated_at,
+        }
+    }
+
+    /// Lists the nutrition of every ingredient that has been imported, by name.
+    pub async fn all(pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<Vec<Self>> {
+        let rows = sqlx::query_as::<_, IngredientNutritionRow>(
+            r#"
+            SELECT
+                ingredient_type,
+                calories,
+                protein,
+                fat,
+                saturated_fat,
+                carbohydrates,
+                sugar,
+                fiber,
+                sodium,
+                calcium,
+                iron,
+                potassium,
+                vitamin_c,
+                piece_weight,
+                updated_at
+            FROM
+                ingredient_nutrition
+            ORDER BY
+                ingredient_type
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(IngredientNutrition::from).collect())
+    }
+
+    /// Stores the nutrition of each ingredient in a single transaction,
+    /// replacing what was known about them before.
+    pub async fn save_all(pool: &sqlx::pool::Pool<sqlx::Postgres>, items: &[Self]) -> Result<()> {
+        let mut transaction = pool.begin().await?;
+
+        for item in items {
+            let per_100g = &item.nutrition.per_100g;
+            sqlx::query(
+                r#"
+                INSERT INTO
+                    ingredient_nutrition(ingredient_type, calories, protein, fat, saturated_fat, carbohydrates, sugar, fiber, sodium, calcium, iron, potassium, vitamin_c, piece_weight, updated_at)
+                VALUES
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                ON CONFLICT (ingredient_type) DO UPDATE SET
+                    calories = EXCLUDED.calories,
+                    protein = EXCLUDED.protein,
+                    fat = EXCLUDED.fat,
+                    saturated_fat = EXCLUDED.saturated_fat,
+                    carbohydrates = EXCLUDED.carbohydrates,
+                    sugar = EXCLUDED.sugar,
+                    fiber = EXCLUDED.fiber,
+                    sodium = EXCLUDED.sodium,
+                    calcium = EXCLUDED.calcium,
+                    iron = EXCLUDED.iron,
+                    potassium = EXCLUDED.potassium,
+                    vitamin_c = EXCLUDED.vitamin_c,
+                    piece_weight = EXCLUDED.piece_weight,
+                    updated_at = EXCLUDED.updated_at
+                "#,
+            )
+            .bind(&item.ingredient_type)
+            .bind(per_100g.calories)
+            .bind(per_100g.protein)
+            .bind(per_100g.fat)
+            .bind(per_100g.saturated_fat)
+            .bind(per_100g.carbohydrates)
+            .bind(per_100g.sugar)
+            .bind(per_100g.fiber)
+            .bind(per_100g.sodium)
+            .bind(per_100g.calcium)
+            .bind(per_100g.iron)
+            .bind(per_100g.potassium)
+            .bind(per_100g.vitamin_c)
+            .bind(item.nutrition.piece_weight)
+            .bind(item.updated_at)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
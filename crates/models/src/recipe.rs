@@ -0,0 +1,577 @@
+use crate::{
+    error::{Error, Result},
+    task::interval_duration,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    postgres::types::PgInterval,
+    types::chrono::{DateTime, Utc},
+};
+use sqlx_ulid::Ulid;
+use std::{collections::HashMap, time::Duration};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Recipe {
+    pub recipe_id: Ulid,
+    /// The household the recipe belongs to, or none if it is part of the shared catalogue.
+    pub household_id: Option<Ulid>,
+    pub name: String,
+    /// The number of servings the recipe makes.
+    pub servings: i32,
+    pub created_at: DateTime<Utc>,
+    pub ingredients: Vec<RecipeIngredient>,
+    #[serde(default)]
+    pub steps: Vec<RecipeStep>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RecipeIngredient {
+    pub ingredient_type: String,
+    /// The amount the recipe needs, if it specifies one.
+    pub quantity: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecipeStep {
+    pub description: String,
+    pub duration: Duration,
+    /// The positions of the steps that must be finished first.
+    /// When missing, the step follows the one before it.
+    #[serde(default)]
+    pub after: Option<Vec<usize>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RecipeRow {
+    recipe_id: Ulid,
+    household_id: Option<Ulid>,
+    name: String,
+    servings: i32,
+    created_at: DateTime<Utc>,
+}
+
+impl RecipeRow {
+    fn with_ingredients(
+        self,
+        ingredients: Vec<RecipeIngredient>,
+        steps: Vec<RecipeStep>,
+    ) -> Recipe {
+        Recipe {
+            recipe_id: self.recipe_id,
+            household_id: self.household_id,
+            name: self.name,
+            servings: self.servings,
+            created_at: self.created_at,
+            ingredients,
+            steps,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RecipeIngredientRow {
+    recipe_id: Ulid,
+    #[sqlx(flatten)]
+    ingredient: RecipeIngredient,
+}
+
+#[derive(sqlx::FromRow)]
+struct RecipeStepRow {
+    recipe_id: Ulid,
+    description: String,
+    duration: PgInterval,
+    after_steps: Option<Vec<i32>>,
+}
+
+impl Recipe {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    pub async fn by_id(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<Option<Self>> {
+        let recipe = sqlx::query_as::<_, RecipeRow>(
+            r#"
+            SELECT
+                recipe_id::uuid as recipe_id,
+                household_id::uuid as household_id,
+                name,
+                servings,
+                created_at
+            FROM
+                recipe
+            WHERE
+                recipe_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        Self::with_ingredients(pool, recipe).await
+    }
+
+    /// Finds a recipe the household can see by name, preferring the household's own recipe
+    /// over one of the same name in the shared catalogue.
+    pub async fn by_name(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+        name: &str,
+    ) -> Result<Option<Self>> {
+        let recipe = sqlx::query_as::<_, RecipeRow>(
+            r#"
+            SELECT
+                recipe_id::uuid as recipe_id,
+                household_id::uuid as household_id,
+                name,
+                servings,
+                created_at
+            FROM
+                recipe
+            WHERE
+                name = $1
+                AND (household_id = $2::ulid OR household_id IS NULL)
+            ORDER BY
+                household_id NULLS LAST
+            LIMIT 1
+            "#,
+        )
+        .bind(name)
+        .bind(household_id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        Self::with_ingredients(pool, recipe).await
+    }
+
+    async fn with_ingredients(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        recipe: Option<RecipeRow>,
+    ) -> Result<Option<Self>> {
+        match recipe {
+            Some(recipe) => {
+                let ingredients = RecipeIngredient::for_recipes(pool, &[recipe.recipe_id])
+                    .await?
+                    .remove(&recipe.recipe_id)
+                    .unwrap_or_default();
+                let steps = RecipeStep::for_recipes(pool, &[recipe.recipe_id])
+                    .await?
+                    .remove(&recipe.recipe_id)
+                    .unwrap_or_default();
+                Ok(Some(recipe.with_ingredients(ingredients, steps)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Creates the recipe along with its ingredients and steps.
+    pub async fn create(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<()> {
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO
+                recipe(recipe_id, household_id, name, servings, created_at)
+            VALUES
+                ($1::uuid, $2::uuid, $3, $4, $5)
+            "#,
+        )
+        .bind(self.recipe_id.to_sqlx_uuid())
+        .bind(self.household_id.as_ref().map(Ulid::to_sqlx_uuid))
+        .bind(&self.name)
+        .bind(self.servings)
+        .bind(self.created_at)
+        .execute(&mut *transaction)
+        .await?;
+
+        self.insert_ingredients(&mut transaction).await?;
+        self.insert_steps(&mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Lists a page of the shared catalogue along with the household's own recipes, if one is
+    /// given, in the order they were added.
+    /// A household's recipe takes the place of the catalogue's recipe of the same name.
+    pub async fn query(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: Option<&Ulid>,
+        pagination: crate::Pagination,
+    ) -> Result<(Vec<Self>, crate::Pagination)> {
+        let recipes = sqlx::query_as::<_, RecipeRow>(
+            r#"
+            SELECT
+                recipe_id,
+                household_id,
+                name,
+                servings,
+                created_at
+            FROM (
+                SELECT DISTINCT ON (name)
+                    recipe_id::uuid as recipe_id,
+                    household_id::uuid as household_id,
+                    name,
+                    servings,
+                    created_at
+                FROM
+                    recipe
+                WHERE
+                    household_id IS NULL
+                    OR household_id = $1::ulid
+                ORDER BY
+                    name,
+                    household_id NULLS LAST
+            ) AS visible
+            WHERE
+                $2::uuid IS NULL OR recipe_id > $2
+            ORDER BY
+                recipe_id
+            LIMIT $3
+            "#,
+        )
+        .bind(household_id.map(Ulid::to_sqlx_uuid))
+        .bind(pagination.after())
+        .bind(pagination.fetch_limit())
+        .fetch_all(pool)
+        .await?;
+        let (recipes, next) = pagination.page(recipes, |recipe| recipe.recipe_id);
+
+        let ids = recipes
+            .iter()
+            .map(|recipe| recipe.recipe_id)
+            .collect::<Vec<_>>();
+        let mut ingredients = RecipeIngredient::for_recipes(pool, &ids).await?;
+        let mut steps = RecipeStep::for_recipes(pool, &ids).await?;
+
+        let recipes = recipes
+            .into_iter()
+            .map(|recipe| {
+                let recipe_ingredients = ingredients.remove(&recipe.recipe_id).unwrap_or_default();
+                let recipe_steps = steps.remove(&recipe.recipe_id).unwrap_or_default();
+                recipe.with_ingredients(recipe_ingredients, recipe_steps)
+            })
+            .collect::<Vec<_>>();
+
+        Ok((recipes, next))
+    }
+
+    /// Saves changes to the recipe, replacing all of its ingredients and steps.
+    /// Returns whether the recipe existed.
+    pub async fn update(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<bool> {
+        let mut transaction = pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                recipe
+            SET
+                name = $2,
+                servings = $3
+            WHERE
+                recipe_id::uuid = $1
+            "#,
+        )
+        .bind(self.recipe_id.to_sqlx_uuid())
+        .bind(&self.name)
+        .bind(self.servings)
+        .execute(&mut *transaction)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            DELETE FROM
+                recipe_ingredient
+            WHERE
+                recipe_id::uuid = $1
+            "#,
+        )
+        .bind(self.recipe_id.to_sqlx_uuid())
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM
+                recipe_step
+            WHERE
+                recipe_id::uuid = $1
+            "#,
+        )
+        .bind(self.recipe_id.to_sqlx_uuid())
+        .execute(&mut *transaction)
+        .await?;
+
+        self.insert_ingredients(&mut transaction).await?;
+        self.insert_steps(&mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Deletes the recipe along with its ingredients and steps, returning whether it existed.
+    pub async fn delete(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                recipe
+            WHERE
+                recipe_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn insert_ingredients(&self, conn: &mut sqlx::PgConnection) -> Result<()> {
+        for (position, ingredient) in self.ingredients.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO
+                    recipe_ingredient(recipe_ingredient_id, recipe_id, position, ingredient_type, quantity)
+                VALUES
+                    ($1::uuid, $2::uuid, $3, $4, $5)
+                "#,
+            )
+            .bind(Ulid::generate().to_sqlx_uuid())
+            .bind(self.recipe_id.to_sqlx_uuid())
+            .bind(position as i32)
+            .bind(&ingredient.ingredient_type)
+            .bind(&ingredient.quantity)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn insert_steps(&self, conn: &mut sqlx::PgConnection) -> Result<()> {
+        for (position, step) in self.steps.iter().enumerate() {
+            let after = step.after.as_ref().map(|after| {
+                after
+                    .iter()
+                    .map(|step| (*step).min(i32::MAX as usize) as i32)
+                    .collect::<Vec<_>>()
+            });
+
+            sqlx::query(
+                r#"
+                INSERT INTO
+                    recipe_step(recipe_step_id, recipe_id, position, description, duration, after_steps)
+                VALUES
+                    ($1::uuid, $2::uuid, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(Ulid::generate().to_sqlx_uuid())
+            .bind(self.recipe_id.to_sqlx_uuid())
+            .bind(position as i32)
+            .bind(&step.description)
+            .bind(step.duration)
+            .bind(after)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl RecipeIngredient {
+    pub fn new(ingredient_type: String, quantity: Option<String>) -> Self {
+        Self {
+            ingredient_type,
+            quantity,
+        }
+    }
+
+    /// Lists every ingredient type used by the shared catalogue or the household's recipes.
+    pub async fn types(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+    ) -> Result<Vec<String>> {
+        let types = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT DISTINCT
+                recipe_ingredient.ingredient_type
+            FROM
+                recipe_ingredient
+                JOIN recipe ON recipe.recipe_id = recipe_ingredient.recipe_id
+            WHERE
+                recipe.household_id IS NULL
+                OR recipe.household_id = $1::ulid
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .fetch_all(pool)
+        .await?;
+
+        Ok(types)
+    }
+
+    /// Lists the ingredients of each of the recipes, in the order they were given.
+    async fn for_recipes(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        ids: &[Ulid],
+    ) -> Result<HashMap<Ulid, Vec<Self>>> {
+        let ids = ids.iter().map(Ulid::to_sqlx_uuid).collect::<Vec<_>>();
+
+        let rows = sqlx::query_as::<_, RecipeIngredientRow>(
+            r#"
+            SELECT
+                recipe_id::uuid as recipe_id,
+                ingredient_type,
+                quantity
+            FROM
+                recipe_ingredient
+            WHERE
+                recipe_id::uuid = ANY($1)
+            ORDER BY
+                position
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+
+        let mut ingredients = HashMap::<Ulid, Vec<Self>>::new();
+        for row in rows {
+            ingredients
+                .entry(row.recipe_id)
+                .or_default()
+                .push(row.ingredient);
+        }
+
+        Ok(ingredients)
+    }
+}
+
+impl RecipeStep {
+    pub fn new(description: String, duration: Duration, after: Option<Vec<usize>>) -> Self {
+        Self {
+            description,
+            duration,
+            after,
+        }
+    }
+
+    /// Lists the steps of each of the recipes, in order.
+    async fn for_recipes(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        ids: &[Ulid],
+    ) -> Result<HashMap<Ulid, Vec<Self>>> {
+        let ids = ids.iter().map(Ulid::to_sqlx_uuid).collect::<Vec<_>>();
+
+        let rows = sqlx::query_as::<_, RecipeStepRow>(
+            r#"
+            SELECT
+                recipe_id::uuid as recipe_id,
+                description,
+                duration,
+                after_steps
+            FROM
+                recipe_step
+            WHERE
+                recipe_id::uuid = ANY($1)
+            ORDER BY
+                position
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+
+        let mut steps = HashMap::<Ulid, Vec<Self>>::new();
+        for row in rows {
+            let after = row
+                .after_steps
+                .map(|after| after.into_iter().map(|step| step.max(0) as usize).collect());
+            steps.entry(row.recipe_id).or_default().push(RecipeStep {
+                description: row.description,
+                duration: interval_duration(row.duration),
+                after,
+            });
+        }
+
+        Ok(steps)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Builder {
+    recipe_id: Option<Ulid>,
+    household_id: Option<Ulid>,
+    name: Option<String>,
+    servings: Option<i32>,
+    created_at: Option<DateTime<Utc>>,
+    ingredients: Vec<RecipeIngredient>,
+    steps: Vec<RecipeStep>,
+}
+
+impl Builder {
+    pub fn recipe_id(mut self, recipe_id: Ulid) -> Self {
+        self.recipe_id = Some(recipe_id);
+        self
+    }
+
+    pub fn name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn servings(mut self, servings: i32) -> Self {
+        self.servings = Some(servings);
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    pub fn ingredient(mut self, ingredient_type: String, quantity: Option<String>) -> Self {
+        self.ingredients
+            .push(RecipeIngredient::new(ingredient_type, quantity));
+        self
+    }
+
+    pub fn step(mut self, step: RecipeStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn household_id(mut self, household_id: Option<Ulid>) -> Self {
+        self.household_id = household_id;
+        self
+    }
+}
+
+impl crate::entity::Builder for Builder {
+    type Item = Recipe;
+
+    fn build(self) -> Result<Self::Item> {
+        let recipe_id = self.recipe_id.unwrap_or_else(Ulid::generate);
+        let name = self.name.ok_or_else(|| Error::ModelFieldsMissing("name"))?;
+        let servings = self.servings.unwrap_or(1);
+        let created_at = self
+            .created_at
+            .ok_or_else(|| Error::ModelFieldsMissing("created_at"))?;
+
+        Ok(Recipe {
+            recipe_id,
+            household_id: self.household_id,
+            name,
+            servings,
+            created_at,
+            ingredients: self.ingredients,
+            steps: self.steps,
+        })
+    }
+}
@@ -0,0 +1,356 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx_ulid::Ulid;
+use std::collections::HashMap;
+
+/// A list of ingredients to buy in order to make a selection of recipes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShoppingList {
+    pub shopping_list_id: Ulid,
+    /// The user who created the list.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
+    pub name: String,
+    /// The recipes the list was generated for.
+    pub recipes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub items: Vec<ShoppingListItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ShoppingListItem {
+    pub shopping_list_item_id: Ulid,
+    pub shopping_list_id: Ulid,
+    pub ingredient_type: String,
+    /// The amount to buy, if it is known.
+    pub quantity: Option<String>,
+    /// Whether the item has been bought.
+    pub checked: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct ShoppingListRow {
+    shopping_list_id: Ulid,
+    owner_id: Ulid,
+    household_id: Ulid,
+    name: String,
+    recipes: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl ShoppingListRow {
+    fn with_items(self, items: Vec<ShoppingListItem>) -> ShoppingList {
+        ShoppingList {
+            shopping_list_id: self.shopping_list_id,
+            owner_id: self.owner_id,
+            household_id: self.household_id,
+            name: self.name,
+            recipes: self.recipes,
+            created_at: self.created_at,
+            items,
+        }
+    }
+}
+
+impl ShoppingList {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    pub async fn by_id(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<Option<Self>> {
+        let list = sqlx::query_as::<_, ShoppingListRow>(
+            r#"
+            SELECT
+                shopping_list_id::uuid as shopping_list_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
+                name,
+                recipes,
+                created_at
+            FROM
+                shopping_list
+            WHERE
+                shopping_list_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        match list {
+            Some(list) => {
+                let items = ShoppingListItem::for_lists(pool, &[*id]).await?;
+                Ok(Some(list.with_items(items)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Creates the list along with all of its items.
+    pub async fn create(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<()> {
+        let mut transaction = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO
+                shopping_list(shopping_list_id, owner_id, household_id, name, recipes, created_at)
+            VALUES
+                ($1::uuid, $2::uuid, $3::uuid, $4, $5, $6)
+            "#,
+        )
+        .bind(self.shopping_list_id.to_sqlx_uuid())
+        .bind(self.owner_id.to_sqlx_uuid())
+        .bind(self.household_id.to_sqlx_uuid())
+        .bind(&self.name)
+        .bind(&self.recipes)
+        .bind(self.created_at)
+        .execute(&mut *transaction)
+        .await?;
+
+        for item in &self.items {
+            sqlx::query(
+                r#"
+                INSERT INTO
+                    shopping_list_item(shopping_list_item_id, shopping_list_id, ingredient_type, quantity, checked)
+                VALUES
+                    ($1::uuid, $2::uuid, $3, $4, $5)
+                "#,
+            )
+            .bind(item.shopping_list_item_id.to_sqlx_uuid())
+            .bind(self.shopping_list_id.to_sqlx_uuid())
+            .bind(&item.ingredient_type)
+            .bind(&item.quantity)
+            .bind(item.checked)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Lists a page of the shopping lists belonging to the household, in the order they were made.
+    pub async fn query(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+        pagination: crate::Pagination,
+    ) -> Result<(Vec<Self>, crate::Pagination)> {
+        let lists = sqlx::query_as::<_, ShoppingListRow>(
+            r#"
+            SELECT
+                shopping_list_id::uuid as shopping_list_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
+                name,
+                recipes,
+                created_at
+            FROM
+                shopping_list
+            WHERE
+                household_id = $1::ulid
+                AND ($2::uuid IS NULL OR shopping_list_id::uuid > $2)
+            ORDER BY
+                shopping_list_id
+            LIMIT $3
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(pagination.after())
+        .bind(pagination.fetch_limit())
+        .fetch_all(pool)
+        .await?;
+        let (lists, next) = pagination.page(lists, |list| list.shopping_list_id);
+
+        let ids = lists
+            .iter()
+            .map(|list| list.shopping_list_id)
+            .collect::<Vec<_>>();
+        let mut items = HashMap::<Ulid, Vec<ShoppingListItem>>::new();
+        for item in ShoppingListItem::for_lists(pool, &ids).await? {
+            items.entry(item.shopping_list_id).or_default().push(item);
+        }
+
+        let lists = lists
+            .into_iter()
+            .map(|list| {
+                let list_items = items.remove(&list.shopping_list_id).unwrap_or_default();
+                list.with_items(list_items)
+            })
+            .collect::<Vec<_>>();
+
+        Ok((lists, next))
+    }
+
+    /// Deletes the list and its items, returning whether it existed.
+    pub async fn delete(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                shopping_list
+            WHERE
+                shopping_list_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl ShoppingListItem {
+    pub fn new(shopping_list_id: Ulid, ingredient_type: String, quantity: Option<String>) -> Self {
+        Self {
+            shopping_list_item_id: Ulid::generate(),
+            shopping_list_id,
+            ingredient_type,
+            quantity,
+            checked: false,
+        }
+    }
+
+    async fn for_lists(pool: &sqlx::pool::Pool<sqlx::Postgres>, ids: &[Ulid]) -> Result<Vec<Self>> {
+        let ids = ids.iter().map(Ulid::to_sqlx_uuid).collect::<Vec<_>>();
+
+        let items = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                shopping_list_item_id::uuid as shopping_list_item_id,
+                shopping_list_id::uuid as shopping_list_id,
+                ingredient_type,
+                quantity,
+                checked
+            FROM
+                shopping_list_item
+            WHERE
+                shopping_list_id::uuid = ANY($1)
+            ORDER BY
+                ingredient_type
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(items)
+    }
+
+    /// Checks an item on a list off, or back on, returning the updated item if it exists.
+    pub async fn set_checked(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        shopping_list_id: &Ulid,
+        shopping_list_item_id: &Ulid,
+        checked: bool,
+    ) -> Result<Option<Self>> {
+        let item = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE
+                shopping_list_item
+            SET
+                checked = $3
+            WHERE
+                shopping_list_id::uuid = $1
+                AND shopping_list_item_id::uuid = $2
+            RETURNING
+                shopping_list_item_id::uuid as shopping_list_item_id,
+                shopping_list_id::uuid as shopping_list_id,
+                ingredient_type,
+                quantity,
+                checked
+            "#,
+        )
+        .bind(shopping_list_id.to_sqlx_uuid())
+        .bind(shopping_list_item_id.to_sqlx_uuid())
+        .bind(checked)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(item)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Builder {
+    shopping_list_id: Option<Ulid>,
+    owner_id: Option<Ulid>,
+    household_id: Option<Ulid>,
+    name: Option<String>,
+    recipes: Vec<String>,
+    created_at: Option<DateTime<Utc>>,
+    items: Vec<(String, Option<String>)>,
+}
+
+impl Builder {
+    pub fn shopping_list_id(mut self, shopping_list_id: Ulid) -> Self {
+        self.shopping_list_id = Some(shopping_list_id);
+        self
+    }
+
+    pub fn owner_id(mut self, owner_id: Ulid) -> Self {
+        self.owner_id = Some(owner_id);
+        self
+    }
+
+    pub fn household_id(mut self, household_id: Ulid) -> Self {
+        self.household_id = Some(household_id);
+        self
+    }
+
+    pub fn name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn recipes(mut self, recipes: Vec<String>) -> Self {
+        self.recipes = recipes;
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    pub fn item(mut self, ingredient_type: String, quantity: Option<String>) -> Self {
+        self.items.push((ingredient_type, quantity));
+        self
+    }
+}
+
+impl crate::entity::Builder for Builder {
+    type Item = ShoppingList;
+
+    fn build(self) -> Result<Self::Item> {
+        let shopping_list_id = self.shopping_list_id.unwrap_or_else(Ulid::generate);
+        let owner_id = self
+            .owner_id
+            .ok_or_else(|| Error::ModelFieldsMissing("owner_id"))?;
+        let household_id = self
+            .household_id
+            .ok_or_else(|| Error::ModelFieldsMissing("household_id"))?;
+        let name = self.name.ok_or_else(|| Error::ModelFieldsMissing("name"))?;
+        let created_at = self
+            .created_at
+            .ok_or_else(|| Error::ModelFieldsMissing("created_at"))?;
+        let items = self
+            .items
+            .into_iter()
+            .map(|(ingredient_type, quantity)| {
+                ShoppingListItem::new(shopping_list_id, ingredient_type, quantity)
+            })
+            .collect();
+
+        Ok(ShoppingList {
+            shopping_list_id,
+            owner_id,
+            household_id,
+            name,
+            recipes: self.recipes,
+            created_at,
+            items,
+        })
+    }
+}
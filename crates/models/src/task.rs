@@ -1,14 +1,433 @@
+use crate::error::{Error, Result};
+use annapurna_data::types::Recurrence;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::postgres::types::PgInterval;
 use sqlx_ulid::Ulid;
 use std::time::Duration;
 
+/// Something to do, on a household's todo list.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Task {
     pub task_id: Ulid,
+    /// The user who added the task.
+    pub owner_id: Ulid,
+    pub household_id: Ulid,
     pub description: String,
     pub completed: bool,
+    /// How long the task takes.
     pub duration: Option<Duration>,
     pub start_time: Option<DateTime<Utc>>,
     pub completion_time: Option<DateTime<Utc>>,
+    /// Recurring tasks are completed one occurrence at a time, through [`TaskOccurrence`].
+    pub recurrence: Option<Recurrence>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TaskRow {
+    task_id: Ulid,
+    owner_id: Ulid,
+    household_id: Ulid,
+    description: String,
+    completed: bool,
+    duration: Option<PgInterval>,
+    start_time: Option<DateTime<Utc>>,
+    completion_time: Option<DateTime<Utc>>,
+    // recurrences are stored as jsonb, and read back as text
+    recurrence: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<TaskRow> for Task {
+    type Error = Error;
+
+    fn try_from(row: TaskRow) -> Result<Self> {
+        let recurrence = row
+            .recurrence
+            .map(|recurrence| serde_json::from_str(&recurrence))
+            .transpose()?;
+
+        Ok(Self {
+            task_id: row.task_id,
+            owner_id: row.owner_id,
+            household_id: row.household_id,
+            description: row.description,
+            completed: row.completed,
+            duration: row.duration.map(interval_duration),
+            start_time: row.start_time,
+            completion_time: row.completion_time,
+            recurrence,
+            created_at: row.created_at,
+        })
+    }
+}
+
+/// Converts an interval to a duration, counting months as 30 days.
+/// Durations are written as microseconds, so only intervals edited by hand have days or months.
+pub(crate) fn interval_duration(interval: PgInterval) -> Duration {
+    const MICROS_PER_DAY: i64 = 24 * 60 * 60 * 1_000_000;
+    let days = i64::from(interval.months) * 30 + i64::from(interval.days);
+    let micros = days * MICROS_PER_DAY + interval.microseconds;
+
+    Duration::from_micros(micros.max(0) as u64)
+}
+
+impl Task {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    pub async fn by_id(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<Option<Self>> {
+        let task = sqlx::query_as::<_, TaskRow>(
+            r#"
+            SELECT
+                task_id::uuid as task_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
+                description,
+                completed,
+                duration,
+                start_time,
+                completion_time,
+                recurrence::text as recurrence,
+                created_at
+            FROM
+                task
+            WHERE
+                task_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .fetch_optional(pool)
+        .await?;
+
+        task.map(Task::try_from).transpose()
+    }
+
+    pub async fn create<'c, E: sqlx::PgExecutor<'c>>(&self, executor: E) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO
+                task(task_id, owner_id, household_id, description, completed, duration, start_time, completion_time, recurrence, created_at)
+            VALUES
+                ($1::uuid, $2::uuid, $3::uuid, $4, $5, $6, $7, $8, $9::jsonb, $10)
+            "#,
+        )
+        .bind(self.task_id.to_sqlx_uuid())
+        .bind(self.owner_id.to_sqlx_uuid())
+        .bind(self.household_id.to_sqlx_uuid())
+        .bind(&self.description)
+        .bind(self.completed)
+        .bind(self.duration)
+        .bind(self.start_time)
+        .bind(self.completion_time)
+        .bind(self.recurrence_json()?)
+        .bind(self.created_at)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists a page of the household's tasks, in the order they were added.
+    pub async fn query(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+        pagination: crate::Pagination,
+    ) -> Result<(Vec<Self>, crate::Pagination)> {
+        let rows = sqlx::query_as::<_, TaskRow>(
+            r#"
+            SELECT
+                task_id::uuid as task_id,
+                owner_id::uuid as owner_id,
+                household_id::uuid as household_id,
+                description,
+                completed,
+                duration,
+                start_time,
+                completion_time,
+                recurrence::text as recurrence,
+                created_at
+            FROM
+                task
+            WHERE
+                household_id = $1::ulid
+                AND ($2::uuid IS NULL OR task_id::uuid > $2)
+            ORDER BY
+                task_id
+            LIMIT $3
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(pagination.after())
+        .bind(pagination.fetch_limit())
+        .fetch_all(pool)
+        .await?;
+
+        let tasks = rows
+            .into_iter()
+            .map(Task::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(pagination.page(tasks, |task: &Task| task.task_id))
+    }
+
+    /// Saves changes to the task, returning whether it existed.
+    pub async fn update(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE
+                task
+            SET
+                description = $2,
+                completed = $3,
+                duration = $4,
+                start_time = $5,
+                completion_time = $6,
+                recurrence = $7::jsonb
+            WHERE
+                task_id::uuid = $1
+            "#,
+        )
+        .bind(self.task_id.to_sqlx_uuid())
+        .bind(&self.description)
+        .bind(self.completed)
+        .bind(self.duration)
+        .bind(self.start_time)
+        .bind(self.completion_time)
+        .bind(self.recurrence_json()?)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn recurrence_json(&self) -> Result<Option<String>> {
+        Ok(self
+            .recurrence
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?)
+    }
+
+    /// Marks the task as done at the given time, or as not done when `None`.
+    pub fn complete(&mut self, completion_time: Option<DateTime<Utc>>) {
+        self.completed = completion_time.is_some();
+        self.completion_time = completion_time;
+    }
+
+    /// Deletes the task, returning whether it existed.
+    pub async fn delete(pool: &sqlx::pool::Pool<sqlx::Postgres>, id: &Ulid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                task
+            WHERE
+                task_id::uuid = $1
+            "#,
+        )
+        .bind(id.to_sqlx_uuid())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// A completed occurrence of a recurring task, keyed by the moment it was scheduled for.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TaskOccurrence {
+    pub task_id: Ulid,
+    pub scheduled_at: DateTime<Utc>,
+    pub completion_time: DateTime<Utc>,
+}
+
+impl TaskOccurrence {
+    /// Lists the completed occurrences of the household's tasks scheduled between `from` and `until`.
+    pub async fn query(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        household_id: &Ulid,
+        from: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Self>> {
+        let occurrences = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                task_occurrence.task_id::uuid as task_id,
+                task_occurrence.scheduled_at,
+                task_occurrence.completion_time
+            FROM
+                task_occurrence
+                JOIN task ON task.task_id = task_occurrence.task_id
+            WHERE
+                task.household_id = $1::ulid
+                AND ($2::timestamptz IS NULL OR task_occurrence.scheduled_at >= $2)
+                AND ($3::timestamptz IS NULL OR task_occurrence.scheduled_at <= $3)
+            ORDER BY
+                task_occurrence.scheduled_at,
+                task_occurrence.task_id
+            "#,
+        )
+        .bind(household_id.to_sqlx_uuid())
+        .bind(from)
+        .bind(until)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(occurrences)
+    }
+
+    /// Records the occurrence as completed, replacing the completion time if it already was.
+    pub async fn save(&self, pool: &sqlx::pool::Pool<sqlx::Postgres>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO
+                task_occurrence(task_id, scheduled_at, completion_time)
+            VALUES
+                ($1::uuid, $2, $3)
+            ON CONFLICT (task_id, scheduled_at) DO UPDATE SET
+                completion_time = EXCLUDED.completion_time
+            "#,
+        )
+        .bind(self.task_id.to_sqlx_uuid())
+        .bind(self.scheduled_at)
+        .bind(self.completion_time)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks the occurrence as not completed, returning whether it was.
+    pub async fn delete(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        task_id: &Ulid,
+        scheduled_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM
+                task_occurrence
+            WHERE
+                task_id::uuid = $1
+                AND scheduled_at = $2
+            "#,
+        )
+        .bind(task_id.to_sqlx_uuid())
+        .bind(scheduled_at)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Builder {
+    task_id: Option<Ulid>,
+    owner_id: Option<Ulid>,
+    household_id: Option<Ulid>,
+    description: Option<String>,
+    duration: Option<Duration>,
+    start_time: Option<DateTime<Utc>>,
+    recurrence: Option<Recurrence>,
+    created_at: Option<DateTime<Utc>>,
+}
+
+impl Builder {
+    pub fn task_id(mut self, task_id: Ulid) -> Self {
+        self.task_id = Some(task_id);
+        self
+    }
+
+    pub fn owner_id(mut self, owner_id: Ulid) -> Self {
+        self.owner_id = Some(owner_id);
+        self
+    }
+
+    pub fn household_id(mut self, household_id: Ulid) -> Self {
+        self.household_id = Some(household_id);
+        self
+    }
+
+    pub fn description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    pub fn duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn start_time(mut self, start_time: Option<DateTime<Utc>>) -> Self {
+        self.start_time = start_time;
+        self
+    }
+
+    pub fn recurrence(mut self, recurrence: Option<Recurrence>) -> Self {
+        self.recurrence = recurrence;
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+}
+
+impl crate::entity::Builder for Builder {
+    type Item = Task;
+
+    fn build(self) -> Result<Self::Item> {
+        let task_id = self.task_id.unwrap_or_else(Ulid::generate);
+        let owner_id = self
+            .owner_id
+            .ok_or_else(|| Error::ModelFieldsMissing("owner_id"))?;
+        let household_id = self
+            .household_id
+            .ok_or_else(|| Error::ModelFieldsMissing("household_id"))?;
+        let description = self
+            .description
+            .ok_or_else(|| Error::ModelFieldsMissing("description"))?;
+        let created_at = self
+            .created_at
+            .ok_or_else(|| Error::ModelFieldsMissing("created_at"))?;
+
+        Ok(Task {
+            task_id,
+            owner_id,
+            household_id,
+            description,
+            completed: false,
+            duration: self.duration,
+            start_time: self.start_time,
+            completion_time: None,
+            recurrence: self.recurrence,
+            created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_intervals_to_durations() {
+        let interval = PgInterval {
+            months: 0,
+            days: 1,
+            microseconds: 90_000_000,
+        };
+
+        assert_eq!(
+            interval_duration(interval),
+            Duration::from_secs(24 * 60 * 60 + 90)
+        );
+        assert_eq!(
+            interval_duration(PgInterval::try_from(Duration::from_secs(600)).unwrap()),
+            Duration::from_secs(600)
+        );
+    }
 }
@@ -0,0 +1,65 @@
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use sqlx_ulid::Ulid;
+
+/// Someone who owns data, identified by the subject of their auth token.
+#[derive(Clone, Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct User {
+    pub user_id: Ulid,
+    pub identifier: String,
+}
+
+impl User {
+    pub async fn by_identifier(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        identifier: &str,
+    ) -> Result<Option<Self>> {
+        let user = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT
+                user_id::uuid as user_id,
+                identifier
+            FROM
+                users
+            WHERE
+                identifier = $1
+            "#,
+        )
+        .bind(identifier)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Finds the user with the identifier, creating them the first time they are seen.
+    pub async fn find_or_create(
+        pool: &sqlx::pool::Pool<sqlx::Postgres>,
+        identifier: &str,
+    ) -> Result<Self> {
+        if let Some(user) = Self::by_identifier(pool, identifier).await? {
+            return Ok(user);
+        }
+
+        // another request may have created the user in the meantime
+        let user = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO
+                users(user_id, identifier)
+            VALUES
+                ($1::uuid, $2)
+            ON CONFLICT (identifier) DO UPDATE
+                SET identifier = EXCLUDED.identifier
+            RETURNING
+                user_id::uuid as user_id,
+                identifier
+            "#,
+        )
+        .bind(Ulid::generate().to_sqlx_uuid())
+        .bind(identifier)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(user)
+    }
+}
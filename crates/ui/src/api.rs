@@ -1,40 +1,242 @@
 use crate::state::AppState;
-use annapurna_data::types::{Ingredient, Recipe};
+use annapurna_client::{
+    error::Result,
+    types::{
+        CompleteOccurrence, CreateRecipe, CreateTask, DateRange, PageQuery, Recipe as StoredRecipe,
+        RecipeIngredient, RecipeNutrition, Task as StoredTask, Ulid, UpdateTask,
+    },
+    Client,
+};
+use annapurna_data::types::{Ingredient, Quantity, Recipe, Task};
 use dioxus::{prelude::Writable, signals::Signal};
 
 pub const BASE_API_URL: &str = "/api";
-pub const RECIPE_API_URL: &str = "/recipes";
-pub const INGREDIENT_API_URL: &str = "/ingredients";
+/// The key in local storage holding the token sent with API requests.
+pub const TOKEN_STORAGE_KEY: &str = "annapurna_token";
 
-/// Format a relative path to an absolute URL for the API.
-fn format_url(path: &str) -> String {
+/// The page that sends the user to the auth server, which returns them to the login callback.
+pub const LOGIN_URL: &str = "/api/login";
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+/// The token the user signed in with, if they have.
+pub fn token() -> Option<String> {
+    local_storage()?.get_item(TOKEN_STORAGE_KEY).ok().flatten()
+}
+
+/// Remembers the token sent with API requests, or forgets it to sign the user out.
+pub fn set_token(token: Option<&str>) {
+    let Some(storage) = local_storage() else {
+        tracing::error!("local storage is unavailable");
+        return;
+    };
+    let result = match token {
+        Some(token) => storage.set_item(TOKEN_STORAGE_KEY, token),
+        None => storage.remove_item(TOKEN_STORAGE_KEY),
+    };
+    if let Err(error) = result {
+        tracing::error!(?error, "failed to store token");
+    }
+}
+
+/// Create a client for the API served alongside the current page.
+fn client() -> Result<Client> {
     // determine the absolute path to base of the current page from the browser
-    let current_url = web_sys::window().unwrap().location().origin().unwrap();
+    let window = web_sys::window().unwrap();
+    let current_url = window.location().origin().unwrap();
+
+    let client = Client::new(&format!("{current_url}{BASE_API_URL}"))?;
 
-    let url = format!("{}{}{}", current_url, BASE_API_URL, path);
-    url
+    Ok(match token() {
+        Some(token) => client.token(token),
+        None => client,
+    })
 }
 
-/// Retrieves recipes from the API.
-pub async fn get_recipes() -> Result<Vec<Recipe>, reqwest::Error> {
-    let url = format_url(RECIPE_API_URL);
-    reqwest::get(&url).await?.json().await
+/// Converts a recipe in the form the API stores it.
+fn from_stored(recipe: StoredRecipe) -> Recipe {
+    let ingredients = recipe
+        .ingredients
+        .into_iter()
+        .map(
+            |ingredient| match ingredient.quantity.map(|q| q.parse::<Quantity>()) {
+                Some(Ok(amount)) => Ingredient::with_amount(ingredient.ingredient_type, amount),
+                _ => Ingredient::new(ingredient.ingredient_type),
+            },
+        )
+        .collect();
+
+    Recipe::new(recipe.name, ingredients).with_steps(recipe.steps)
+}
+
+/// Converts a recipe to the form the API stores it in.
+fn to_stored(recipe: &Recipe) -> CreateRecipe {
+    let ingredients = recipe
+        .ingredients
+        .iter()
+        .map(|ingredient| RecipeIngredient {
+            ingredient_type: ingredient.name.clone(),
+            quantity: ingredient.amount.as_ref().map(Quantity::to_string),
+        })
+        .collect();
+
+    CreateRecipe {
+        recipe_id: None,
+        name: recipe.name.clone(),
+        servings: None,
+        ingredients,
+        steps: recipe.steps.clone(),
+    }
+}
+
+/// Retrieves every recipe from the API.
+pub async fn get_recipes() -> Result<Vec<Recipe>> {
+    let client = client()?;
+    let mut query = PageQuery::default();
+    let mut recipes = Vec::new();
+    loop {
+        let page = client.list_recipes(&query).await?;
+        recipes.extend(page.items.into_iter().map(from_stored));
+        match page.next {
+            Some(next) => query.after = Some(next),
+            None => return Ok(recipes),
+        }
+    }
+}
+
+/// Stores a new recipe using the API.
+pub async fn create_recipe(recipe: &Recipe) -> Result<()> {
+    client()?.create_recipe(&to_stored(recipe)).await?;
+    Ok(())
+}
+
+/// Deletes a stored recipe using the API.
+pub async fn delete_recipe(name: &str) -> Result<()> {
+    client()?.delete_recipe(name).await
+}
+
+/// Retrieves the nutrition of a serving of a stored recipe from the API.
+pub async fn get_recipe_nutrition(name: &str) -> Result<RecipeNutrition> {
+    client()?.recipe_nutrition(name).await
 }
 
 /// Retrieves recipes from the API and updates the app state.
 pub async fn resolve_recipes(mut app_state: Signal<AppState>) {
-    let recipes = get_recipes().await.unwrap();
-    app_state.write().recipes = recipes;
+    match get_recipes().await {
+        Ok(recipes) => app_state.write().recipes = recipes,
+        Err(error) => tracing::error!(?error, "failed to load recipes"),
+    }
 }
 
 /// Retrieves ingredients from the API.
-pub async fn get_ingredients() -> Result<Vec<Ingredient>, reqwest::Error> {
-    let url = format_url(INGREDIENT_API_URL);
-    reqwest::get(&url).await?.json().await
+pub async fn get_ingredients() -> Result<Vec<Ingredient>> {
+    client()?.ingredients().await
 }
 
 /// Retrieves ingredients from the API and updates the app state.
 pub async fn resolve_ingredients(mut app_state: Signal<AppState>) {
-    let ingredients = get_ingredients().await.unwrap();
-    app_state.write().ingredients = ingredients;
+    match get_ingredients().await {
+        Ok(ingredients) => app_state.write().ingredients = ingredients,
+        Err(error) => tracing::error!(?error, "failed to load ingredients"),
+    }
+}
+
+/// Converts a task in the form the API stores it.
+pub fn task_from_stored(task: &StoredTask) -> Task {
+    Task {
+        description: task.description.clone(),
+        completed: task.completed,
+        duration: task.duration,
+        start_time: task.start_time.map(|start_time| start_time.time()),
+        completion_time: task.completion_time,
+        recurrence: task.recurrence.clone(),
+    }
+}
+
+/// Retrieves every task of the todo list from the API.
+pub async fn get_tasks() -> Result<Vec<StoredTask>> {
+    let client = client()?;
+    let mut query = PageQuery::default();
+    let mut tasks = Vec::new();
+    loop {
+        let page = client.list_tasks(&query).await?;
+        tasks.extend(page.items);
+        match page.next {
+            Some(next) => query.after = Some(next),
+            None => return Ok(tasks),
+        }
+    }
+}
+
+/// Retrieves the todo list from the API and updates the app state.
+pub async fn resolve_tasks(mut app_state: Signal<AppState>) {
+    match get_tasks().await {
+        Ok(tasks) => app_state.write().todo = tasks,
+        Err(error) => tracing::error!(?error, "failed to load tasks"),
+    }
+}
+
+/// Stores a new task using the API.
+/// The start time is taken to be today's, in UTC.
+pub async fn create_task(task: &Task) -> Result<StoredTask> {
+    let today = chrono::Utc::now().date_naive();
+    let task = CreateTask {
+        task_id: None,
+        description: task.description.clone(),
+        duration: task.duration,
+        start_time: task
+            .start_time
+            .map(|start_time| today.and_time(start_time).and_utc()),
+        recurrence: task.recurrence.clone(),
+    };
+
+    client()?.create_task(&task).await
+}
+
+/// Marks a stored task as done using the API.
+/// For recurring tasks, this completes the earliest of today's occurrences that is not done yet.
+pub async fn complete_task(task: &StoredTask) -> Result<StoredTask> {
+    let client = client()?;
+    if task.recurrence.is_none() {
+        let changes = UpdateTask {
+            completed: Some(true),
+            ..Default::default()
+        };
+        return client.update_task(task.task_id, &changes).await;
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let range = DateRange {
+        from: Some(today),
+        until: Some(today),
+    };
+    let due = client
+        .list_occurrences(&range)
+        .await?
+        .into_iter()
+        .find(|occurrence| occurrence.task_id == task.task_id && !occurrence.completed);
+    if let Some(occurrence) = due {
+        let occurrence = CompleteOccurrence {
+            date: occurrence.date,
+            time: occurrence.time,
+            completed: true,
+        };
+        client
+            .complete_occurrence(task.task_id, &occurrence)
+            .await?;
+    }
+
+    client.get_task(task.task_id).await
+}
+
+/// Deletes a stored task using the API.
+pub async fn delete_task(task_id: Ulid) -> Result<()> {
+    client()?.delete_task(task_id).await
+}
+
+/// Adds the server's starting todo list using the API, returning the tasks that were missing.
+pub async fn import_tasks() -> Result<Vec<StoredTask>> {
+    client()?.import_tasks().await
 }
@@ -1,4 +1,7 @@
-use annapurna_data::types::{Ingredient, Recipe as RecipeData, Task as TaskData};
+use crate::api::get_recipe_nutrition;
+use annapurna_data::types::{
+    Ingredient, Recipe as RecipeData, Recurrence, Repeat, Task as TaskData,
+};
 use dioxus::prelude::*;
 use std::{collections::HashSet, time::Duration};
 use wasm_bindgen::JsValue;
@@ -11,6 +14,10 @@ pub(crate) struct RecipeProps {
 
 #[allow(non_snake_case)]
 pub(crate) fn Recipe(props: RecipeProps) -> Element {
+    let nutrition = use_resource(use_reactive((&props.name,), |(name,)| async move {
+        get_recipe_nutrition(&name).await
+    }));
+
     rsx! {
         div {
             h3 { "{props.name}" }
@@ -22,6 +29,32 @@ pub(crate) fn Recipe(props: RecipeProps) -> Element {
                     })}
                 }
             }
+            match &*nutrition.read() {
+                Some(Ok(nutrition)) => {
+                    let per_serving = nutrition.per_serving;
+                    rsx! {
+                        div {
+                            h4 { "Nutrition per serving" }
+                            ul {
+                                li { "{per_serving.calories:.0} kcal" }
+                                li { "{per_serving.protein:.1} g protein" }
+                                li { "{per_serving.carbohydrates:.1} g carbohydrates, {per_serving.sugar:.1} g sugar" }
+                                li { "{per_serving.fat:.1} g fat, {per_serving.saturated_fat:.1} g saturated" }
+                                li { "{per_serving.fiber:.1} g fiber" }
+                                li { "{per_serving.sodium:.0} mg sodium" }
+                            }
+                            if !nutrition.unknown.is_empty() {
+                                p { "Not counted: {nutrition.unknown.join(\", \")}" }
+                            }
+                        }
+                    }
+                }
+                Some(Err(error)) => {
+                    tracing::warn!(?error, "failed to load the nutrition of {}", props.name);
+                    None
+                }
+                None => None,
+            }
         }
     }
 }
@@ -89,10 +122,10 @@ pub(crate) fn RecipeCreate(props: CreateFormProps<RecipeData>) -> Element {
             }
             button {
                 onclick: move |_| {
-                    let recipe = annapurna_data::types::Recipe {
-                        name: name().clone(),
-                        ingredients: ingredients.read().iter().cloned().map(Ingredient::new).collect(),
-                    };
+                    let recipe = RecipeData::new(
+                        name().clone(),
+                        ingredients.read().iter().cloned().map(Ingredient::new).collect(),
+                    );
                     props.on_create.call(recipe);
                 },
                 "create"
@@ -124,9 +157,7 @@ pub(crate) fn IngredientCreate(props: CreateFormProps<Ingredient>) -> Element {
 
             button {
                 onclick: move |_| {
-                    let ingredient = annapurna_data::types::Ingredient {
-                        name: name(),
-                    };
+                    let ingredient = Ingredient::new(name());
                     props.on_create.call(ingredient);
                 },
                 "create"
@@ -159,9 +190,7 @@ pub(crate) fn InventoryCreate(props: CreateFormProps<Ingredient>) -> Element {
 
             button {
                 onclick: move |_| {
-                    let ingredient = annapurna_data::types::Ingredient {
-                        name: name(),
-                    };
+                    let ingredient = Ingredient::new(name());
                     props.on_create.call(ingredient);
                 },
                 "create"
@@ -179,6 +208,7 @@ pub(crate) fn TaskCreate(props: CreateFormProps<TaskData>) -> Element {
     let mut description = use_signal(|| "".to_string());
     let mut duration = use_signal(|| "".to_string());
     let mut start_time = use_signal(|| "".to_string());
+    let mut repeat = use_signal(|| "".to_string());
 
     rsx! {
         div {
@@ -209,6 +239,16 @@ pub(crate) fn TaskCreate(props: CreateFormProps<TaskData>) -> Element {
                 oninput: move |event| start_time.set(event.value().clone()),
             }
 
+            label { r#for: "repeat", "Repeats" }
+            select {
+                id: "repeat",
+                value: "{repeat}",
+                oninput: move |event| repeat.set(event.value().clone()),
+                option { value: "", "never" }
+                option { value: "daily", "daily" }
+                option { value: "weekdays", "on weekdays" }
+            }
+
             button {
                 onclick: move |_| {
                     let duration = if duration().is_empty() {
@@ -225,12 +265,24 @@ pub(crate) fn TaskCreate(props: CreateFormProps<TaskData>) -> Element {
                         Some(start_time().parse().ok().unwrap())
                     };
 
+                    // recurring tasks are due at the start time on every day they occur
+                    let recurrence = match repeat().as_str() {
+                        "daily" => Some(Repeat::Daily),
+                        "weekdays" => Some(Repeat::Weekdays),
+                        _ => None,
+                    }
+                    .map(|repeat| Recurrence {
+                        repeat,
+                        times: start_time.into_iter().collect(),
+                    });
+
                     let task = TaskData {
                         description: description(),
                         completed: false,
                         duration,
                         start_time,
                         completion_time: None,
+                        recurrence,
                     };
                     props.on_create.call(task);
                 },
@@ -280,6 +332,19 @@ pub fn TodoTask(props: TodoTaskProps) -> Element {
                 rsx!{}
             }}
 
+            {if let Some(recurrence) = &props.task.recurrence {
+                let repeat = match recurrence.repeat {
+                    Repeat::Daily => "daily".to_string(),
+                    Repeat::Weekdays => "on weekdays".to_string(),
+                    Repeat::Every { days, .. } => format!("every {days} days"),
+                };
+                rsx!{
+                    label { "Repeats {repeat}" }
+                }
+            } else {
+                rsx!{}
+            }}
+
             {if let Some(start_time) = props.task.start_time {
                 rsx!{
                     label { r#for: "start_time", "Start Time" }
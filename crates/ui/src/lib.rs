@@ -1,5 +1,5 @@
 use crate::{
-    api::{resolve_ingredients, resolve_recipes},
+    api::{resolve_ingredients, resolve_recipes, resolve_tasks},
     components::Datalist,
     routing::Route,
     state::AppState,
@@ -29,6 +29,7 @@ fn app() -> Element {
     let app_state = use_context_provider(|| Signal::new(AppState::default()));
     use_future(move || resolve_recipes(app_state));
     use_future(move || resolve_ingredients(app_state));
+    use_future(move || resolve_tasks(app_state));
 
     rsx! {
         Router::<Route> { }
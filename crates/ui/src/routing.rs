@@ -1,9 +1,13 @@
 use crate::{
+    api::{
+        self, complete_task, create_recipe, create_task, delete_recipe, delete_task, import_tasks,
+        task_from_stored,
+    },
     components::{IngredientCreate, InventoryCreate, Recipe, RecipeCreate, TaskCreate, TodoTask},
     state::AppState,
     util,
 };
-use annapurna_data::types::Ingredient;
+use annapurna_data::types::{Ingredient, Recipe as RecipeData};
 use annapurna_logic::recipe::RecipeResult;
 use dioxus::prelude::*;
 use dioxus_router::prelude::*;
@@ -27,6 +31,8 @@ pub(crate) enum Route {
     AppTodo {},
     #[route("/debug")]
     DebugPage {},
+    #[route("/login-callback?:token")]
+    LoginCallback { token: String },
 }
 
 #[allow(non_snake_case)]
@@ -100,6 +106,22 @@ pub(crate) fn Nav() -> Element {
                 to: Route::DebugPage {},
                 "debug"
             }
+            if api::token().is_some() {
+                a {
+                    class: "navlink",
+                    onclick: |_| {
+                        api::set_token(None);
+                        reload("/");
+                    },
+                    "log out"
+                }
+            } else {
+                a {
+                    class: "navlink",
+                    href: api::LOGIN_URL,
+                    "log in"
+                }
+            }
         }
 
         Outlet::<Route> { }
@@ -119,6 +141,37 @@ pub(crate) fn Index() -> Element {
     }
 }
 
+/// Loads the page afresh, so everything fetched from the API is fetched again as the current user.
+fn reload(path: &str) {
+    if let Err(error) = web_sys::window().unwrap().location().set_href(path) {
+        tracing::error!(?error, "failed to reload");
+    }
+}
+
+/// Where the auth server returns the user to after they log in, with the token for the API.
+#[allow(non_snake_case)]
+#[component]
+pub(crate) fn LoginCallback(token: String) -> Element {
+    let logged_in = !token.is_empty();
+    use_effect(move || {
+        if logged_in {
+            api::set_token(Some(&token));
+            reload("/app");
+        }
+    });
+
+    if logged_in {
+        rsx! {
+            p { "Logging in..." }
+        }
+    } else {
+        rsx! {
+            p { "Logging in failed." }
+            a { href: api::LOGIN_URL, "try again" }
+        }
+    }
+}
+
 #[allow(non_snake_case)]
 pub(crate) fn AppIndex() -> Element {
     rsx! {
@@ -151,9 +204,14 @@ pub(crate) fn AppRecipes() -> Element {
             if creating_recipe() {
                 {rsx! {
                     RecipeCreate {
-                        on_create: move |recipe| {
-                            app_state.write().add_recipe(recipe);
+                        on_create: move |recipe: RecipeData| {
                             creating_recipe.set(false);
+                            spawn(async move {
+                                match create_recipe(&recipe).await {
+                                    Ok(()) => app_state.write().add_recipe(recipe),
+                                    Err(error) => tracing::error!(?error, "failed to create recipe"),
+                                }
+                            });
                         },
                         on_cancel: move |_| creating_recipe.set(false),
                     }
@@ -169,7 +227,13 @@ pub(crate) fn AppRecipes() -> Element {
                         }
                         button {
                             onclick: move |_| {
-                                app_state().remove_recipe(&recipe.name.clone());
+                                let name = recipe.name.clone();
+                                spawn(async move {
+                                    match delete_recipe(&name).await {
+                                        Ok(()) => app_state.write().remove_recipe(&name),
+                                        Err(error) => tracing::error!(?error, "failed to delete recipe"),
+                                    }
+                                });
                             },
                             "remove"
                         }
@@ -355,6 +419,20 @@ pub(crate) fn AppLogicViewer() -> Element {
                         }
                     }
 
+                    h3 { "prepare these intermediates first" }
+                    ul {
+                        for (recipe, chain) in result.production_chains.iter() {
+                            li {
+                                h4 { "{recipe}" }
+                                ol {
+                                    for intermediate in chain.iter().cloned() {
+                                        li { "{intermediate}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
                     h3 { "missing items for these recipes" }
                     ul {
                         for (recipe, ingredients) in result.missing.iter() {
@@ -387,10 +465,22 @@ pub(crate) fn AppTodo() -> Element {
                 onclick: move |_| creating_todo.set(true),
                 "add todo"
             }
+            button {
+                onclick: move |_| {
+                    spawn(async move {
+                        match import_tasks().await {
+                            Ok(tasks) => app_state.write().add_todo(tasks),
+                            Err(error) => tracing::error!(?error, "failed to import tasks"),
+                        }
+                    });
+                },
+                "import todos"
+            }
             button {
                 onclick: move |_| {
                     let filename = "todo.ron";
-                    let text = ron::ser::to_string_pretty(&app_state().todo, Default::default()).unwrap();
+                    let todo = app_state().todo.iter().map(task_from_stored).collect::<Vec<_>>();
+                    let text = ron::ser::to_string_pretty(&todo, Default::default()).unwrap();
                     util::download_string(filename, &text).expect("failed to download");
                 },
                 "export todos"
@@ -400,8 +490,13 @@ pub(crate) fn AppTodo() -> Element {
                 {rsx! {
                     TaskCreate {
                         on_create: move |todo| {
-                            app_state.write().add_todo(todo);
                             creating_todo.set(false);
+                            spawn(async move {
+                                match create_task(&todo).await {
+                                    Ok(task) => app_state.write().add_todo([task]),
+                                    Err(error) => tracing::error!(?error, "failed to create task"),
+                                }
+                            });
                         },
                         on_cancel: move |_| creating_todo.set(false),
                     }
@@ -409,19 +504,31 @@ pub(crate) fn AppTodo() -> Element {
             }
 
             {app_state().todo.iter().cloned().map(move |todo| {
-                let cloned_todo = todo.clone();
+                let task_id = todo.task_id;
+                let stored = todo.clone();
                 rsx! {
                    div {
-                       key: "{todo.description}",
+                       key: "{task_id}",
                        TodoTask {
-                           task: todo.clone(),
+                           task: task_from_stored(&todo),
                            on_complete: move |_| {
-                               app_state.write().complete_todo(cloned_todo.clone());
+                               let stored = stored.clone();
+                               spawn(async move {
+                                   match complete_task(&stored).await {
+                                       Ok(task) => app_state.write().update_todo(task),
+                                       Err(error) => tracing::error!(?error, "failed to complete task"),
+                                   }
+                               });
                            },
                        }
                        button {
                            onclick: move |_| {
-                               app_state.write().remove_todo(todo.clone());
+                               spawn(async move {
+                                   match delete_task(task_id).await {
+                                       Ok(()) => app_state.write().remove_todo(task_id),
+                                       Err(error) => tracing::error!(?error, "failed to delete task"),
+                                   }
+                               });
                            },
                            "remove"
                        }
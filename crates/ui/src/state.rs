@@ -1,4 +1,5 @@
-use annapurna_data::types::{Ingredient, Recipe, Task as TaskData};
+use annapurna_client::types::{Task, Ulid};
+use annapurna_data::types::{Ingredient, Recipe};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
@@ -6,7 +7,7 @@ pub(crate) struct AppState {
     pub recipes: Vec<Recipe>,
     pub ingredients: Vec<Ingredient>,
     pub inventory: Vec<Ingredient>,
-    pub todo: Vec<TaskData>,
+    pub todo: Vec<Task>,
 }
 
 impl AppState {
@@ -38,19 +39,18 @@ impl AppState {
         self.inventory = ingredients;
     }
 
-    pub fn add_todo(&mut self, item: TaskData) {
-        self.todo.push(item);
+    pub fn add_todo(&mut self, items: impl IntoIterator<Item = Task>) {
+        self.todo.extend(items);
     }
 
-    pub fn remove_todo(&mut self, todo: TaskData) {
-        self.todo.retain(|t| *t != todo);
+    pub fn remove_todo(&mut self, task_id: Ulid) {
+        self.todo.retain(|t| t.task_id != task_id);
     }
 
-    pub fn complete_todo(&mut self, todo: TaskData) {
-        self.todo.iter_mut().for_each(|t| {
-            if *t == todo {
-                t.completed = true;
-            }
-        });
+    /// Replaces the stored version of a task with the one given.
+    pub fn update_todo(&mut self, task: Task) {
+        if let Some(t) = self.todo.iter_mut().find(|t| t.task_id == task.task_id) {
+            *t = task;
+        }
     }
 }
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS shopping_list_item;
+DROP TABLE IF EXISTS shopping_list;
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS shopping_list (
+    shopping_list_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    name text NOT NULL,
+    recipes text[] NOT NULL DEFAULT '{}',
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS shopping_list_item (
+    shopping_list_item_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    shopping_list_id ulid NOT NULL REFERENCES shopping_list(shopping_list_id) ON DELETE CASCADE,
+    ingredient_type text NOT NULL,
+    quantity text,
+    checked boolean NOT NULL DEFAULT false
+);
+
+CREATE INDEX IF NOT EXISTS shopping_list_item_shopping_list_id_idx ON shopping_list_item(shopping_list_id);
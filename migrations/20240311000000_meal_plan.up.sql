@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS meal_plan (
+    meal_plan_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    date date NOT NULL,
+    meal text NOT NULL,
+    recipe text NOT NULL,
+    servings integer NOT NULL DEFAULT 1 CHECK (servings > 0),
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS meal_plan_date_idx ON meal_plan(date);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS cooking_log (
+    cooking_log_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    recipe text NOT NULL,
+    servings integer NOT NULL CHECK (servings > 0),
+    cooked_at timestamptz NOT NULL DEFAULT now()
+);
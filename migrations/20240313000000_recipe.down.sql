@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS recipe_ingredient;
+DROP TABLE IF EXISTS recipe;
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS recipe (
+    recipe_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    name text NOT NULL UNIQUE,
+    servings integer NOT NULL DEFAULT 1 CHECK (servings > 0),
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS recipe_ingredient (
+    recipe_ingredient_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    recipe_id ulid NOT NULL REFERENCES recipe(recipe_id) ON DELETE CASCADE,
+    position integer NOT NULL,
+    ingredient_type text NOT NULL,
+    quantity text
+);
+
+CREATE INDEX IF NOT EXISTS recipe_ingredient_recipe_id_idx ON recipe_ingredient(recipe_id);
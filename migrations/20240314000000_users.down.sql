@@ -0,0 +1,6 @@
+ALTER TABLE cooking_log DROP COLUMN IF EXISTS owner_id;
+ALTER TABLE meal_plan DROP COLUMN IF EXISTS owner_id;
+ALTER TABLE shopping_list DROP COLUMN IF EXISTS owner_id;
+ALTER TABLE inventory DROP COLUMN IF EXISTS owner_id;
+
+DROP TABLE IF EXISTS users;
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS users (
+    user_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    identifier text NOT NULL UNIQUE,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+-- rows created before users existed have no owner, and are not visible to anyone
+ALTER TABLE inventory ADD COLUMN IF NOT EXISTS owner_id ulid REFERENCES users(user_id) ON DELETE CASCADE;
+ALTER TABLE shopping_list ADD COLUMN IF NOT EXISTS owner_id ulid REFERENCES users(user_id) ON DELETE CASCADE;
+ALTER TABLE meal_plan ADD COLUMN IF NOT EXISTS owner_id ulid REFERENCES users(user_id) ON DELETE CASCADE;
+ALTER TABLE cooking_log ADD COLUMN IF NOT EXISTS owner_id ulid REFERENCES users(user_id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS inventory_owner_id_idx ON inventory(owner_id);
+CREATE INDEX IF NOT EXISTS shopping_list_owner_id_idx ON shopping_list(owner_id);
+CREATE INDEX IF NOT EXISTS meal_plan_owner_id_idx ON meal_plan(owner_id);
+CREATE INDEX IF NOT EXISTS cooking_log_owner_id_idx ON cooking_log(owner_id);
@@ -0,0 +1,7 @@
+ALTER TABLE recipe DROP COLUMN IF EXISTS household_id;
+ALTER TABLE shopping_list DROP COLUMN IF EXISTS household_id;
+ALTER TABLE inventory DROP COLUMN IF EXISTS household_id;
+
+DROP TABLE IF EXISTS household_invitation;
+DROP TABLE IF EXISTS household_member;
+DROP TABLE IF EXISTS household;
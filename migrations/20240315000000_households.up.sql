@@ -0,0 +1,54 @@
+CREATE TABLE IF NOT EXISTS household (
+    household_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    name text NOT NULL,
+    -- every user has a household of their own, which is used when they do not pick another
+    personal_for ulid UNIQUE REFERENCES users(user_id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS household_member (
+    household_id ulid NOT NULL REFERENCES household(household_id) ON DELETE CASCADE,
+    user_id ulid NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    role text NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    created_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (household_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS household_member_user_id_idx ON household_member(user_id);
+
+CREATE TABLE IF NOT EXISTS household_invitation (
+    household_invitation_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    household_id ulid NOT NULL REFERENCES household(household_id) ON DELETE CASCADE,
+    -- invitations are addressed to an identifier, as the user may not have signed in yet
+    identifier text NOT NULL,
+    role text NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    invited_by ulid NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    UNIQUE (household_id, identifier)
+);
+
+CREATE INDEX IF NOT EXISTS household_invitation_identifier_idx ON household_invitation(identifier);
+
+ALTER TABLE inventory ADD COLUMN IF NOT EXISTS household_id ulid REFERENCES household(household_id) ON DELETE CASCADE;
+ALTER TABLE shopping_list ADD COLUMN IF NOT EXISTS household_id ulid REFERENCES household(household_id) ON DELETE CASCADE;
+-- recipes without a household belong to the shared catalogue
+ALTER TABLE recipe ADD COLUMN IF NOT EXISTS household_id ulid REFERENCES household(household_id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS inventory_household_id_idx ON inventory(household_id);
+CREATE INDEX IF NOT EXISTS shopping_list_household_id_idx ON shopping_list(household_id);
+CREATE INDEX IF NOT EXISTS recipe_household_id_idx ON recipe(household_id);
+
+-- move everyone's existing data into their personal household
+INSERT INTO household(household_id, name, personal_for)
+SELECT gen_ulid(), identifier, user_id FROM users
+ON CONFLICT (personal_for) DO NOTHING;
+
+INSERT INTO household_member(household_id, user_id, role)
+SELECT household_id, personal_for, 'owner' FROM household WHERE personal_for IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+UPDATE inventory SET household_id = household.household_id
+FROM household WHERE household.personal_for = inventory.owner_id;
+
+UPDATE shopping_list SET household_id = household.household_id
+FROM household WHERE household.personal_for = shopping_list.owner_id;
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS inventory_household_id_created_at_idx;
+DROP INDEX IF EXISTS inventory_household_id_expiration_date_idx;
+DROP INDEX IF EXISTS inventory_household_id_ingredient_type_idx;
@@ -0,0 +1,4 @@
+-- text_pattern_ops lets prefix matches such as `oil/%` use the index
+CREATE INDEX IF NOT EXISTS inventory_household_id_ingredient_type_idx ON inventory(household_id, ingredient_type text_pattern_ops);
+CREATE INDEX IF NOT EXISTS inventory_household_id_expiration_date_idx ON inventory(household_id, expiration_date);
+CREATE INDEX IF NOT EXISTS inventory_household_id_created_at_idx ON inventory(household_id, created_at);
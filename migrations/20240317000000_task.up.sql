@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS task (
+    task_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    owner_id ulid NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    household_id ulid NOT NULL REFERENCES household(household_id) ON DELETE CASCADE,
+    description text NOT NULL,
+    completed boolean NOT NULL DEFAULT false,
+    duration interval,
+    start_time timestamptz,
+    completion_time timestamptz,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS task_household_id_idx ON task(household_id, task_id);
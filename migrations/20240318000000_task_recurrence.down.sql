@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS task_occurrence;
+
+ALTER TABLE task DROP COLUMN IF EXISTS recurrence;
@@ -0,0 +1,9 @@
+ALTER TABLE task ADD COLUMN IF NOT EXISTS recurrence jsonb;
+
+-- recurring tasks are completed one occurrence at a time, and only completed occurrences are stored
+CREATE TABLE IF NOT EXISTS task_occurrence (
+    task_id ulid NOT NULL REFERENCES task(task_id) ON DELETE CASCADE,
+    scheduled_at timestamptz NOT NULL,
+    completion_time timestamptz NOT NULL,
+    PRIMARY KEY (task_id, scheduled_at)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS recipe_step (
+    recipe_step_id ulid NOT NULL DEFAULT gen_ulid() PRIMARY KEY,
+    recipe_id ulid NOT NULL REFERENCES recipe(recipe_id) ON DELETE CASCADE,
+    position integer NOT NULL,
+    description text NOT NULL,
+    duration interval NOT NULL,
+    -- the positions of the steps to finish first, or null to follow the step before
+    after_steps integer[],
+    UNIQUE (recipe_id, position)
+);
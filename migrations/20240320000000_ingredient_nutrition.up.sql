@@ -0,0 +1,19 @@
+-- nutrients per 100 g: energy in kcal, macronutrients in g and micronutrients in mg
+CREATE TABLE IF NOT EXISTS ingredient_nutrition (
+    ingredient_type text NOT NULL PRIMARY KEY,
+    calories double precision NOT NULL DEFAULT 0,
+    protein double precision NOT NULL DEFAULT 0,
+    fat double precision NOT NULL DEFAULT 0,
+    saturated_fat double precision NOT NULL DEFAULT 0,
+    carbohydrates double precision NOT NULL DEFAULT 0,
+    sugar double precision NOT NULL DEFAULT 0,
+    fiber double precision NOT NULL DEFAULT 0,
+    sodium double precision NOT NULL DEFAULT 0,
+    calcium double precision NOT NULL DEFAULT 0,
+    iron double precision NOT NULL DEFAULT 0,
+    potassium double precision NOT NULL DEFAULT 0,
+    vitamin_c double precision NOT NULL DEFAULT 0,
+    -- the weight of a single piece in grams, for ingredients that are counted
+    piece_weight double precision,
+    updated_at timestamptz NOT NULL DEFAULT now()
+);
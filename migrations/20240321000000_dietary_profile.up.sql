@@ -0,0 +1,7 @@
+-- what each user will not eat, by the names of diets and dietary tags
+CREATE TABLE IF NOT EXISTS dietary_profile (
+    user_id ulid NOT NULL PRIMARY KEY REFERENCES users(user_id) ON DELETE CASCADE,
+    diets text[] NOT NULL DEFAULT '{}',
+    avoid text[] NOT NULL DEFAULT '{}',
+    updated_at timestamptz NOT NULL DEFAULT now()
+);
@@ -0,0 +1,6 @@
+ALTER TABLE cooking_log ALTER COLUMN owner_id DROP NOT NULL;
+ALTER TABLE meal_plan ALTER COLUMN owner_id DROP NOT NULL;
+ALTER TABLE shopping_list ALTER COLUMN household_id DROP NOT NULL;
+ALTER TABLE shopping_list ALTER COLUMN owner_id DROP NOT NULL;
+ALTER TABLE inventory ALTER COLUMN household_id DROP NOT NULL;
+ALTER TABLE inventory ALTER COLUMN owner_id DROP NOT NULL;
@@ -0,0 +1,12 @@
+-- rows created before users existed have no owner or household, and could not be read back
+DELETE FROM inventory WHERE owner_id IS NULL OR household_id IS NULL;
+DELETE FROM shopping_list WHERE owner_id IS NULL OR household_id IS NULL;
+DELETE FROM meal_plan WHERE owner_id IS NULL;
+DELETE FROM cooking_log WHERE owner_id IS NULL;
+
+ALTER TABLE inventory ALTER COLUMN owner_id SET NOT NULL;
+ALTER TABLE inventory ALTER COLUMN household_id SET NOT NULL;
+ALTER TABLE shopping_list ALTER COLUMN owner_id SET NOT NULL;
+ALTER TABLE shopping_list ALTER COLUMN household_id SET NOT NULL;
+ALTER TABLE meal_plan ALTER COLUMN owner_id SET NOT NULL;
+ALTER TABLE cooking_log ALTER COLUMN owner_id SET NOT NULL;
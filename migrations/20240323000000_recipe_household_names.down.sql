@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS recipe_catalogue_name_idx;
+ALTER TABLE recipe DROP CONSTRAINT IF EXISTS recipe_household_id_name_key;
+ALTER TABLE recipe ADD CONSTRAINT recipe_name_key UNIQUE (name);
@@ -0,0 +1,5 @@
+-- a household may name its recipes as it likes, even after a recipe in the shared catalogue
+ALTER TABLE recipe DROP CONSTRAINT IF EXISTS recipe_name_key;
+ALTER TABLE recipe ADD CONSTRAINT recipe_household_id_name_key UNIQUE (household_id, name);
+-- unique constraints treat every null as distinct, so the catalogue needs its own index
+CREATE UNIQUE INDEX IF NOT EXISTS recipe_catalogue_name_idx ON recipe(name) WHERE household_id IS NULL;
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS cooking_log_household_id_idx;
+DROP INDEX IF EXISTS meal_plan_household_id_date_idx;
+
+ALTER TABLE cooking_log DROP COLUMN IF EXISTS household_id;
+ALTER TABLE meal_plan DROP COLUMN IF EXISTS household_id;
@@ -0,0 +1,24 @@
+-- meal plans and cooking logs draw on the household's inventory, so they belong to the household
+ALTER TABLE meal_plan ADD COLUMN IF NOT EXISTS household_id ulid REFERENCES household(household_id) ON DELETE CASCADE;
+ALTER TABLE cooking_log ADD COLUMN IF NOT EXISTS household_id ulid REFERENCES household(household_id) ON DELETE CASCADE;
+
+-- move everyone's existing plans and logs into their personal household
+INSERT INTO household(household_id, name, personal_for)
+SELECT gen_ulid(), identifier, user_id FROM users
+ON CONFLICT (personal_for) DO NOTHING;
+
+INSERT INTO household_member(household_id, user_id, role)
+SELECT household_id, personal_for, 'owner' FROM household WHERE personal_for IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+UPDATE meal_plan SET household_id = household.household_id
+FROM household WHERE household.personal_for = meal_plan.owner_id AND meal_plan.household_id IS NULL;
+
+UPDATE cooking_log SET household_id = household.household_id
+FROM household WHERE household.personal_for = cooking_log.owner_id AND cooking_log.household_id IS NULL;
+
+ALTER TABLE meal_plan ALTER COLUMN household_id SET NOT NULL;
+ALTER TABLE cooking_log ALTER COLUMN household_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS meal_plan_household_id_date_idx ON meal_plan(household_id, date);
+CREATE INDEX IF NOT EXISTS cooking_log_household_id_idx ON cooking_log(household_id);